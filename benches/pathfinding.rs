@@ -0,0 +1,204 @@
+use hoffman_final_project::{
+    balance::Balance,
+    crowd,
+    direction::Direction,
+    enemy::Enemy,
+    entity::Entity,
+    pathfinding::{Footprint, Navigation, Pathfinder},
+    random,
+    tile::{EnemyKind, TileKind},
+    utils::Position,
+    world::World,
+    RNG_SEED, WORLD_SIZE,
+};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::collections::LinkedList;
+
+// Chasers the room is filled with
+const BENCH_ENEMIES: usize = 40;
+
+// Times a turn of every enemy finding its way to the player three ways: the flow fields enemies
+// follow, an A* search for each of them, and the breadth first search Enemy::get_best_path did
+// before both, on the first room of the world filled with chasers all hunting the player in the
+// middle of it. Run with cargo bench
+fn pathfinding(c: &mut Criterion) {
+    let mut world = crowded_room();
+    let enemies = world.enemies_map[world.world_position.y][world.world_position.x].len();
+
+    let mut navigation = Navigation::default();
+    c.bench_function("flow fields", |b| {
+        b.iter(|| {
+            Enemy::update_flow_fields(&world, &mut navigation.flow_fields);
+            crowd::assign_slots(&world, &navigation.flow_fields, &mut navigation.crowd);
+            for index in 0..enemies {
+                Enemy::flow_path(index, &world, &mut navigation);
+            }
+        })
+    });
+
+    let mut pathfinder = Pathfinder::default();
+    c.bench_function("a star", |b| {
+        b.iter(|| {
+            for index in 0..enemies {
+                a_star(&world, &mut pathfinder, index);
+            }
+        })
+    });
+
+    c.bench_function("breadth first", |b| {
+        b.iter(|| {
+            for index in 0..enemies {
+                get_best_path(index, &mut world, false);
+            }
+        })
+    });
+}
+
+criterion_group!(benches, pathfinding);
+criterion_main!(benches);
+
+// The first room of the default world with the player in the middle and BENCH_ENEMIES chasers
+fn crowded_room() -> World {
+    let balance = Balance::default();
+    let mut rng = ChaCha8Rng::seed_from_u64(RNG_SEED);
+    let mut world = World::new(&mut rng, &balance);
+    let room = world.world_position;
+    let middle = Position::new(WORLD_SIZE.0 as usize / 2, WORLD_SIZE.1 as usize / 2);
+    if !world.entity_map[room.y][room.x].contains_key(&middle)
+        && !world.terrain_map[room.y][room.x].contains_key(&middle)
+    {
+        let player = world.player.pos;
+        World::update_position(&mut world, player, (middle, room));
+        world.player.pos = middle;
+    }
+    let mut tries = 0;
+    while world.enemies_map[room.y][room.x].len() < BENCH_ENEMIES && tries < 10000 {
+        tries += 1;
+        let pos = Position::new(
+            random::rand_range(&mut rng, 0, WORLD_SIZE.0) as usize,
+            random::rand_range(&mut rng, 0, WORLD_SIZE.1) as usize,
+        );
+        if world.entity_map[room.y][room.x].contains_key(&pos)
+            || world.terrain_map[room.y][room.x].contains_key(&pos)
+        {
+            continue;
+        }
+        world.enemies_map[room.y][room.x].push(Enemy::chasing(pos.x, pos.y, room, &balance.enemies));
+        world.entity_map[room.y][room.x]
+            .insert(pos, (TileKind::Enemy(EnemyKind::Chaser), Entity::Enemy));
+    }
+    world
+}
+
+// The cheapest path from the enemy to the player, what every enemy searched for on its own
+// between the breadth first search and the flow fields
+fn a_star(world: &World, pathfinder: &mut Pathfinder, index: usize) -> Option<LinkedList<Position>> {
+    let enemy = &world.enemies_map[world.world_position.y][world.world_position.x][index];
+    let (start, room) = (enemy.pos[0], enemy.world_pos);
+    pathfinder.find_path(start, Footprint::SINGLE, &[world.player.pos], |tile| {
+        Enemy::step_cost(world, room, tile, false)
+    })
+}
+
+// Enemy::get_best_path and Enemy::get_neighbors as they were before the A* search replaced them,
+// kept here to compare against
+
+fn get_best_path(
+    index: usize,
+    world: &mut World,
+    can_dodge_projectiles: bool,
+) -> LinkedList<Position> {
+    // Used to check if the enemy should be able to dodge around player projectiles
+
+    let enemy = &world.enemies_map[world.world_position.y][world.world_position.x][index];
+    // this is a visited array to save if we have visited a location on the grid
+    let mut visited = [[false; WORLD_SIZE.0 as usize]; WORLD_SIZE.1 as usize];
+
+    // this stores every location's previous location so that we can reconstruct the best path
+    // given our start and end
+    let mut previous = [[Position::new(WORLD_SIZE.0 as usize + 1, WORLD_SIZE.1 as usize + 1);
+        WORLD_SIZE.0 as usize]; WORLD_SIZE.1 as usize];
+    let mut queue = LinkedList::new();
+    queue.push_back(enemy.pos[0]);
+
+    visited[enemy.pos[0].y][enemy.pos[0].x] = true;
+    while !queue.is_empty() {
+        if let Some(node) = queue.pop_front() {
+            if node == world.player.pos {
+                // reached the goal location, break and reconstruct path
+                break;
+            }
+
+            // standard bfs stuff, for each neighbor, if it hasn't been visited, put it into
+            // the queue
+            let neighbors = get_neighbors(world, node, can_dodge_projectiles, index, Entity::Enemy);
+            for next in neighbors {
+                if !visited[next.y][next.x] {
+                    queue.push_back(next);
+                    visited[next.y][next.x] = true;
+
+                    // mark the previous of the neighbor as the node to reconstruct the path
+                    previous[next.y][next.x] = node;
+                }
+            }
+        }
+    }
+
+    // This uses the previous 2 dimensional array to reconstruct the best path
+    let mut path = LinkedList::new();
+    let mut position = world.player.pos;
+    let enemy_pos = world.enemies_map[world.world_position.y][world.world_position.x][index].pos[0];
+    while position != enemy_pos {
+        path.push_front(position);
+
+        // if the position's or y is greater than the world size, that means that a path wasn't
+        // found, as it means the previous position did not have a previous, so we break out
+        if position.x as i16 >= WORLD_SIZE.0 {
+            break;
+        }
+        position = previous[position.y][position.x];
+    }
+    path
+}
+
+fn get_neighbors(
+    world: &mut World,
+    position: Position,
+    can_dodge_projectiles: bool,
+    index: usize,
+    entity_type: Entity,
+) -> Vec<Position> {
+    let directions = [
+        Direction::North,
+        Direction::South,
+        Direction::West,
+        Direction::East,
+    ];
+    let mut moves = Vec::new();
+
+    // loop through all the directions
+    for direction in directions {
+        let (new_pos, _) =
+            World::new_position(position, direction, world, 1, entity_type.clone(), Some(index));
+        // if the new position is valid(correct tiles & within bounds) add it to the potential
+        // neighbors
+        if new_pos != world.enemies_map[world.world_position.y][world.world_position.x][index].pos[0]
+            && Enemy::can_travel_to(
+                world,
+                (new_pos, world.enemies_map[world.world_position.y][world.world_position.x][index].world_pos),
+                &world.entity_map,
+                &world.terrain_map,
+                &world.atmosphere_map,
+                can_dodge_projectiles,
+            )
+            && world.enemies_map[world.world_position.y][world.world_position.x][index].world_pos
+                == world.world_position
+        {
+            moves.push(new_pos);
+        }
+    }
+    return moves;
+}
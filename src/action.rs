@@ -0,0 +1,24 @@
+use crate::{direction::Direction, utils::Position};
+
+// Everything the player can do in one turn. The engine only ever consumes these, turning key
+// presses and mouse clicks into actions is the job of the input layer in input.rs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Action {
+    // Step one tile without changing the facing direction
+    Move(Direction),
+    // Turn to face a direction without moving
+    Face(Direction),
+    Melee,
+    Slam,
+    // The targeted abilities carry the tile they were aimed at
+    Lightning(Position),
+    Teleport(Position),
+    Build(Position),
+    Fire,
+    Heal,
+    Invisibility,
+    Missile,
+    Projectile,
+    // Do nothing and let the rest of the world take its turn
+    Wait,
+}
@@ -0,0 +1,275 @@
+use crate::{
+    direction::Direction,
+    enemy::Enemy,
+    pathfinding::{Footprint, Navigation},
+    random,
+    tile::{EnemyKind, TerrainKind},
+    utils::Position,
+    world::World,
+};
+
+use rand_chacha::ChaCha8Rng;
+
+/*
+ * What every enemy is up to. Enemies only go after the player once they have seen them, and lose
+ * them again when they can't:
+ *
+ *      Idle/Wander/Patrol --sees the player--> Alert --still sees them--> Chase
+ *      Chase --loses sight--> Search (walks to where it last saw the player) --gives up--> Wander
+ *      Wander --after a while--> Idle, or Patrol for knights
+ *      Chase --badly hurt--> Flee, until it can't see the player anymore
+ *
+ * An enemy sees the player when they aren't invisible, are within balance.enemies.sight_radius
+ * tiles and no wall or mountain stands between them. Alert is the one turn an enemy stands still
+ * after noticing the player, which is the player's chance to get out of sight again
+ */
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum AiState {
+    // Stands where it is
+    #[default]
+    Idle,
+    // Takes random steps around where it is
+    Wander,
+    // Walks back and forth along the way it is facing
+    Patrol,
+    // Just noticed the player
+    Alert,
+    // Goes after the player, see Enemy::move_enemy
+    Chase,
+    // Heads for where it last saw the player
+    Search,
+    // Runs from the player
+    Flee,
+}
+
+// What an enemy goes back to once it has nothing better to do
+fn resting(kind: EnemyKind) -> AiState {
+    match kind {
+        EnemyKind::Knight => AiState::Patrol,
+        _ => AiState::Idle,
+    }
+}
+
+// Only the ones that wouldn't survive another fight run, the rest fight to the end
+fn flees(kind: EnemyKind) -> bool {
+    matches!(kind, EnemyKind::Chaser | EnemyKind::Shooter)
+}
+
+// The state an enemy starts in when it is made. Enemies of a boss fight are there to fight, there
+// is no sneaking around them
+pub fn initial_state(kind: EnemyKind, boss: bool) -> AiState {
+    if boss || kind == EnemyKind::MajorBoss {
+        AiState::Chase
+    } else {
+        resting(kind)
+    }
+}
+
+// Whether anything at pos sees the player
+pub fn sees_player(world: &World, room: Position, pos: Position) -> bool {
+    let player = world.player.pos;
+    if room != world.world_position || !world.player.is_visible() {
+        return false;
+    }
+    let (delta_x, delta_y) = (
+        player.x as i32 - pos.x as i32,
+        player.y as i32 - pos.y as i32,
+    );
+    let radius = world.balance.enemies.sight_radius as i32;
+    if delta_x * delta_x + delta_y * delta_y > radius * radius {
+        return false;
+    }
+    line_of_sight(world, room, pos, player)
+}
+
+// Walks the line between the two tiles, Bresenham style, and checks nothing in between blocks the
+// view. Only walls and mountains do, everything else can be seen over
+fn line_of_sight(world: &World, room: Position, from: Position, to: Position) -> bool {
+    let (mut x, mut y) = (from.x as i32, from.y as i32);
+    let (end_x, end_y) = (to.x as i32, to.y as i32);
+    let (delta_x, delta_y) = ((end_x - x).abs(), -(end_y - y).abs());
+    let (step_x, step_y) = ((end_x - x).signum(), (end_y - y).signum());
+    let mut error = delta_x + delta_y;
+    loop {
+        if (x, y) == (end_x, end_y) {
+            return true;
+        }
+        if (x, y) != (from.x as i32, from.y as i32) {
+            let tile = Position::new(x as usize, y as usize);
+            if matches!(
+                world.terrain_map[room.y][room.x].get(&tile),
+                Some(TerrainKind::Wall | TerrainKind::Mountain(_))
+            ) {
+                return false;
+            }
+        }
+        let double = 2 * error;
+        if double >= delta_y {
+            error += delta_y;
+            x += step_x;
+        }
+        if double <= delta_x {
+            error += delta_x;
+            y += step_y;
+        }
+    }
+}
+
+// Looks for the player and moves the enemy's state on a turn, before it acts
+pub fn perceive(world: &mut World, index: usize, rng: &mut ChaCha8Rng) {
+    let balance = &world.balance.enemies;
+    let enemy = &world.enemies_map[world.world_position.y][world.world_position.x][index];
+    let sees = sees_player(world, enemy.world_pos, enemy.pos[0]);
+    // elites stand their ground
+    let hurt = !enemy.is_elite()
+        && enemy.health() as f32 <= balance.health(enemy.kind) as f32 * balance.flee_health;
+    let (kind, state, turns) = (enemy.kind, enemy.ai, enemy.ai_turns);
+
+    let (state, turns) = if enemy.is_boss() || kind == EnemyKind::MajorBoss {
+        (AiState::Chase, 0)
+    } else if sees {
+        match state {
+            _ if flees(kind) && hurt => (AiState::Flee, 0),
+            AiState::Idle | AiState::Wander | AiState::Patrol => {
+                (AiState::Alert, balance.alert_turns)
+            }
+            AiState::Alert if turns > 0 => (AiState::Alert, turns),
+            _ => (AiState::Chase, 0),
+        }
+    } else {
+        match state {
+            AiState::Alert | AiState::Chase => (AiState::Search, balance.search_turns),
+            // got away, and isn't going to go looking for more
+            AiState::Flee => (AiState::Wander, balance.wander_turns),
+            AiState::Search | AiState::Wander if turns > 0 => (state, turns),
+            AiState::Search => (AiState::Wander, balance.wander_turns),
+            AiState::Wander => (resting(kind), 0),
+            AiState::Idle if random::bernoulli(rng, balance.wander_chance) => {
+                (AiState::Wander, balance.wander_turns)
+            }
+            AiState::Idle | AiState::Patrol => (state, 0),
+        }
+    };
+
+    let player = world.player.pos;
+    let enemy = &mut world.enemies_map[world.world_position.y][world.world_position.x][index];
+    enemy.last_seen = if sees {
+        Some(player)
+    } else if state == AiState::Search {
+        enemy.last_seen
+    } else {
+        None
+    };
+    enemy.ai = state;
+    enemy.ai_turns = turns.saturating_sub(1);
+}
+
+// One action of an enemy that isn't chasing the player. Enemies bigger than a tile stay put until
+// they do
+pub fn act(world: &mut World, index: usize, navigation: &mut Navigation, rng: &mut ChaCha8Rng) {
+    let enemy = &world.enemies_map[world.world_position.y][world.world_position.x][index];
+    if enemy.pos.len() != 1 {
+        return;
+    }
+    let (pos, state) = (enemy.pos[0], enemy.ai);
+    match state {
+        AiState::Idle | AiState::Alert | AiState::Chase => {}
+        AiState::Wander => {
+            let direction = DIRECTIONS[random::rand_range(rng, 0, 4) as usize];
+            step(world, index, direction);
+        }
+        AiState::Patrol => {
+            // turns around at whatever stops it
+            let direction = enemy.direction;
+            if !step(world, index, direction) {
+                world.enemies_map[world.world_position.y][world.world_position.x][index].direction =
+                    opposite(direction);
+            }
+        }
+        AiState::Search => {
+            let target = match enemy.last_seen {
+                Some(target) if target != pos => target,
+                // got there and the player is gone, it gives up on its next turn
+                _ => {
+                    world.enemies_map[world.world_position.y][world.world_position.x][index]
+                        .ai_turns = 0;
+                    return;
+                }
+            };
+            let can_dodge_projectiles = enemy.can_dodge_projectiles();
+            let room = enemy.world_pos;
+            let path = navigation.pathfinder.find_path(pos, Footprint::SINGLE, &[target], |tile| {
+                Enemy::step_cost(world, room, tile, can_dodge_projectiles)
+            });
+            match path.and_then(|path| path.front().copied()) {
+                Some(next) => {
+                    if let Some(direction) = direction_to(pos, next) {
+                        step(world, index, direction);
+                    }
+                }
+                // there is no getting there, so it gives up
+                None => {
+                    world.enemies_map[world.world_position.y][world.world_position.x][index]
+                        .ai_turns = 0;
+                }
+            }
+        }
+        AiState::Flee => {
+            let room = enemy.world_pos;
+            let can_dodge_projectiles = enemy.can_dodge_projectiles();
+            let next = navigation.flow_fields.ground.step_away(pos, |tile| {
+                Enemy::step_cost(world, room, tile, can_dodge_projectiles)
+                    .filter(|_| tile != world.player.pos)
+            });
+            if let Some(direction) = next.and_then(|next| direction_to(pos, next)) {
+                step(world, index, direction);
+            }
+        }
+    }
+}
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::North,
+    Direction::South,
+    Direction::West,
+    Direction::East,
+];
+
+fn opposite(direction: Direction) -> Direction {
+    match direction {
+        Direction::North => Direction::South,
+        Direction::South => Direction::North,
+        Direction::West => Direction::East,
+        Direction::East => Direction::West,
+    }
+}
+
+fn direction_to(from: Position, to: Position) -> Option<Direction> {
+    match (to.x as i32 - from.x as i32, to.y as i32 - from.y as i32) {
+        (0, -1) => Some(Direction::North),
+        (0, 1) => Some(Direction::South),
+        (-1, 0) => Some(Direction::West),
+        (1, 0) => Some(Direction::East),
+        _ => None,
+    }
+}
+
+// Moves the enemy a tile that way if it is free, never onto the player. Returns if it moved
+fn step(world: &mut World, index: usize, direction: Direction) -> bool {
+    let enemy = &world.enemies_map[world.world_position.y][world.world_position.x][index];
+    let (pos, room) = (enemy.pos[0], enemy.world_pos);
+    let new_pos = match Enemy::next_tile(pos, direction) {
+        Some(new_pos) => new_pos,
+        None => return false,
+    };
+    if world.entity_map[room.y][room.x].contains_key(&new_pos)
+        || Enemy::step_cost(world, room, new_pos, enemy.can_dodge_projectiles()).is_none()
+    {
+        return false;
+    }
+    World::update_position(world, pos, (new_pos, room));
+    let enemy = &mut world.enemies_map[world.world_position.y][world.world_position.x][index];
+    enemy.pos[0] = new_pos;
+    enemy.direction = direction;
+    true
+}
@@ -0,0 +1,141 @@
+use crate::{
+    game::Game,
+    save::{self, SaveError},
+    slot::{self, SlotMeta},
+};
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Name of the autosave settings inside the save directory
+const CONFIG_FILE: &'static str = "autosave.ron";
+
+// When to autosave, read from autosave.ron in the save directory. The file is written with the
+// defaults the first time the game starts so it can be edited
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct AutosaveConfig {
+    pub enabled: bool,
+    // Autosave every this many turns, 0 to never autosave on a timer
+    pub every_turns: usize,
+    // Autosave whenever the player walks into another room
+    pub on_room_change: bool,
+    // Autosave right after a boss is defeated
+    pub on_boss_kill: bool,
+    // How many autosaves each slot keeps, the oldest one is overwritten first
+    pub ring_size: usize,
+}
+
+impl Default for AutosaveConfig {
+    fn default() -> Self {
+        AutosaveConfig {
+            enabled: true,
+            every_turns: 100,
+            on_room_change: true,
+            on_boss_kill: true,
+            ring_size: 3,
+        }
+    }
+}
+
+impl AutosaveConfig {
+    // Reads the settings, falling back to the defaults (and writing them out) if there are none
+    pub fn load(save_dir: &Path) -> Self {
+        let path = save_dir.join(CONFIG_FILE);
+        match fs::read_to_string(&path) {
+            Ok(config_str) => match ron::from_str(&config_str) {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("Couldn't parse {}, using the defaults: {}", path.display(), e);
+                    AutosaveConfig::default()
+                }
+            },
+            Err(_) => {
+                let config = AutosaveConfig::default();
+                let serialized =
+                    ron::ser::to_string_pretty(&config, ron::ser::PrettyConfig::default());
+                if let (Ok(serialized), Ok(())) = (serialized, fs::create_dir_all(save_dir)) {
+                    save::write_atomic(&path, serialized.as_bytes()).ok();
+                }
+                config
+            }
+        }
+    }
+
+    // If the turn that was just played should be autosaved
+    pub fn should_autosave(&self, game: &Game) -> bool {
+        self.enabled
+            && self.ring_size > 0
+            && game.world.player.is_alive()
+            && ((self.on_room_change && game.events.room_changed)
+                || (self.on_boss_kill && game.events.boss_killed)
+                || (self.every_turns > 0 && game.turn % self.every_turns == 0))
+    }
+}
+
+pub fn autosave_dir(save_dir: &Path, slot: usize, index: usize) -> PathBuf {
+    slot::slot_dir(save_dir, slot).join(format!("autosave_{}", index + 1))
+}
+
+// The autosaves of one slot, written to in a ring so there are always a few to go back to
+pub struct Autosaver {
+    pub config: AutosaveConfig,
+    // The ring entry the next autosave goes into
+    next: usize,
+}
+
+impl Autosaver {
+    // Continues the ring of the slot after its most recent autosave
+    pub fn new(config: AutosaveConfig, save_dir: &Path, slot: usize) -> Self {
+        let next = match most_recent(&config, save_dir, slot) {
+            Some((index, _)) => (index + 1) % config.ring_size.max(1),
+            None => 0,
+        };
+        Autosaver { config, next }
+    }
+
+    // Autosaves the game if the turn that was just played calls for it. Returns if it did
+    pub fn after_turn(
+        &mut self,
+        game: &mut Game,
+        save_dir: &Path,
+        slot: usize,
+    ) -> Result<bool, SaveError> {
+        if !self.config.should_autosave(game) {
+            return Ok(false);
+        }
+        slot::save_in(game, &autosave_dir(save_dir, slot, self.next))?;
+        self.next = (self.next + 1) % self.config.ring_size;
+        Ok(true)
+    }
+}
+
+// Deletes every autosave of a slot, however many the ring held when they were written
+pub fn clear(save_dir: &Path, slot: usize) -> io::Result<()> {
+    let entries = match fs::read_dir(slot::slot_dir(save_dir, slot)) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_name().to_string_lossy().starts_with("autosave_") && entry.path().is_dir() {
+            fs::remove_dir_all(entry.path())?;
+        }
+    }
+    Ok(())
+}
+
+// The most recent autosave of a slot, as its index in the ring and its metadata
+pub fn most_recent(
+    config: &AutosaveConfig,
+    save_dir: &Path,
+    slot: usize,
+) -> Option<(usize, SlotMeta)> {
+    (0..config.ring_size)
+        .filter_map(|index| {
+            slot::read_meta_in(&autosave_dir(save_dir, slot, index)).map(|meta| (index, meta))
+        })
+        .max_by_key(|(_, meta)| (meta.timestamp, meta.turn))
+}
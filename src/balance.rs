@@ -0,0 +1,518 @@
+use crate::damage::Resistances;
+use crate::save;
+use crate::tile::{BossKind, EnemyKind};
+
+use std::fs;
+use std::path::Path;
+
+// Goes at the top of the balance file written out with the defaults
+const HEADER: &'static str = "\
+// Every tuning number in the game. Change any of them and restart to play with the new values,
+// or delete a line to go back to its default. See src/balance.rs for what each one does
+";
+
+// Every tuning number in the game. The defaults are the values the game was balanced with, and
+// any of them can be overridden from a RON file (balance.ron by default, see --balance) so
+// balance can be changed without recompiling. Fields left out of the file keep their default.
+// The defaults are only written down here, balance.ron is written from them the first time the
+// game starts
+#[derive(Debug, Clone, PartialEq, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Balance {
+    pub player: PlayerBalance,
+    pub enemies: EnemyBalance,
+    pub projectiles: ProjectileBalance,
+    pub bosses: BossBalance,
+    pub world: WorldBalance,
+}
+
+impl Balance {
+    // Reads the balance from a file. A file that is missing, doesn't parse or has a field the game
+    // doesn't know (most likely a typo) is an error, so a mistake doesn't silently fall back to
+    // the defaults
+    pub fn load(path: &Path) -> Result<Balance, String> {
+        let balance_str = fs::read_to_string(path)
+            .map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+        ron::from_str(&balance_str).map_err(|e| format!("couldn't parse {}: {}", path.display(), e))
+    }
+
+    // Reads the default balance file like load, except that if there is none yet it is written
+    // out with the defaults so it can be edited
+    pub fn load_or_create(path: &Path) -> Result<Balance, String> {
+        if path.exists() {
+            return Balance::load(path);
+        }
+        let balance = Balance::default();
+        let written = ron::ser::to_string_pretty(&balance, ron::ser::PrettyConfig::default())
+            .map_err(save::serialize_error)
+            .and_then(|serialized| {
+                save::write_atomic(path, format!("{}{}\n", HEADER, serialized).as_bytes())
+            });
+        if let Err(e) = written {
+            eprintln!("Couldn't write the default balance to {}: {}", path.display(), e);
+        }
+        Ok(balance)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlayerBalance {
+    pub max_health: usize,
+    pub max_energy: usize,
+    pub initial_energy: usize,
+    pub initial_speed: usize,
+    pub melee_damage: usize,
+    pub slam_damage: usize,
+    // Damage a player projectile does to a boss, which is less than it does to other enemies
+    pub projectile_boss_damage: usize,
+    // Health gained from one heal
+    pub heal_amount: usize,
+    // How many turns invisibility lasts
+    pub invisibility_duration: usize,
+
+    // Energy cost of each ability
+    pub teleport_cost: usize,
+    pub heal_cost: usize,
+    pub fire_cost: usize,
+    pub slam_cost: usize,
+    pub lightning_cost: usize,
+    pub invisibility_cost: usize,
+    pub tracking_projectile_cost: usize,
+
+    // Turns before each ability can be used again
+    pub lightning_cooldown: usize,
+    pub teleport_cooldown: usize,
+    pub fire_cooldown: usize,
+    pub slam_cooldown: usize,
+    pub projectile_cooldown: usize,
+    // Counted from when invisibility is used, so it includes the time spent invisible
+    pub invisibility_cooldown: usize,
+    pub tracking_projectile_cooldown: usize,
+}
+
+impl Default for PlayerBalance {
+    fn default() -> Self {
+        PlayerBalance {
+            max_health: 100,
+            max_energy: 100,
+            initial_energy: 100,
+            initial_speed: 1,
+            melee_damage: 30,
+            slam_damage: 50,
+            projectile_boss_damage: 10,
+            heal_amount: 10,
+            invisibility_duration: 10,
+            teleport_cost: 5,
+            heal_cost: 20,
+            fire_cost: 30,
+            slam_cost: 10,
+            lightning_cost: 25,
+            invisibility_cost: 30,
+            tracking_projectile_cost: 75,
+            lightning_cooldown: 5,
+            teleport_cooldown: 1,
+            fire_cooldown: 10,
+            slam_cooldown: 10,
+            projectile_cooldown: 1,
+            invisibility_cooldown: 35,
+            tracking_projectile_cooldown: 20,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnemyBalance {
+    pub chasing_health: usize,
+    pub bomber_health: usize,
+    pub knight_health: usize,
+    pub shooter_health: usize,
+    pub major_health: usize,
+    // Health of an elite, whatever its kind, see elite
+    pub minor_boss_health: usize,
+    pub major_boss_health: usize,
+
+    // Initiative each kind of enemy gains a turn, it acts once for every scheduler::ACTION_COST
+    // (100) saved up. 100 keeps pace with the player, 200 acts twice a turn, 50 every other turn
+    pub chasing_initiative: usize,
+    pub bomber_initiative: usize,
+    pub knight_initiative: usize,
+    pub shooter_initiative: usize,
+    pub major_initiative: usize,
+    pub major_boss_initiative: usize,
+
+    // Energy the player gets back for killing each kind of enemy
+    pub chasing_energy_return: usize,
+    pub bomber_energy_return: usize,
+    pub knight_energy_return: usize,
+    pub shooter_energy_return: usize,
+    pub major_energy_return: usize,
+    pub minor_boss_energy_return: usize,
+    pub major_boss_energy_return: usize,
+
+    pub bomber_damage: usize,
+    pub chaser_damage: usize,
+    pub major_damage: usize,
+    pub major_boss_damage: usize,
+
+    // Damage of a shooter's shot, and how many tiles it flies each turn
+    pub shooter_damage: usize,
+    pub shooter_shot_speed: usize,
+    // How far along a row or column a shooter fires at the player from
+    pub shooter_range: usize,
+    // Turns a shooter waits after firing before it can fire again
+    pub shooter_reload: usize,
+    // Shooters back off when the player comes closer than this many tiles
+    pub shooter_keep_distance: usize,
+    // A shooter's shot poisons the player, doing this much damage a turn for this many turns
+    pub shooter_poison_damage: usize,
+    pub shooter_poison_turns: usize,
+
+    // Damage of a knight's lunge, and how many tiles along a row or column it reaches
+    pub knight_damage: usize,
+    pub knight_reach: usize,
+    // Turns a knight's lunge slows the player for, they only act on every other one
+    pub knight_slow_turns: usize,
+
+    // Enemies this close to the player, counting the way they have to walk, spread out to the
+    // tiles around it instead of all coming straight at it, see crowd
+    pub flank_distance: usize,
+
+    // How far away enemies see the player from, if nothing is in the way, see ai
+    pub sight_radius: usize,
+    // Turns an enemy stands still after noticing the player before it goes after them
+    pub alert_turns: usize,
+    // Turns an enemy looks for the player after losing sight of them, then wanders for this many
+    pub search_turns: usize,
+    pub wander_turns: usize,
+    // Chance an idle enemy starts wandering on a turn
+    pub wander_chance: f32,
+    // Chasers and shooters run once they are down to this much of their health
+    pub flee_health: f32,
+
+    // Tiles an elite moves each time it acts, and how many affixes it has, see elite
+    pub minor_boss_speed: usize,
+    pub elite_affixes: usize,
+    // Initiative a fast elite gains on top of its kind's
+    pub elite_fast_initiative: usize,
+    // Damage a shielded elite's shield soaks up
+    pub elite_shield: usize,
+    // Enemies a splitting elite falls apart into
+    pub elite_split_count: usize,
+    // Damage an explosive elite does to the player when it goes off next to them
+    pub elite_explosion_damage: usize,
+    // Health the player gets back for killing an elite, on top of minor_boss_energy_return energy
+    pub elite_heal: usize,
+
+    // How much of each kind of damage each kind of enemy takes
+    pub chasing_resistances: Resistances,
+    pub bomber_resistances: Resistances,
+    pub knight_resistances: Resistances,
+    pub shooter_resistances: Resistances,
+    pub major_resistances: Resistances,
+    pub major_boss_resistances: Resistances,
+}
+
+impl Default for EnemyBalance {
+    fn default() -> Self {
+        EnemyBalance {
+            chasing_health: 50,
+            bomber_health: 25,
+            knight_health: 100,
+            shooter_health: 25,
+            major_health: 200,
+            minor_boss_health: 1000,
+            major_boss_health: 2000,
+            chasing_initiative: 100,
+            bomber_initiative: 200,
+            knight_initiative: 100,
+            shooter_initiative: 100,
+            major_initiative: 100,
+            major_boss_initiative: 100,
+            chasing_energy_return: 3,
+            bomber_energy_return: 5,
+            knight_energy_return: 12,
+            shooter_energy_return: 7,
+            major_energy_return: 25,
+            minor_boss_energy_return: 100,
+            major_boss_energy_return: 100,
+            bomber_damage: 20,
+            chaser_damage: 3,
+            major_damage: 10,
+            major_boss_damage: 10,
+            shooter_damage: 5,
+            shooter_shot_speed: 1,
+            shooter_range: 8,
+            shooter_reload: 3,
+            shooter_keep_distance: 4,
+            shooter_poison_damage: 2,
+            shooter_poison_turns: 3,
+            knight_damage: 15,
+            knight_reach: 2,
+            knight_slow_turns: 4,
+            flank_distance: 8,
+            sight_radius: 12,
+            alert_turns: 1,
+            search_turns: 12,
+            wander_turns: 6,
+            wander_chance: 0.1,
+            flee_health: 0.25,
+            minor_boss_speed: 1,
+            elite_affixes: 2,
+            elite_fast_initiative: 100,
+            elite_shield: 60,
+            elite_split_count: 2,
+            elite_explosion_damage: 25,
+            elite_heal: 30,
+            chasing_resistances: Resistances::default(),
+            // packed with explosives, so fire sets them off
+            bomber_resistances: Resistances {
+                fire: 2.0,
+                explosive: 0.5,
+                ..Resistances::default()
+            },
+            // armored against anything shot at them, but the armor conducts
+            knight_resistances: Resistances {
+                lightning: 2.0,
+                projectile: 0.5,
+                ..Resistances::default()
+            },
+            shooter_resistances: Resistances {
+                lightning: 1.5,
+                ..Resistances::default()
+            },
+            major_resistances: Resistances {
+                fire: 1.5,
+                lightning: 0.5,
+                ..Resistances::default()
+            },
+            major_boss_resistances: Resistances {
+                fire: 1.5,
+                lightning: 0.5,
+                ..Resistances::default()
+            },
+        }
+    }
+}
+
+impl EnemyBalance {
+    // The health each kind of enemy starts with
+    pub fn health(&self, kind: EnemyKind) -> usize {
+        match kind {
+            EnemyKind::Chaser => self.chasing_health,
+            EnemyKind::Bomber | EnemyKind::BomberActivated | EnemyKind::BomberDeactivated => {
+                self.bomber_health
+            }
+            EnemyKind::Knight => self.knight_health,
+            EnemyKind::Shooter => self.shooter_health,
+            EnemyKind::Major => self.major_health,
+            EnemyKind::MajorBoss => self.major_boss_health,
+        }
+    }
+
+    pub fn initiative(&self, kind: EnemyKind) -> usize {
+        match kind {
+            EnemyKind::Chaser => self.chasing_initiative,
+            EnemyKind::Bomber | EnemyKind::BomberActivated | EnemyKind::BomberDeactivated => {
+                self.bomber_initiative
+            }
+            EnemyKind::Knight => self.knight_initiative,
+            EnemyKind::Shooter => self.shooter_initiative,
+            EnemyKind::Major => self.major_initiative,
+            EnemyKind::MajorBoss => self.major_boss_initiative,
+        }
+    }
+
+    pub fn resistances(&self, kind: EnemyKind) -> &Resistances {
+        match kind {
+            EnemyKind::Chaser => &self.chasing_resistances,
+            EnemyKind::Bomber | EnemyKind::BomberActivated | EnemyKind::BomberDeactivated => {
+                &self.bomber_resistances
+            }
+            EnemyKind::Knight => &self.knight_resistances,
+            EnemyKind::Shooter => &self.shooter_resistances,
+            EnemyKind::Major => &self.major_resistances,
+            EnemyKind::MajorBoss => &self.major_boss_resistances,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProjectileBalance {
+    pub player_damage: usize,
+    pub player_speed: usize,
+    pub tracking_damage: usize,
+    pub tracking_speed: usize,
+    pub lightning_damage: usize,
+    pub lightning_speed: usize,
+    // How many tiles out from where it strikes lightning reaches
+    pub lightning_size: i16,
+    // Fire does less damage the further it travels
+    pub fire_damage_initial: usize,
+    pub fire_damage_secondary: usize,
+    pub fire_damage_tertiary: usize,
+    pub fire_damage_final: usize,
+    pub fire_speed: usize,
+    // Fire leaves whatever it hits burning, taking this much fire damage a turn for this many turns
+    pub fire_burn_damage: usize,
+    pub fire_burn_turns: usize,
+}
+
+impl Default for ProjectileBalance {
+    fn default() -> Self {
+        ProjectileBalance {
+            player_damage: 20,
+            player_speed: 1,
+            tracking_damage: 100,
+            tracking_speed: 2,
+            lightning_damage: 80,
+            lightning_speed: 0,
+            lightning_size: 2,
+            fire_damage_initial: 60,
+            fire_damage_secondary: 45,
+            fire_damage_tertiary: 30,
+            fire_damage_final: 15,
+            fire_speed: 1,
+            fire_burn_damage: 5,
+            fire_burn_turns: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BossBalance {
+    pub health: usize,
+    pub major_health: usize,
+    // Damage the boss does by running into the player
+    pub collision_damage: usize,
+    pub laser_amount: usize,
+    pub laser_damage: usize,
+    // Turns a laser stays up
+    pub laser_linger: usize,
+    pub column_laser_damage: usize,
+    pub asteroid_damage: usize,
+    pub asteroid_cooldown: usize,
+    // Turns an asteroid stays up
+    pub asteroid_linger: usize,
+    pub stun_well_cooldown: usize,
+    // Turns a stun well stays up
+    pub stun_well_linger: usize,
+    // Turns the player is stunned for when caught in a stun well
+    pub stun_well_stun_time: usize,
+    pub safe_spot_attack_cooldown: usize,
+    // Turns the player has to reach a safe spot
+    pub safe_spot_time: usize,
+    // Turns a boss stays vulnerable, some bosses add to this
+    pub vulnerable_time_base: usize,
+    pub shield_hits_needed: usize,
+    pub enemy_spawn_cooldown: usize,
+    // The chasing boss
+    pub rush_cooldown: usize,
+    pub move_delay: usize,
+
+    // How much of each kind of damage each boss takes while it is vulnerable
+    pub laser_resistances: Resistances,
+    pub column_resistances: Resistances,
+    pub chasing_resistances: Resistances,
+    pub blackout_resistances: Resistances,
+    pub major_resistances: Resistances,
+}
+
+impl Default for BossBalance {
+    fn default() -> Self {
+        BossBalance {
+            health: 1000,
+            major_health: 5000,
+            collision_damage: 10,
+            laser_amount: 7,
+            laser_damage: 5,
+            laser_linger: 3,
+            column_laser_damage: 20,
+            asteroid_damage: 10,
+            asteroid_cooldown: 20,
+            asteroid_linger: 5,
+            stun_well_cooldown: 10,
+            stun_well_linger: 50,
+            stun_well_stun_time: 2,
+            safe_spot_attack_cooldown: 20,
+            safe_spot_time: 10,
+            vulnerable_time_base: 15,
+            shield_hits_needed: 3,
+            enemy_spawn_cooldown: 10,
+            rush_cooldown: 20,
+            move_delay: 2,
+            laser_resistances: Resistances {
+                laser: 0.0,
+                lightning: 0.5,
+                fire: 1.5,
+                ..Resistances::default()
+            },
+            column_resistances: Resistances {
+                laser: 0.0,
+                fire: 0.5,
+                lightning: 1.5,
+                ..Resistances::default()
+            },
+            // it leaves fire behind it, so it doesn't mind fire
+            chasing_resistances: Resistances {
+                fire: 0.25,
+                lightning: 1.5,
+                ..Resistances::default()
+            },
+            blackout_resistances: Resistances {
+                lightning: 0.5,
+                fire: 1.5,
+                ..Resistances::default()
+            },
+            major_resistances: Resistances::default(),
+        }
+    }
+}
+
+impl BossBalance {
+    pub fn resistances(&self, kind: BossKind) -> &Resistances {
+        match kind {
+            BossKind::Laser => &self.laser_resistances,
+            BossKind::Column => &self.column_resistances,
+            BossKind::Chasing => &self.chasing_resistances,
+            BossKind::BlackOut => &self.blackout_resistances,
+            BossKind::Major => &self.major_resistances,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorldBalance {
+    // Enemies spread over the rooms when the world is generated
+    pub enemy_count: usize,
+    pub lakes_per_room: i16,
+    // Mountains spread over the whole board
+    pub mountains: i16,
+    // How many rooms away from the player enemies keep moving, see offscreen
+    pub offscreen_radius: usize,
+    // Most enemies that move in each of those rooms a turn
+    pub offscreen_moves_per_room: usize,
+    // Chance that an enemy off screen heads towards the player instead of wandering
+    pub offscreen_pursuit: f32,
+    // How many plain steps wading through a tile of water is worth, both to the paths enemies
+    // pick and to how long they take to cross, see pathfinding
+    pub water_cost: usize,
+}
+
+impl Default for WorldBalance {
+    fn default() -> Self {
+        WorldBalance {
+            enemy_count: 500,
+            lakes_per_room: 3,
+            mountains: 60,
+            offscreen_radius: 1,
+            offscreen_moves_per_room: 8,
+            offscreen_pursuit: 0.5,
+            water_cost: 2,
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{
+    ai::AiState, enemy::Enemy, pathfinding::FlowFields, tile::EnemyKind, utils::Position,
+    world::World, WORLD_SIZE,
+};
+
+/*
+ * How the enemies of the room keep out of each other's way while they close in on the player:
+ *      - At the start of their turn the tiles next to the player are handed out as slots, one
+ *        enemy each, to the closest enemies within balance.enemies.flank_distance. An enemy with
+ *        a slot heads for it rather than straight at the player, so a group spreads out around
+ *        the player instead of queueing up behind whoever is in front
+ *      - A slot is reserved for its enemy for the whole turn, nobody else steps onto it
+ *      - An enemy that finds every step closer taken waits, or sidesteps onto a tile no further
+ *        away, which gets it around whoever is blocking it in a corridor
+ * Enemies still move one after the other, a tile being taken is just whatever is on it when it is
+ * an enemy's turn to move, so two enemies never end up on the same tile
+ */
+
+#[derive(Debug, Clone, Default)]
+pub struct Crowd {
+    // The slot next to the player each enemy of the room has this turn, by its index in the room
+    slots: Vec<Option<Position>>,
+}
+
+impl Crowd {
+    pub fn slot(&self, index: usize) -> Option<Position> {
+        self.slots.get(index).copied().flatten()
+    }
+
+    // Whether the tile is some other enemy's slot
+    pub fn reserved(&self, tile: Position, index: usize) -> bool {
+        self.slots
+            .iter()
+            .enumerate()
+            .any(|(other, slot)| other != index && *slot == Some(tile))
+    }
+}
+
+// Enemies that walk up to the player to hit it, the ones slots are for
+fn flanks(kind: EnemyKind) -> bool {
+    matches!(
+        kind,
+        EnemyKind::Chaser | EnemyKind::Bomber | EnemyKind::Knight | EnemyKind::MajorBoss
+    )
+}
+
+// Hands out the tiles next to the player for this turn. Needs the flow fields to be up to date
+pub fn assign_slots(world: &World, flow_fields: &FlowFields, crowd: &mut Crowd) {
+    let room = world.world_position;
+    let player = world.player.pos;
+    let mut free: Vec<Position> = [(0, -1), (0, 1), (-1, 0), (1, 0)]
+        .iter()
+        .map(|(x, y)| (player.x as i16 + x, player.y as i16 + y))
+        .filter(|(x, y)| *x >= 0 && *x < WORLD_SIZE.0 && *y >= 0 && *y < WORLD_SIZE.1)
+        .map(|(x, y)| Position::new(x as usize, y as usize))
+        .filter(|tile| Enemy::field_cost(world, room, *tile, false).is_some())
+        .collect();
+
+    let enemies = &world.enemies_map[room.y][room.x];
+    let mut slots = vec![None; enemies.len()];
+    let mut candidates = Vec::new();
+    for (index, enemy) in enemies.iter().enumerate() {
+        // whoever is already standing on a slot keeps it, or blocks it if it doesn't need one
+        if let Some(taken) = free.iter().position(|slot| enemy.pos.contains(slot)) {
+            let slot = free.remove(taken);
+            if flanks(enemy.kind) {
+                slots[index] = Some(slot);
+            }
+            continue;
+        }
+        if !flanks(enemy.kind) || enemy.ai != AiState::Chase {
+            continue;
+        }
+        if let Some(distance) = flow_fields.ground.distance(enemy.pos[0]) {
+            if distance <= world.balance.enemies.flank_distance {
+                candidates.push((distance, index));
+            }
+        }
+    }
+    // the closest enemies pick first, each taking the free slot nearest to it
+    candidates.sort();
+    for (_, index) in candidates {
+        let pos = enemies[index].pos[0];
+        let nearest = (0..free.len()).min_by_key(|slot| {
+            (free[*slot].x as i16 - pos.x as i16).abs() + (free[*slot].y as i16 - pos.y as i16).abs()
+        });
+        match nearest {
+            Some(nearest) => slots[index] = Some(free.remove(nearest)),
+            None => break,
+        }
+    }
+    crowd.slots = slots;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pathfinding::Navigation, tile::TerrainKind};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    fn chaser(world: &World, pos: Position) -> Enemy {
+        let mut enemy = Enemy::chasing(pos.x, pos.y, world.world_position, &world.balance.enemies);
+        enemy.ai = AiState::Chase;
+        enemy.last_seen = Some(world.player.pos);
+        enemy
+    }
+
+    fn distance(a: Position, b: Position) -> usize {
+        a.x.abs_diff(b.x) + a.y.abs_diff(b.y)
+    }
+
+    #[test]
+    fn slots_go_to_the_closest_enemies() {
+        let mut world = World::empty_room(Position::new(10, 25));
+        let shooter = Enemy::shooting_enemy(11, 25, world.world_position, &world.balance.enemies);
+        world.place_enemy(shooter);
+        for pos in [(10, 24), (10, 29), (4, 25), (13, 26), (10, 40)] {
+            world.place_enemy(chaser(&world, Position::new(pos.0, pos.1)));
+        }
+        let mut navigation = Navigation::default();
+        Enemy::update_flow_fields(&world, &mut navigation.flow_fields);
+        assign_slots(&world, &navigation.flow_fields, &mut navigation.crowd);
+
+        let slots: Vec<_> = (0..6).map(|index| navigation.crowd.slot(index)).collect();
+        assert_eq!(
+            slots,
+            [
+                // the shooter blocks the slot it stands on without taking it
+                None,
+                // whoever already stands on a slot keeps it
+                Some(Position::new(10, 24)),
+                // then the closest take the slots nearest to them
+                Some(Position::new(10, 26)),
+                // until there are none left
+                None,
+                Some(Position::new(9, 25)),
+                // and past flank_distance nobody gets one
+                None,
+            ]
+        );
+        assert!(navigation.crowd.reserved(Position::new(10, 26), 4));
+        assert!(!navigation.crowd.reserved(Position::new(10, 26), 2));
+        assert!(!navigation.crowd.reserved(Position::new(11, 25), 0));
+    }
+
+    #[test]
+    fn chasers_get_around_each_other_in_a_corridor() {
+        let mut world = World::empty_room(Position::new(5, 25));
+        // a corridor two tiles wide from the player's side of the room to the enemies'
+        for x in 11..=30 {
+            world.terrain_map[0][0].insert(Position::new(x, 24), TerrainKind::Wall);
+            world.terrain_map[0][0].insert(Position::new(x, 27), TerrainKind::Wall);
+        }
+        world.place_enemy(chaser(&world, Position::new(20, 25)));
+        world.place_enemy(chaser(&world, Position::new(21, 25)));
+
+        let mut navigation = Navigation::default();
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        for _ in 0..20 {
+            Enemy::update(&mut world, &mut navigation, &mut rng);
+            let enemies = &world.enemies_map[0][0];
+            assert_ne!(enemies[0].pos, enemies[1].pos);
+        }
+        // the one behind went past the one in front rather than waiting on it forever
+        let player = world.player.pos;
+        for enemy in &world.enemies_map[0][0] {
+            assert_eq!(distance(enemy.pos[0], player), 1);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::tile::ProjectileKind;
+
+// What dealt the damage. Enemies and bosses can resist some kinds and be weak to others, see
+// Resistances
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum DamageKind {
+    // Melee, slams, projectiles and anything running into something
+    Physical,
+    Fire,
+    Lightning,
+    // Bomber explosions and missiles
+    Explosive,
+    // Boss lasers
+    Laser,
+    // Poison wearing on whoever is poisoned, see StatusKind::Poison
+    Poison,
+}
+
+impl DamageKind {
+    // The kind of damage a projectile does, which never changes over its flight
+    pub fn of_projectile(kind: ProjectileKind) -> Self {
+        match kind {
+            ProjectileKind::Player | ProjectileKind::Enemy => DamageKind::Physical,
+            ProjectileKind::Tracking => DamageKind::Explosive,
+            ProjectileKind::LightningPending
+            | ProjectileKind::LightningInitial
+            | ProjectileKind::LightningSecondary
+            | ProjectileKind::LightningFinal => DamageKind::Lightning,
+            ProjectileKind::FirePending
+            | ProjectileKind::FireInitial
+            | ProjectileKind::FireSecondary
+            | ProjectileKind::FireTertiary
+            | ProjectileKind::FireFinal => DamageKind::Fire,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Damage {
+    pub amount: usize,
+    pub kind: DamageKind,
+}
+
+impl Damage {
+    pub fn new(amount: usize, kind: DamageKind) -> Self {
+        Damage { amount, kind }
+    }
+
+    pub fn physical(amount: usize) -> Self {
+        Damage::new(amount, DamageKind::Physical)
+    }
+}
+
+// How much of each kind of damage is taken: 1.0 is all of it, less resists it and more is a
+// weakness. Kinds left out of the balance file take full damage. Projectile is on top of the
+// kind, for anything the player's projectiles, missiles, fire and lightning do
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Resistances {
+    pub physical: f32,
+    pub fire: f32,
+    pub lightning: f32,
+    pub explosive: f32,
+    pub laser: f32,
+    pub poison: f32,
+    pub projectile: f32,
+}
+
+impl Default for Resistances {
+    fn default() -> Self {
+        Resistances {
+            physical: 1.0,
+            fire: 1.0,
+            lightning: 1.0,
+            explosive: 1.0,
+            laser: 1.0,
+            poison: 1.0,
+            projectile: 1.0,
+        }
+    }
+}
+
+impl Resistances {
+    pub fn multiplier(&self, kind: DamageKind) -> f32 {
+        match kind {
+            DamageKind::Physical => self.physical,
+            DamageKind::Fire => self.fire,
+            DamageKind::Lightning => self.lightning,
+            DamageKind::Explosive => self.explosive,
+            DamageKind::Laser => self.laser,
+            DamageKind::Poison => self.poison,
+        }
+    }
+
+    // The health actually lost to the damage, never negative
+    pub fn scale(&self, damage: Damage) -> usize {
+        (damage.amount as f32 * self.multiplier(damage.kind).max(0.0)).round() as usize
+    }
+}
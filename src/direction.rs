@@ -1,4 +1,4 @@
-#[derive(PartialEq, Eq, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
 // Super simple direction enum, pretty self explanatory
 pub enum Direction {
     North,
@@ -0,0 +1,192 @@
+use crate::{
+    balance::Balance,
+    damage::{Damage, DamageKind},
+    enemy::Enemy,
+    entity::Entity,
+    random,
+    tile::{EnemyKind, Palette, TerrainKind, TileKind},
+    utils::Position,
+    world::{World, BOSS_ROOMS},
+    BOARD_SIZE, TILE_SIZE, UNIVERSAL_OFFSET, WORLD_SIZE,
+};
+
+use ggez::graphics;
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashMap;
+
+/*
+ * Elites are the mini bosses of the overworld: a chaser, knight or shooter with
+ * balance.enemies.minor_boss_health health, moving balance.enemies.minor_boss_speed tiles at a
+ * time, and balance.enemies.elite_affixes of these, picked at random:
+ *      - Fast: gains balance.enemies.elite_fast_initiative more initiative a turn
+ *      - Shielded: a shield soaks up the first balance.enemies.elite_shield damage it takes
+ *      - Splitting: falls apart into balance.enemies.elite_split_count of its plain kind when it
+ *        dies
+ *      - Explosive: goes off like a bomber when it dies
+ * One waits in each of the ELITE_ROOMS, the rooms between the boss rooms, with a health bar over
+ * it and its room marked on the map until it is dead. It never leaves its room, not even to follow
+ * the player (see Enemy::migrate). Killing one always gives back
+ * balance.enemies.minor_boss_energy_return energy and heals balance.enemies.elite_heal health
+ */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum Affix {
+    Fast,
+    Shielded,
+    Splitting,
+    Explosive,
+}
+
+const AFFIXES: [Affix; 4] = [Affix::Fast, Affix::Shielded, Affix::Splitting, Affix::Explosive];
+
+// The rooms halfway between the boss rooms, one elite each
+pub const ELITE_ROOMS: [Position; 4] = [
+    Position::new(3, 1),
+    Position::new(1, 3),
+    Position::new(5, 3),
+    Position::new(3, 5),
+];
+
+// Kinds an elite can be. Bombers blow themselves up, and majors are already big enough
+const ELITE_KINDS: [EnemyKind; 3] = [EnemyKind::Chaser, EnemyKind::Knight, EnemyKind::Shooter];
+
+// Puts an elite somewhere free in each of the ELITE_ROOMS, after the other enemies are placed
+pub fn gen_elites(
+    rng: &mut ChaCha8Rng,
+    terrain_map: &[[HashMap<Position, TerrainKind>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
+         (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
+    entity_map: &mut [[HashMap<Position, (TileKind, Entity)>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
+             (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
+    enemies_map: &mut [[Vec<Enemy>; (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
+             (BOARD_SIZE.1 / WORLD_SIZE.1) as usize],
+    balance: &Balance,
+) {
+    for room in ELITE_ROOMS {
+        debug_assert!(!BOSS_ROOMS.contains(&room));
+        let kind = ELITE_KINDS[random::rand_range(rng, 0, ELITE_KINDS.len() as i16) as usize];
+        let affixes = roll_affixes(rng, balance.enemies.elite_affixes);
+        // rooms are mostly open, so a free tile turns up long before this runs out
+        for _ in 0..1000 {
+            let pos = Position::new(
+                random::rand_range(rng, 0, WORLD_SIZE.0) as usize,
+                random::rand_range(rng, 0, WORLD_SIZE.1) as usize,
+            );
+            if terrain_map[room.y][room.x].contains_key(&pos)
+                || entity_map[room.y][room.x].contains_key(&pos)
+            {
+                continue;
+            }
+            let enemy = base(kind, pos, room, balance).elite(affixes.clone(), &balance.enemies);
+            entity_map[room.y][room.x].insert(pos, (TileKind::Enemy(kind), Entity::Enemy));
+            enemies_map[room.y][room.x].push(enemy);
+            break;
+        }
+    }
+}
+
+// As many different affixes as asked for, in a random order
+fn roll_affixes(rng: &mut ChaCha8Rng, count: usize) -> Vec<Affix> {
+    let mut left = AFFIXES.to_vec();
+    let mut affixes = Vec::new();
+    while affixes.len() < count && !left.is_empty() {
+        let index = random::rand_range(rng, 0, left.len() as i16) as usize;
+        affixes.push(left.remove(index));
+    }
+    affixes
+}
+
+// A plain enemy of one of the ELITE_KINDS
+fn base(kind: EnemyKind, pos: Position, room: Position, balance: &Balance) -> Enemy {
+    match kind {
+        EnemyKind::Knight => Enemy::knight(pos.x, pos.y, room, &balance.enemies),
+        EnemyKind::Shooter => Enemy::shooting_enemy(pos.x, pos.y, room, &balance.enemies),
+        _ => Enemy::chasing(pos.x, pos.y, room, &balance.enemies),
+    }
+}
+
+// What happens when an elite dies, after it has been taken off the board. The enemy has to be one
+// of the current room
+pub fn die(world: &mut World, enemy: &Enemy) {
+    let balance = &world.balance.enemies;
+    let (heal, energy, max_health, max_energy) = (
+        balance.elite_heal,
+        balance.minor_boss_energy_return,
+        world.balance.player.max_health,
+        world.balance.player.max_energy,
+    );
+    world.player.heal(heal, max_health);
+    world.player.change_energy(energy as i32, max_energy);
+
+    let pos = enemy.pos[0];
+    if enemy.has_affix(Affix::Explosive) {
+        Enemy::create_explosion(pos, world);
+        if Enemy::player_within_spaces(&pos, world, 2) {
+            world.player.damage(Damage::new(
+                world.balance.enemies.elite_explosion_damage,
+                DamageKind::Explosive,
+            ));
+        }
+    }
+    if enemy.has_affix(Affix::Splitting) {
+        split(world, enemy);
+    }
+}
+
+// Puts plain enemies of the elite's kind where it died and on the free tiles around it
+fn split(world: &mut World, enemy: &Enemy) {
+    let room = enemy.world_pos;
+    let pos = enemy.pos[0];
+    let tiles = [(0, 0), (0, -1), (0, 1), (-1, 0), (1, 0)]
+        .iter()
+        .map(|(x, y)| (pos.x as i16 + x, pos.y as i16 + y))
+        .filter(|(x, y)| *x >= 0 && *x < WORLD_SIZE.0 && *y >= 0 && *y < WORLD_SIZE.1)
+        .map(|(x, y)| Position::new(x as usize, y as usize))
+        .filter(|tile| {
+            !world.entity_map[room.y][room.x].contains_key(tile)
+                && Enemy::step_cost(world, room, *tile, enemy.can_dodge_projectiles()).is_some()
+        })
+        .take(world.balance.enemies.elite_split_count)
+        .collect::<Vec<_>>();
+    for tile in tiles {
+        let mut part = base(enemy.kind, tile, room, &world.balance);
+        // it knows exactly where the player is, it was just fighting them
+        part.ai = enemy.ai;
+        part.last_seen = enemy.last_seen;
+        world.entity_map[room.y][room.x].insert(tile, (TileKind::Enemy(enemy.kind), Entity::Enemy));
+        world.enemies_map[room.y][room.x].push(part);
+    }
+}
+
+// Whether an elite is still alive in the room, for the map
+pub fn has_elite(world: &World, room: Position) -> bool {
+    world.enemies_map[room.y][room.x].iter().any(Enemy::is_elite)
+}
+
+// A bar along the top of every elite of the room, how much of it is filled is how much health it
+// has left. It is drawn in the shield's color while the shield holds
+pub fn draw_health_bars(world: &World, canvas: &mut graphics::Canvas, palette: &Palette) {
+    for enemy in &world.enemies_map[world.world_position.y][world.world_position.x] {
+        if !enemy.is_elite() {
+            continue;
+        }
+        let max_health = enemy.max_health(&world.balance.enemies).max(1);
+        let filled = (TILE_SIZE.0 as usize * enemy.health().min(max_health) / max_health) as i32;
+        let color = if enemy.shield > 0 {
+            palette.elite_shield
+        } else {
+            palette.elite_health
+        };
+        let (x, y) = (
+            enemy.pos[0].x as i32 * TILE_SIZE.0 as i32,
+            (enemy.pos[0].y as i32 + UNIVERSAL_OFFSET as i32) * TILE_SIZE.1 as i32,
+        );
+        let height = (TILE_SIZE.1 as i32 / 4).max(1);
+        for (width, color) in [(TILE_SIZE.0 as i32, palette.elite_health_lost), (filled, color)] {
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(graphics::Rect::new_i32(x, y, width, height))
+                    .color(color),
+            )
+        }
+    }
+}
@@ -1,57 +1,25 @@
 use crate::{
+    ai::{self, AiState},
+    balance::EnemyBalance,
+    crowd,
+    damage::{Damage, DamageKind},
     direction::Direction,
+    elite::{self, Affix},
     entity::Entity,
+    pathfinding::{self, FlowFields, Footprint, Navigation},
     projectile::Projectile,
-    tile::{self, PROJECTILE_PLAYER},
+    scheduler,
+    status::{StatusEffect, StatusEffects, StatusKind},
+    tile::{EnemyKind, Palette, ProjectileKind, TerrainKind, TileKind},
     utils::Position,
     utils::Boss,
-    world::World,
+    world::{World, BOSS_ROOMS},
     BOARD_SIZE, TILE_SIZE, UNIVERSAL_OFFSET, WORLD_SIZE,
 };
 use ggez::graphics::{self, Canvas};
-use std::{cmp::max, collections::HashMap, collections::LinkedList};
-
-const CHASING_ENEMY_HEALTH: usize = 50;
-const BOMBER_ENEMY_HEALTH: usize = 25;
-const KNIGHT_ENEMY_HEALTH: usize = 100;
-const SHOOTER_ENEMY_HEALTH: usize = 25;
-const MAJOR_ENEMY_HEALTH: usize = 200;
-const MINOR_BOSS_HEALTH: usize = 1000;
-const MAJOR_BOSS_HEALTH: usize = 2000;
-
-const PERMISSIBLE_TILES: [[f32; 4]; 4] = [
-    tile::GRASS,
-    tile::PROJECTILE_PLAYER,
-    tile::LIGHTNING_SECONDARY,
-    tile::LIGHTNING_INITIAL,
-];
-const PERMISSIBLE_TILES_DODGING: [[f32; 4]; 3] = [
-    tile::GRASS,
-    tile::LIGHTNING_INITIAL,
-    tile::LIGHTNING_SECONDARY,
-];
-const PERMISSIBLE_TILES_BOSS: [[f32; 4]; 0] = [];
-
-const CHASING_ENEMY_SPEED: usize = 1;
-const BOMBER_ENEMY_SPEED: usize = 2;
-const KNIGHT_ENEMY_SPEED: usize = 1;
-const SHOOTER_ENEMY_SPEED: usize = 1;
-const MAJOR_ENEMY_SPEED: usize = 1;
-const MINOR_BOSS_SPEED: usize = 1;
-const MAJOR_BOSS_SPEED: usize = 1;
-
-const CHASING_ENEMY_ENERGY_RETURN: usize = 3;
-const BOMBER_ENEMY_ENERGY_RETURN: usize = 5;
-const KNIGHT_ENEMY_ENERGY_RETURN: usize = 12;
-const SHOOTER_ENEMY_ENERGY_RETURN: usize = 7;
-const MAJOR_ENEMY_ENERGY_RETURN: usize = 25;
-const MINOR_BOSS_ENERGY_RETURN: usize = 100;
-const MAJOR_BOSS_ENERGY_RETURN: usize = 100;
-
-const BOMBER_DAMAGE: usize = 20;
-const CHASER_DAMAGE: usize = 3;
-const MAJOR_DAMAGE: usize = 10;
-const MAJOR_BOSS_DAMAGE: usize = 10;
+use rand_chacha::ChaCha8Rng;
+use std::{cmp::{max, min}, collections::HashMap, collections::LinkedList};
+
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
 // This is basically the same as the enemy for now, but I am just testing an enemy system
@@ -65,12 +33,18 @@ pub struct Enemy {
     // shoot in the right direction
     pub direction: Direction,
 
-    // Just like in player controls the amount of tiles an enemy moves in one "turn"
+    // Just like in player controls the amount of tiles an enemy moves in one action. How often it
+    // acts is up to the initiative of its kind, see scheduler
     pub speed: usize,
 
-    // This is the enemy color. NOTE: both this and the previous attribute assume that the game
-    // world is a set of tiles and the enemy is represented as a solid color
-    pub color: [f32; 4],
+    // What kind of enemy this is, which decides how it moves. Bombers also use it to keep track
+    // of whether they are about to explode
+    #[serde(default)]
+    pub kind: EnemyKind,
+
+    // Saves up to version 2 stored the color instead of the kind, the migration converts it
+    #[serde(rename = "color", default, skip_serializing)]
+    pub legacy_color: [f32; 4],
 
     //Enemy attack damage
     pub attack_damage: usize,
@@ -80,20 +54,65 @@ pub struct Enemy {
 
     pub world_pos: Position,
 
-    resistance: f32,
+    // Saves up to version 5 had a resistance to projectiles that only knights used, resistances
+    // are now looked up in the balance by kind. The migration converts it
+    #[serde(rename = "resistance", default = "full_resistance", skip_serializing)]
+    pub legacy_resistance: f32,
+
+    // Used instead of its kind's projectile resistance, only set on enemies from saves that had
+    // one of their own
+    #[serde(default)]
+    pub projectile_resistance: Option<f32>,
 
     can_dodge_projectiles: bool,
 
     is_boss: bool,
 
     pub movement_cooldown: bool,
+
+    // Turns until a shooter can fire again
+    #[serde(default)]
+    pub reload: usize,
+
+    // Set on the turn a knight telegraphs a lunge, it lunges this way on the next turn
+    #[serde(default)]
+    pub lunge: Option<Direction>,
+
+    // Burns, stuns and every other effect on the enemy
+    #[serde(default)]
+    pub status: StatusEffects,
+
+    // Initiative saved up towards its next action
+    #[serde(default)]
+    pub initiative: usize,
+
+    // What it is up to, see ai
+    #[serde(default)]
+    pub ai: AiState,
+    // Turns left of the state, for the ones that wear off
+    #[serde(default)]
+    pub ai_turns: usize,
+    // Where it last saw the player, where it searches once it loses them
+    #[serde(default)]
+    pub last_seen: Option<Position>,
+
+    // What makes it an elite, none for every other enemy. See elite
+    #[serde(default)]
+    pub affixes: Vec<Affix>,
+    // Damage a shielded elite's shield still soaks up
+    #[serde(default)]
+    pub shield: usize,
+}
+
+// Every enemy took all of the damage before resistances
+fn full_resistance() -> f32 {
+    1.0
 }
 
 impl Enemy {
     fn new(
         pos: Vec<Position>,
-        speed: usize,
-        color: [f32; 4],
+        kind: EnemyKind,
         world_pos: Position,
         health: usize,
         can_dodge_projectiles: bool,
@@ -104,49 +123,58 @@ impl Enemy {
             pos,
             direction: Direction::North,
             speed: 1,
-            color,
+            kind,
+            legacy_color: [0.0; 4],
             attack_damage,
             health,
-            resistance: 1.0,
+            legacy_resistance: full_resistance(),
+            projectile_resistance: None,
             world_pos,
             can_dodge_projectiles,
             is_boss: boss,
             movement_cooldown: false,
+            reload: 0,
+            lunge: None,
+            status: StatusEffects::default(),
+            initiative: 0,
+            ai: ai::initial_state(kind, boss),
+            ai_turns: 0,
+            last_seen: None,
+            affixes: Vec::new(),
+            shield: 0,
         };
         temp
     }
 
-    pub fn bomber(x: usize, y: usize, world_pos: Position) -> Self {
+    pub fn bomber(x: usize, y: usize, world_pos: Position, balance: &EnemyBalance) -> Self {
         let mut pos = Vec::new();
         pos.push(Position::new(x, y));
         Enemy::new(
             pos,
-            BOMBER_ENEMY_SPEED,
-            tile::BOMBER_ENEMY,
+            EnemyKind::Bomber,
             world_pos,
-            BOMBER_ENEMY_HEALTH,
+            balance.bomber_health,
             true,
             false,
-            BOMBER_DAMAGE
+            balance.bomber_damage,
         )
     }
 
-    pub fn chasing(x: usize, y: usize, world_pos: Position) -> Self {
+    pub fn chasing(x: usize, y: usize, world_pos: Position, balance: &EnemyBalance) -> Self {
         let mut pos = Vec::new();
         pos.push(Position::new(x, y));
         Enemy::new(
             pos,
-            CHASING_ENEMY_SPEED,
-            tile::CHASING_ENEMY,
+            EnemyKind::Chaser,
             world_pos,
-            CHASING_ENEMY_HEALTH,
+            balance.chasing_health,
             true,
             false,
-            CHASER_DAMAGE,
+            balance.chaser_damage,
         )
     }
 
-    pub fn major_enemy(x: usize, y: usize, world_pos: Position) -> Self {
+    pub fn major_enemy(x: usize, y: usize, world_pos: Position, balance: &EnemyBalance) -> Self {
         let mut pos = Vec::new();
         for i in 0..3 {
             for j in 0..3 {
@@ -155,111 +183,296 @@ impl Enemy {
         }
         Enemy::new(
             pos,
-            MAJOR_ENEMY_SPEED,
-            tile::MAJOR_ENEMY,
+            EnemyKind::Major,
             world_pos,
-            MAJOR_ENEMY_HEALTH,
+            balance.major_health,
             true,
             false,
-           MAJOR_DAMAGE,
+            balance.major_damage,
         )
     }
 
-    pub fn shooting_enemy(x: usize, y: usize, world_pos: Position) -> Self {
+    pub fn shooting_enemy(x: usize, y: usize, world_pos: Position, balance: &EnemyBalance) -> Self {
         let mut pos = Vec::new();
         pos.push(Position::new(x, y));
         Enemy::new(
             pos,
-            SHOOTER_ENEMY_SPEED,
-            tile::SHOOTER_ENEMY,
+            EnemyKind::Shooter,
             world_pos,
-            SHOOTER_ENEMY_HEALTH,
+            balance.shooter_health,
             true,
             false,
             0,
         )
     }
 
-    pub fn knight(x: usize, y: usize, world_pos: Position) -> Self {
+    pub fn knight(x: usize, y: usize, world_pos: Position, balance: &EnemyBalance) -> Self {
         let mut pos = Vec::new();
         pos.push(Position::new(x, y));
         Enemy::new(
             pos,
-            KNIGHT_ENEMY_SPEED,
-            tile::KNIGHT_ENEMY,
+            EnemyKind::Knight,
             world_pos,
-            KNIGHT_ENEMY_HEALTH,
+            balance.knight_health,
             true,
             false,
-            0,
+            balance.knight_damage,
         )
     }
 
-    pub fn major_boss(x: usize, y: usize, world_pos: Position) -> Self {
+    pub fn major_boss(x: usize, y: usize, world_pos: Position, balance: &EnemyBalance) -> Self {
         let mut pos = Vec::new();
         pos.push(Position::new(x, y));
         Enemy::new(
             pos,
-            MAJOR_BOSS_SPEED,
-            tile::MAJOR_BOSS,
+            EnemyKind::MajorBoss,
             world_pos,
-            MAJOR_BOSS_HEALTH,
+            balance.major_boss_health,
             true,
             false,
-            MAJOR_BOSS_DAMAGE
+            balance.major_boss_damage,
         )
     }
 
+    // Turns the enemy into an elite with these affixes, see elite
+    pub fn elite(mut self, affixes: Vec<Affix>, balance: &EnemyBalance) -> Self {
+        self.health = balance.minor_boss_health;
+        self.speed = balance.minor_boss_speed;
+        if affixes.contains(&Affix::Shielded) {
+            self.shield = balance.elite_shield;
+        }
+        self.affixes = affixes;
+        self
+    }
+
+    pub fn is_elite(&self) -> bool {
+        !self.affixes.is_empty()
+    }
+
+    pub fn has_affix(&self, affix: Affix) -> bool {
+        self.affixes.contains(&affix)
+    }
+
     pub fn health(&self) -> usize {
         self.health
     }
 
-    pub fn damage(&mut self, damage: usize) {
-        // potentially modify the damage done with the multiplier
+    // The health it started with
+    pub fn max_health(&self, balance: &EnemyBalance) -> usize {
+        if self.is_elite() {
+            balance.minor_boss_health
+        } else {
+            balance.health(self.kind)
+        }
+    }
+
+    // The damage is scaled by how much of its kind this kind of enemy resists, and whatever is
+    // left of a shield soaks it up before its health does
+    pub fn damage(&mut self, damage: Damage, balance: &EnemyBalance) {
+        let mut damage = balance.resistances(self.kind).scale(damage);
+        let absorbed = min(self.shield, damage);
+        self.shield -= absorbed;
+        damage -= absorbed;
         self.health = max(0, self.health as i32 - damage as i32) as usize;
     }
 
-    pub fn update(world: &mut World) {
+    // Every hit a projectile of the player's lands on an enemy goes through here, whether the
+    // projectile flew into it, it walked into the projectile or it was caught in fire or lightning
+    pub fn hit_by_projectile(&mut self, damage: Damage, balance: &EnemyBalance) {
+        let resistance = self
+            .projectile_resistance
+            .unwrap_or(balance.resistances(self.kind).projectile);
+        let amount = (damage.amount as f32 * resistance.max(0.0)).round() as usize;
+        self.damage(Damage::new(amount, damage.kind), balance);
+    }
+
+    // The enemy walks onto these tiles, taking the hit of every projectile or missile of the
+    // player's on them, which are used up
+    fn run_into_projectiles(index: usize, world: &mut World, tiles: &[Position]) {
+        let room = world.enemies_map[world.world_position.y][world.world_position.x][index].world_pos;
+        let mut index_proj = 0;
+        while index_proj < world.projectiles.len() {
+            let projectile = &world.projectiles[index_proj];
+            if matches!(projectile.kind, ProjectileKind::Player | ProjectileKind::Tracking)
+                && projectile.world_pos == room
+                && tiles.contains(&projectile.pos)
+            {
+                let hit = projectile.hit();
+                world.enemies_map[world.world_position.y][world.world_position.x][index]
+                    .hit_by_projectile(hit, &world.balance.enemies);
+                Projectile::kill(index_proj, world);
+            } else {
+                index_proj += 1;
+            }
+        }
+    }
+
+    pub fn update(world: &mut World, navigation: &mut Navigation, rng: &mut ChaCha8Rng) {
+        Self::update_flow_fields(world, &mut navigation.flow_fields);
+        crowd::assign_slots(world, &navigation.flow_fields, &mut navigation.crowd);
         for index in
             (0..world.enemies_map[world.world_position.y][world.world_position.x].len()).rev()
         {
             // if world.enemies_map[world.world_position.y][world.world_position.x][index].pos.x >= WORLD_SIZE.0 as usize || world.enemies_map[world.world_position.y][world.world_position.x][index].pos.y >= WORLD_SIZE.1 as usize {
             //     panic!("Enemy out of bounds with position: {:?}", world.enemies_map[world.world_position.y][world.world_position.x][index].pos);
             // }
+            // whether it can act is decided before its effects count down, so a stun of one turn
+            // costs it one move
+            let enemy = &mut world.enemies_map[world.world_position.y][world.world_position.x][index];
+            let can_act = enemy.status.can_act();
+            for damage in enemy.status.tick() {
+                enemy.damage(damage, &world.balance.enemies);
+            }
             if world.enemies_map[world.world_position.y][world.world_position.x][index].health <= 0
             {
                 Enemy::kill(world, index);
             } else {
-                if world.player.is_visible() {
-                    Self::move_enemy(index, world);
+                // it keeps an eye out even while it can't act
+                ai::perceive(world, index, rng);
+                if can_act {
+                    Self::take_actions(index, world, navigation, rng);
                 }
             }
         }
     }
 
+    // Initiative the enemy gains this turn. Wading slows it down by as much as the water costs
+    // its paths
+    pub fn initiative_gain(&self, world: &World) -> usize {
+        let terrain = world.terrain_map[self.world_pos.y][self.world_pos.x].get(&self.pos[0]);
+        let cost = pathfinding::terrain_cost(terrain.copied(), &world.balance.world).unwrap_or(1);
+        let mut gained = world.balance.enemies.initiative(self.kind);
+        if self.has_affix(Affix::Fast) {
+            gained += world.balance.enemies.elite_fast_initiative;
+        }
+        gained / cost
+    }
+
+    // Moves the enemy once for every action its initiative pays for this turn
+    fn take_actions(
+        index: usize,
+        world: &mut World,
+        navigation: &mut Navigation,
+        rng: &mut ChaCha8Rng,
+    ) {
+        let gained = world.enemies_map[world.world_position.y][world.world_position.x][index]
+            .initiative_gain(world);
+        let enemy = &mut world.enemies_map[world.world_position.y][world.world_position.x][index];
+        let actions = scheduler::actions_this_turn(&mut enemy.initiative, gained);
+        let enemies = world.enemies_map[world.world_position.y][world.world_position.x].len();
+        for action in 0..actions {
+            // a lit fuse burns down a turn at a time however fast the bomber is, so the player
+            // always gets a turn to get away from an explosion
+            let kind = world.enemies_map[world.world_position.y][world.world_position.x][index].kind;
+            if action > 0 && matches!(kind, EnemyKind::BomberActivated | EnemyKind::BomberDeactivated) {
+                return;
+            }
+            // a lit fuse burns down whatever the bomber is up to
+            let state = world.enemies_map[world.world_position.y][world.world_position.x][index].ai;
+            if state == AiState::Chase
+                || matches!(kind, EnemyKind::BomberActivated | EnemyKind::BomberDeactivated)
+            {
+                Self::move_enemy(index, world, navigation);
+            } else {
+                ai::act(world, index, navigation, rng);
+            }
+            // the enemy removed itself, e.g. a bomber that went off
+            if world.enemies_map[world.world_position.y][world.world_position.x].len() != enemies {
+                return;
+            }
+        }
+    }
+
     pub fn kill(world: &mut World, index: usize) {
         // for now all it does is remove the tile on the world "board"
+        let balance = &world.balance.enemies;
         let delta =
-            match world.enemies_map[world.world_position.y][world.world_position.x][index].color {
-                tile::CHASING_ENEMY => CHASING_ENEMY_ENERGY_RETURN,
-                tile::BOMBER_ENEMY => BOMBER_ENEMY_ENERGY_RETURN,
-                tile::BOMBER_ENEMY_ACTIVATED => BOMBER_ENEMY_ENERGY_RETURN,
-                tile::BOMBER_ENEMY_DEACTIVATED => BOMBER_ENEMY_ENERGY_RETURN,
-                tile::MAJOR_ENEMY => MAJOR_ENEMY_ENERGY_RETURN,
-                tile::SHOOTER_ENEMY => SHOOTER_ENEMY_ENERGY_RETURN,
-                tile::KNIGHT_ENEMY => KNIGHT_ENEMY_ENERGY_RETURN,
-                tile::LASER_BOSS => MINOR_BOSS_ENERGY_RETURN,
-                tile::MAJOR_BOSS => MAJOR_BOSS_ENERGY_RETURN,
-                tile::BOMBER_ENEMY_ACTIVATED => 0,
-                _ => unreachable!("Cannot be anything other than the enemy tiles"),
+            match world.enemies_map[world.world_position.y][world.world_position.x][index].kind {
+                EnemyKind::Chaser => balance.chasing_energy_return,
+                EnemyKind::Bomber => balance.bomber_energy_return,
+                EnemyKind::BomberActivated => balance.bomber_energy_return,
+                EnemyKind::BomberDeactivated => balance.bomber_energy_return,
+                EnemyKind::Major => balance.major_energy_return,
+                EnemyKind::Shooter => balance.shooter_energy_return,
+                EnemyKind::Knight => balance.knight_energy_return,
+                EnemyKind::MajorBoss => balance.major_boss_energy_return,
             } as i32;
-        world.player.change_energy(delta);
+        world.player.change_energy(delta, world.balance.player.max_energy);
         let enemy = &mut world.enemies_map[world.world_position.y][world.world_position.x][index]; 
         let pos = &mut enemy.pos;
         for tile in pos {
             world.entity_map[enemy.world_pos.y][enemy.world_pos.x].remove(&tile);
         }
-        world.enemies_map[world.world_position.y][world.world_position.x].remove(index);
+        let enemy = world.enemies_map[world.world_position.y][world.world_position.x].remove(index);
+        if enemy.is_elite() {
+            elite::die(world, &enemy);
+        }
+    }
+
+    // Moves the enemy at index in room over the edge of the room it is facing into the next room,
+    // the same way World::travel moves the player. Only enemies one tile big can, elites stay in
+    // their room, and never into or out of a boss room. Returns if the enemy moved, in which case
+    // it is now the last enemy of the other room and the enemies after index in this room have
+    // shifted down one
+    pub fn migrate(world: &mut World, room: Position, index: usize, direction: Direction) -> bool {
+        let enemy = &world.enemies_map[room.y][room.x][index];
+        if enemy.pos.len() != 1 || enemy.is_elite() {
+            return false;
+        }
+        let pos = enemy.pos[0];
+        let last = (WORLD_SIZE.0 as usize - 1, WORLD_SIZE.1 as usize - 1);
+        let rooms = (
+            (BOARD_SIZE.0 / WORLD_SIZE.0) as usize,
+            (BOARD_SIZE.1 / WORLD_SIZE.1) as usize,
+        );
+        let (new_room, new_pos) = match direction {
+            Direction::North if pos.y == 0 && room.y > 0 => (
+                Position::new(room.x, room.y - 1),
+                Position::new(pos.x, last.1),
+            ),
+            Direction::South if pos.y == last.1 && room.y + 1 < rooms.1 => (
+                Position::new(room.x, room.y + 1),
+                Position::new(pos.x, 0),
+            ),
+            Direction::West if pos.x == 0 && room.x > 0 => (
+                Position::new(room.x - 1, room.y),
+                Position::new(last.0, pos.y),
+            ),
+            Direction::East if pos.x == last.0 && room.x + 1 < rooms.0 => (
+                Position::new(room.x + 1, room.y),
+                Position::new(0, pos.y),
+            ),
+            _ => return false,
+        };
+        if BOSS_ROOMS.contains(&room) || BOSS_ROOMS.contains(&new_room) {
+            return false;
+        }
+        // the tile has to be empty, an enemy never arrives on top of the player
+        if world.entity_map[new_room.y][new_room.x].contains_key(&new_pos)
+            || !Self::can_travel_to(
+                world,
+                (new_pos, new_room),
+                &world.entity_map,
+                &world.terrain_map,
+                &world.atmosphere_map,
+                enemy.can_dodge_projectiles,
+            )
+        {
+            return false;
+        }
+        let tile = match world.entity_map[room.y][room.x].remove(&pos) {
+            Some(tile) => tile,
+            None => return false,
+        };
+        world.entity_map[new_room.y][new_room.x].insert(new_pos, tile);
+        let mut enemy = world.enemies_map[room.y][room.x].remove(index);
+        enemy.pos[0] = new_pos;
+        enemy.world_pos = new_room;
+        enemy.direction = direction;
+        // a telegraphed lunge was aimed at the old room
+        enemy.lunge = None;
+        world.enemies_map[new_room.y][new_room.x].push(enemy);
+        true
     }
 
     pub fn move_enemy_with_deltas(index: usize, world: &mut World) {
@@ -305,8 +518,8 @@ impl Enemy {
         
         for tile in &new_pos {
             if (world.terrain_map[world_pos.y][world_pos.x].contains_key(&tile)) {
-                if (!PERMISSIBLE_TILES.contains(
-                    world.terrain_map[world_pos.y][world_pos.x]
+                if (!Self::can_enter_terrain(
+                    *world.terrain_map[world_pos.y][world_pos.x]
                         .get(&tile)
                         .unwrap(),
                 )) {
@@ -314,42 +527,31 @@ impl Enemy {
                 }
             }
             if (world.atmosphere_map[world_pos.y][world_pos.x].contains_key(&tile)) {
-                if (!PERMISSIBLE_TILES.contains(
-                    world.atmosphere_map[world_pos.y][world_pos.x]
+                if (!Self::can_enter_tile(
+                    *world.atmosphere_map[world_pos.y][world_pos.x]
                         .get(&tile)
                         .unwrap(),
+                    false,
                 )) {
                     return;
                 }
             }
             if (world.entity_map[world_pos.y][world_pos.x].contains_key(&tile)) {
-                if (!PERMISSIBLE_TILES.contains(
-                    &world.entity_map[world_pos.y][world_pos.x]
+                if (!Self::can_enter_tile(
+                    world.entity_map[world_pos.y][world_pos.x]
                         .get(&tile)
                         .unwrap()
                     .0,
+                    false,
                 )) {
                     return;
                 }
             }
         }
         if new_pos.contains(&world.player.pos) {
-            world.player.damage(world.enemies_map[world.world_position.y][world.world_position.x][index].attack_damage);
+            world.player.damage(Damage::physical(world.enemies_map[world.world_position.y][world.world_position.x][index].attack_damage));
         } else {
-            let mut index_proj: i32 = 0;
-            for _ in 0..world.projectiles.len() {
-                if world.projectiles[index_proj as usize].color == tile::PROJECTILE_PLAYER {
-                    if new_pos.contains(&world.projectiles[index_proj as usize].pos)
-                        && world.enemies_map[world.world_position.y][world.world_position.x][index].world_pos
-                            == world.projectiles[index_proj as usize].world_pos
-                    {
-                        world.enemies_map[world.world_position.y][world.world_position.x][index].damage(world.projectiles[index_proj as usize].damage);
-                        Projectile::kill(index_proj as usize, world);
-                        index_proj -= 1;
-                    }
-                    index_proj += 1
-                }
-            }
+            Self::run_into_projectiles(index, world, &new_pos);
             // simply updates the render queue
             for i in 0..new_pos.len() {
                 World::update_position(
@@ -365,56 +567,48 @@ impl Enemy {
     // This just makes move along the best path for the speed, eg speed 2 = 2 moves along the best
     // path
     // returns if the enemy dies
-    pub fn move_enemy(index: usize, world: &mut World) {
+    pub fn move_enemy(index: usize, world: &mut World, navigation: &mut Navigation) {
+        if world.enemies_map[world.world_position.y][world.world_position.x][index].kind == EnemyKind::Shooter {
+            Self::move_shooter(index, world);
+            return;
+        }
+        if world.enemies_map[world.world_position.y][world.world_position.x][index].kind == EnemyKind::Knight
+            && Self::knight_lunge(index, world)
+        {
+            return;
+        }
         // This gets the shortest path
-        let can_dodge_projectiles = match world.enemies_map[world.world_position.y][world.world_position.x][index].color {
-            tile::BOMBER_ENEMY => true,
-            _ => false,
-        };
-        let mut travel_path = Self::get_best_path(index, world, can_dodge_projectiles);
+        let mut travel_path = Self::flow_path(index, world, navigation);
         let enemy = &world.enemies_map[world.world_position.y][world.world_position.x][index];
         let mut cur_pos = enemy.pos[0];
         for _ in 0..enemy.speed {
             if let Some(new_pos) = travel_path.pop_front() {
-                if Self::match_color(&world.enemies_map[world.world_position.y][world.world_position.x][index].color, &tile::CHASING_ENEMY) {
+                let kind = world.enemies_map[world.world_position.y][world.world_position.x][index].kind;
+                // knights chase the player until it is in reach of a lunge
+                if matches!(
+                    kind,
+                    EnemyKind::Chaser | EnemyKind::Knight | EnemyKind::MajorBoss
+                ) {
                     if new_pos.x >= WORLD_SIZE.0 as usize || new_pos.y >= WORLD_SIZE.1 as usize {
                         Self::move_enemy_with_deltas(index, world);
                         return;
                     }
                     if new_pos == world.player.pos {
-                        world.player.damage(world.enemies_map[world.world_position.y][world.world_position.x][index].attack_damage);
+                        world.player.damage(Damage::physical(world.enemies_map[world.world_position.y][world.world_position.x][index].attack_damage));
                     } else {
-                        let mut index_proj: i32 = 0;
-                        for _ in 0..world.projectiles.len() {
-                            if world.projectiles[index_proj as usize].color
-                                == tile::PROJECTILE_PLAYER
-                                || world.projectiles[index_proj as usize].color
-                                    == tile::TRACKING_PROJECTILE
-                            {
-                                if new_pos == world.projectiles[index_proj as usize].pos
-                                    && world.enemies_map[world.world_position.y][world.world_position.x][index].world_pos
-                                        == world.projectiles[index_proj as usize].world_pos
-                                {
-                                    world.enemies_map[world.world_position.y][world.world_position.x][index]
-                                        .damage(world.projectiles[index_proj as usize].damage);
-                                    Projectile::kill(index_proj as usize, world);
-                                    index_proj -= 1;
-                                }
-                                index_proj += 1
-                            }
-                        }
+                        Self::run_into_projectiles(index, world, &[new_pos]);
                         // simply updates the render queue
                         World::update_position(world, cur_pos, (new_pos, world.world_position));
                         world.enemies_map[world.world_position.y][world.world_position.x][index].pos[0] = new_pos;
                         cur_pos = new_pos;
                     }
-                } else if Self::match_color(&world.enemies_map[world.world_position.y][world.world_position.x][index].color, &tile::BOMBER_ENEMY) {
+                } else if kind == EnemyKind::Bomber {
                     // activate bomber if within range (no movement)
                     if Self::player_within_spaces(&cur_pos, &world, 2) {
-                        world.enemies_map[world.world_position.y][world.world_position.x][index].color = tile::BOMBER_ENEMY_ACTIVATED;
+                        world.enemies_map[world.world_position.y][world.world_position.x][index].kind = EnemyKind::BomberActivated;
                         let curr_world =
                             &mut world.entity_map[world.world_position.y][world.world_position.x];
-                        curr_world.insert(cur_pos, (world.enemies_map[world.world_position.y][world.world_position.x][index].color, Entity::Enemy));
+                        curr_world.insert(cur_pos, (TileKind::Enemy(EnemyKind::BomberActivated), Entity::Enemy));
                         return;
                     }
 
@@ -424,75 +618,48 @@ impl Enemy {
                         return;
                     }
                     if new_pos == world.player.pos {
-                        world.player.damage(world.enemies_map[world.world_position.y][world.world_position.x][index].attack_damage);
+                        world.player.damage(Damage::physical(world.enemies_map[world.world_position.y][world.world_position.x][index].attack_damage));
                     } else {
-                        let mut index_proj: i32 = 0;
-                        for _ in 0..world.projectiles.len() {
-                            if world.projectiles[index_proj as usize].color
-                                == tile::PROJECTILE_PLAYER
-                            {
-                                if new_pos == world.projectiles[index_proj as usize].pos
-                                    && world.enemies_map[world.world_position.y][world.world_position.x][index].world_pos
-                                        == world.projectiles[index_proj as usize].world_pos
-                                {
-                                    world.enemies_map[world.world_position.y][world.world_position.x][index]
-                                        .damage(world.projectiles[index_proj as usize].damage);
-                                    Projectile::kill(index_proj as usize, world);
-                                    index_proj -= 1;
-                                }
-                                index_proj += 1
-                            }
-                        }
+                        Self::run_into_projectiles(index, world, &[new_pos]);
                         // simply updates the render queue
                         World::update_position(world, cur_pos, (new_pos, world.world_position));
                         world.enemies_map[world.world_position.y][world.world_position.x][index].pos[0] = new_pos;
                         cur_pos = new_pos;
                     }
-                } else if Self::match_color(
-                    &world.enemies_map[world.world_position.y][world.world_position.x][index].color,
-                    &tile::BOMBER_ENEMY_ACTIVATED,
-                ) {
-                    world.enemies_map[world.world_position.y][world.world_position.x][index].color = tile::BOMBER_ENEMY_DEACTIVATED;
+                } else if kind == EnemyKind::BomberActivated {
+                    world.enemies_map[world.world_position.y][world.world_position.x][index].kind = EnemyKind::BomberDeactivated;
                     let curr_world =
                         &mut world.entity_map[world.world_position.y][world.world_position.x];
-                    curr_world.insert(cur_pos, (world.enemies_map[world.world_position.y][world.world_position.x][index].color, Entity::Enemy));
+                    curr_world.insert(cur_pos, (TileKind::Enemy(EnemyKind::BomberDeactivated), Entity::Enemy));
                     if Self::player_within_spaces(&cur_pos, &world, 2) {
-                        world.player.damage(world.enemies_map[world.world_position.y][world.world_position.x][index].attack_damage);
+                        world.player.damage(Damage::new(
+                            world.enemies_map[world.world_position.y][world.world_position.x][index].attack_damage,
+                            DamageKind::Explosive,
+                        ));
                     }
                     Self::create_bomber_explosion(index, world);
-                } else if Self::match_color(
-                    &world.enemies_map[world.world_position.y][world.world_position.x][index].color,
-                    &tile::BOMBER_ENEMY_DEACTIVATED,
-                ) {
+                } else if kind == EnemyKind::BomberDeactivated {
                     let pos = &world.enemies_map[world.world_position.y][world.world_position.x][index].pos;
                     world.entity_map[world.enemies_map[world.world_position.y][world.world_position.x][index].world_pos.y]
                         [world.enemies_map[world.world_position.y][world.world_position.x][index].world_pos.x]
                         .remove(&pos[0]);
                     world.enemies_map[world.world_position.y][world.world_position.x].remove(index);
-                } else if Self::match_color(
-                    &world.enemies_map[world.world_position.y][world.world_position.x][index].color,
-                    &tile::MAJOR_ENEMY
-                ) {
-                    let del_x = world.enemies_map[world.world_position.y][world.world_position.x][index].pos[4].x as i16 - world.player.pos.x as i16;
-                    let del_y = world.enemies_map[world.world_position.y][world.world_position.x][index].pos[4].y as i16 - world.player.pos.y as i16;
-
-                    let (move_x, move_y): (i16, i16) = {
-                        if del_x.abs() > del_y.abs() {
-                            if del_x > 0 {
-                                (-1, 0)
-                            } else {
-                                (1, 0)
-                            }
-                        } else {
-                            if del_y > 0 {
-                                (0, -1)
-                            } else {
-                                (0, 1)
-                            }
-                        }
-                    };
+                } else if kind == EnemyKind::Major {
+                    // a major's path is the one its top left tile takes, the flow field already
+                    // made sure all nine tiles fit wherever it goes
+                    if new_pos.x >= WORLD_SIZE.0 as usize || new_pos.y >= WORLD_SIZE.1 as usize {
+                        return;
+                    }
+                    let corner = world.enemies_map[world.world_position.y][world.world_position.x][index].pos[0];
+                    let (move_x, move_y) = (
+                        new_pos.x as i16 - corner.x as i16,
+                        new_pos.y as i16 - corner.y as i16,
+                    );
 
+                    // every tile it would take up is checked before it moves at all, and only the
+                    // tiles it newly takes up can have a projectile on them for it to run into
                     let positions = world.enemies_map[world.world_position.y][world.world_position.x][index].pos.clone();
+                    let mut entered = Vec::new();
                     for tile in &positions {
                         let new_x = tile.x as i16 + move_x;
                         let new_y = tile.y as i16 + move_y;
@@ -501,34 +668,28 @@ impl Enemy {
                             || new_y < 0 || new_y >= WORLD_SIZE.1 {
                             return;
                         } else if new_pos == world.player.pos {
-                            world.player.damage(world.enemies_map[world.world_position.y][world.world_position.x][index].attack_damage);
+                            world.player.damage(Damage::physical(world.enemies_map[world.world_position.y][world.world_position.x][index].attack_damage));
                             return;
                         }
 
-                        let mut index_proj: i32 = 0;
-                        for _ in 0..world.projectiles.len() {
-                            if world.projectiles[index_proj as usize].color
-                                == tile::PROJECTILE_PLAYER
-                            {
-                                if new_pos == world.projectiles[index_proj as usize].pos
-                                    && world.enemies_map[world.world_position.y][world.world_position.x][index].world_pos
-                                        == world.projectiles[index_proj as usize].world_pos
-                                {
-                                    world.enemies_map[world.world_position.y][world.world_position.x][index]
-                                        .damage(world.projectiles[index_proj as usize].damage);
-                                    Projectile::kill(index_proj as usize, world);
-                                    index_proj -= 1;
-                                }
-                                index_proj += 1
-                            }
-                        }
-
                         for i in 0..world.enemies_map[world.world_position.y][world.world_position.x].len() {
                             if i != index && world.enemies_map[world.world_position.y][world.world_position.x][i].pos.contains(&new_pos) {
                                 return;
                             }
                         }
+                        if pathfinding::terrain_cost(
+                            world.terrain_map[world.world_position.y][world.world_position.x].get(&new_pos).copied(),
+                            &world.balance.world,
+                        )
+                        .is_none()
+                        {
+                            return;
+                        }
+                        if !positions.contains(&new_pos) {
+                            entered.push(new_pos);
+                        }
                     }
+                    Self::run_into_projectiles(index, world, &entered);
 
                     if move_x == -1 || move_y == -1 {
                         for i in 0..positions.len() {
@@ -554,150 +715,406 @@ impl Enemy {
         }
     }
 
-    pub fn get_best_path(
-        index: usize,
-        world: &mut World,
-        can_dodge_projectiles: bool,
-    ) -> LinkedList<Position> {
-        // Used to check if the enemy should be able to dodge around player projectiles
+    // Shooters keep their distance from the player and try to get onto the player's row or
+    // column, firing along it once they are there, in range and nothing is in the way. They don't
+    // move on a turn they fire
+    fn move_shooter(index: usize, world: &mut World) {
+        let balance = world.balance.enemies.clone();
+        let enemy = &mut world.enemies_map[world.world_position.y][world.world_position.x][index];
+        if enemy.reload > 0 {
+            enemy.reload -= 1;
+        }
+        let (reload, speed, world_pos) = (enemy.reload, enemy.speed, enemy.world_pos);
+        let mut cur_pos = enemy.pos[0];
+        if reload == 0 {
+            if let Some(direction) = Self::line_of_fire(world, cur_pos, world_pos, balance.shooter_range) {
+                Self::fire(index, world, direction);
+                return;
+            }
+        }
 
-        let enemy = &world.enemies_map[world.world_position.y][world.world_position.x][index];
-        // this is a visited array to save if we have visited a location on the grid
-        let mut visited = [[false; WORLD_SIZE.0 as usize]; WORLD_SIZE.1 as usize];
-
-        // this stores every location's previous location so that we can reconstruct the best path
-        // given our start and end
-        let mut previous = [[Position::new(WORLD_SIZE.0 as usize + 1, WORLD_SIZE.1 as usize + 1);
-            WORLD_SIZE.0 as usize]; WORLD_SIZE.1 as usize];
-        let mut queue = LinkedList::new();
-        queue.push_back(enemy.pos[0]);
-
-        visited[enemy.pos[0].y][enemy.pos[0].x] = true;
-        // visited[enemy.pos.y - (world.world_position.y * WORLD_SIZE.1 as usize)][enemy.pos.x - (world.world_position.x * WORLD_SIZE.0 as usize)] = true;
-        while !queue.is_empty() {
-            if let Some(node) = queue.pop_front() {
-                if node == world.player.pos {
-                    // reached the goal location, break and reconstruct path
-                    break;
+        for _ in 0..speed {
+            // take the step that lines it up best, staying put if none is better than where it is
+            let mut best = (Self::shooter_score(world.player.pos, cur_pos, &balance), cur_pos);
+            for direction in [Direction::North, Direction::South, Direction::West, Direction::East] {
+                let new_pos = match Self::next_tile(cur_pos, direction) {
+                    Some(new_pos) => new_pos,
+                    None => continue,
+                };
+                if new_pos == world.player.pos
+                    || !Self::can_travel_to(
+                        world,
+                        (new_pos, world_pos),
+                        &world.entity_map,
+                        &world.terrain_map,
+                        &world.atmosphere_map,
+                        true,
+                    )
+                {
+                    continue;
                 }
-
-                // standard bfs stuff, for each neighbor, if it hasn't been visited, put it into
-                // the queue
-                let neighbors =
-                    Self::get_neighbors(world, node, can_dodge_projectiles, index, Entity::Enemy);
-                for next in neighbors {
-                    if !visited[next.y][next.x] {
-                        queue.push_back(next);
-                        visited[next.y][next.x] = true;
-
-                        // mark the previous of the neighbor as the node to reconstruct the path
-                        previous[next.y][next.x] = node;
-                    }
+                let score = Self::shooter_score(world.player.pos, new_pos, &balance);
+                if score < best.0 {
+                    best = (score, new_pos);
                 }
             }
+            if best.1 == cur_pos {
+                return;
+            }
+            World::update_position(world, cur_pos, (best.1, world.world_position));
+            world.enemies_map[world.world_position.y][world.world_position.x][index].pos[0] = best.1;
+            cur_pos = best.1;
         }
+    }
 
-        // This uses the previous 2 dimensional array to reconstruct the best path
-        let mut path = LinkedList::new();
-        let mut position = world.player.pos;
-        let enemy_pos = world.enemies_map[world.world_position.y][world.world_position.x][index].pos[0];
-        while position != enemy_pos {
-            path.push_front(position);
-
-            // if the position's or y is greater than the world size, that means that a path wasn't
-            // found, as it means the previous position did not have a previous, so we break out
-            if position.x as i16 >= WORLD_SIZE.0 {
-                break;
+    // How far a shooter at pos is from where it wants to be, 0 being on the player's row or
+    // column at a distance it can fire from. Backing off counts the most, then lining up
+    fn shooter_score(player_pos: Position, pos: Position, balance: &EnemyBalance) -> usize {
+        let delta_x = (player_pos.x as i32 - pos.x as i32).abs() as usize;
+        let delta_y = (player_pos.y as i32 - pos.y as i32).abs() as usize;
+        let distance = delta_x + delta_y;
+        let off_line = min(delta_x, delta_y);
+        let too_close = balance.shooter_keep_distance.saturating_sub(distance);
+        let too_far = distance.saturating_sub(balance.shooter_range);
+        too_close * 3 + off_line * 2 + too_far
+    }
+
+    // The direction to fire in if the player is on the same row or column within range, with
+    // nothing a projectile can't fly through in between
+    fn line_of_fire(
+        world: &World,
+        pos: Position,
+        world_pos: Position,
+        range: usize,
+    ) -> Option<Direction> {
+        if world_pos != world.world_position {
+            return None;
+        }
+        let (direction, distance) = Self::line_to(pos, world.player.pos)?;
+        if distance > range {
+            return None;
+        }
+        let mut tile = pos;
+        for _ in 1..distance {
+            tile = Self::next_tile(tile, direction)?;
+            if world.entity_map[world_pos.y][world_pos.x].contains_key(&tile)
+                || Boss::pos_inside_boss(world, tile, world_pos)
+            {
+                return None;
+            }
+            if let Some(kind) = world.terrain_map[world_pos.y][world_pos.x].get(&tile) {
+                if !Projectile::can_enter_terrain(*kind) {
+                    return None;
+                }
             }
-            position = previous[position.y][position.x];
         }
-        path
+        Some(direction)
     }
 
-    pub fn get_neighbors(
-        world: &mut World,
-        position: Position,
-        can_dodge_projectiles: bool,
-        index: usize,
-        entity_type: Entity,
-    ) -> Vec<Position> {
-        let directions = [
-            Direction::North,
-            Direction::South,
-            Direction::West,
-            Direction::East,
-        ];
-        let mut moves = Vec::new();
-
-        // loop through all the directions
-        for direction in directions {
-            let (new_pos, _) = World::new_position(
-                position,
-                direction,
-                world,
-                1,
-                entity_type.clone(),
-                Some(index),
-            );
-            // if the new position is valid(correct tiles & within bounds) add it to the potential
-            // neighbors
-            if new_pos != world.enemies_map[world.world_position.y][world.world_position.x][index].pos[0]
-                && Self::can_travel_to(
+    // Fires a shot from the tile next to the shooter, which hits the player right away if they
+    // are standing there
+    fn fire(index: usize, world: &mut World, direction: Direction) {
+        let balance = world.balance.enemies.clone();
+        let enemy = &mut world.enemies_map[world.world_position.y][world.world_position.x][index];
+        enemy.direction = direction;
+        enemy.reload = balance.shooter_reload;
+        let world_pos = enemy.world_pos;
+        let start = match Self::next_tile(enemy.pos[0], direction) {
+            Some(start) => start,
+            None => return,
+        };
+        if start == world.player.pos {
+            Self::shot_hit_player(world, Damage::physical(balance.shooter_damage));
+            return;
+        }
+        world.entity_map[world_pos.y][world_pos.x]
+            .insert(start, (TileKind::Projectile(ProjectileKind::Enemy), Entity::Projectile));
+        world.projectiles.push(Projectile::enemy_shot(
+            start.x,
+            start.y,
+            direction,
+            world_pos,
+            &balance,
+        ));
+    }
+
+    // A shooter's shot hurts the player and leaves them poisoned
+    pub fn shot_hit_player(world: &mut World, damage: Damage) {
+        world.player.damage(damage);
+        world.player.status.apply(StatusEffect::with_damage(
+            StatusKind::Poison,
+            world.balance.enemies.shooter_poison_turns,
+            world.balance.enemies.shooter_poison_damage,
+        ));
+    }
+
+    // Knights telegraph a lunge when the player is in reach along a row or column, then lunge on
+    // the next turn, moving up to their reach and hitting the player if they are still in the
+    // way. Returns if the knight telegraphed or lunged, which takes its whole turn
+    fn knight_lunge(index: usize, world: &mut World) -> bool {
+        let reach = world.balance.enemies.knight_reach;
+        let enemy = &mut world.enemies_map[world.world_position.y][world.world_position.x][index];
+        let world_pos = enemy.world_pos;
+        let mut cur_pos = enemy.pos[0];
+        if let Some(direction) = enemy.lunge.take() {
+            let damage = enemy.attack_damage;
+            for _ in 0..reach {
+                let new_pos = match Self::next_tile(cur_pos, direction) {
+                    Some(new_pos) => new_pos,
+                    None => break,
+                };
+                if new_pos == world.player.pos {
+                    world.player.damage(Damage::physical(damage));
+                    world.player.status.apply(StatusEffect::new(
+                        StatusKind::Slow,
+                        world.balance.enemies.knight_slow_turns,
+                    ));
+                    break;
+                }
+                if !Self::can_travel_to(
                     world,
-                    (new_pos, world.enemies_map[world.world_position.y][world.world_position.x][index].world_pos),
+                    (new_pos, world_pos),
                     &world.entity_map,
                     &world.terrain_map,
                     &world.atmosphere_map,
-                    can_dodge_projectiles,
+                    true,
+                ) {
+                    break;
+                }
+                World::update_position(world, cur_pos, (new_pos, world.world_position));
+                world.enemies_map[world.world_position.y][world.world_position.x][index].pos[0] = new_pos;
+                cur_pos = new_pos;
+            }
+            return true;
+        }
+
+        if world_pos != world.world_position {
+            return false;
+        }
+        let (direction, distance) = match Self::line_to(cur_pos, world.player.pos) {
+            Some(line) => line,
+            None => return false,
+        };
+        if distance > reach {
+            return false;
+        }
+        // the tiles before the player have to be clear for the lunge to reach them
+        let mut tile = cur_pos;
+        for _ in 1..distance {
+            tile = match Self::next_tile(tile, direction) {
+                Some(tile) => tile,
+                None => return false,
+            };
+            if !Self::can_travel_to(
+                world,
+                (tile, world_pos),
+                &world.entity_map,
+                &world.terrain_map,
+                &world.atmosphere_map,
+                true,
+            ) {
+                return false;
+            }
+        }
+        let enemy = &mut world.enemies_map[world.world_position.y][world.world_position.x][index];
+        enemy.direction = direction;
+        enemy.lunge = Some(direction);
+        true
+    }
+
+    // The direction and distance from one position to another on the same row or column
+    fn line_to(from: Position, to: Position) -> Option<(Direction, usize)> {
+        if from.x == to.x && from.y != to.y {
+            if to.y < from.y {
+                Some((Direction::North, from.y - to.y))
+            } else {
+                Some((Direction::South, to.y - from.y))
+            }
+        } else if from.y == to.y && from.x != to.x {
+            if to.x < from.x {
+                Some((Direction::West, from.x - to.x))
+            } else {
+                Some((Direction::East, to.x - from.x))
+            }
+        } else {
+            None
+        }
+    }
+
+    // The tile next to pos in the direction, None past the edge of the room
+    pub fn next_tile(pos: Position, direction: Direction) -> Option<Position> {
+        let (x, y) = match direction {
+            Direction::North => (pos.x as i16, pos.y as i16 - 1),
+            Direction::South => (pos.x as i16, pos.y as i16 + 1),
+            Direction::West => (pos.x as i16 - 1, pos.y as i16),
+            Direction::East => (pos.x as i16 + 1, pos.y as i16),
+        };
+        if x < 0 || x >= WORLD_SIZE.0 || y < 0 || y >= WORLD_SIZE.1 {
+            None
+        } else {
+            Some(Position::new(x as usize, y as usize))
+        }
+    }
+
+    // Works out the flow fields towards the player for the room's enemies to follow this turn.
+    // Other enemies are left out since they move during the turn, whether one is in the way is
+    // only checked when stepping, see flow_path
+    pub fn update_flow_fields(world: &World, fields: &mut FlowFields) {
+        let room = world.world_position;
+        if world.enemies_map[room.y][room.x].is_empty() {
+            return;
+        }
+        let goal = world.player.pos;
+        let ground = |tile| Self::field_cost(world, room, tile, false);
+        fields.ground.compute(goal, Footprint::SINGLE, ground);
+        fields.dodging.compute(goal, Footprint::SINGLE, |tile| {
+            Self::field_cost(world, room, tile, true)
+        });
+        fields.major.compute(goal, Footprint::MAJOR, ground);
+    }
+
+    // step_cost, except that the tiles of other enemies cost whatever they are standing on
+    pub fn field_cost(
+        world: &World,
+        room: Position,
+        tile: Position,
+        can_dodge_projectiles: bool,
+    ) -> Option<usize> {
+        match world.entity_map[room.y][room.x].get(&tile) {
+            Some((_, Entity::Enemy)) => pathfinding::terrain_cost(
+                world.terrain_map[room.y][room.x].get(&tile).copied(),
+                &world.balance.world,
+            ),
+            _ => Self::step_cost(world, room, tile, can_dodge_projectiles),
+        }
+    }
+
+    // The next speed tiles down the enemy's flow field, stopping early where every step closer is
+    // blocked for now. When the player can't be reached at all it is a single position off the
+    // room. A major's path is the one of its top left tile, pos[0]. See crowd for how enemies get
+    // around each other
+    pub fn flow_path(
+        index: usize,
+        world: &World,
+        navigation: &mut Navigation,
+    ) -> LinkedList<Position> {
+        let Navigation { pathfinder, flow_fields, crowd } = navigation;
+        let enemy = &world.enemies_map[world.world_position.y][world.world_position.x][index];
+        let can_dodge_projectiles = enemy.can_dodge_projectiles();
+        let field = match enemy.kind {
+            EnemyKind::Major => &flow_fields.major,
+            _ if can_dodge_projectiles => &flow_fields.dodging,
+            _ => &flow_fields.ground,
+        };
+        let mut path = LinkedList::new();
+        if field.distance(enemy.pos[0]).is_none() {
+            path.push_back(Position::new(WORLD_SIZE.0 as usize + 1, WORLD_SIZE.1 as usize + 1));
+            return path;
+        }
+        // a big enemy's own tiles don't get in its way, the slots of other enemies do
+        let step_cost = |tile: Position| {
+            if crowd.reserved(tile, index) {
+                None
+            } else if enemy.pos.contains(&tile) {
+                pathfinding::terrain_cost(
+                    world.terrain_map[enemy.world_pos.y][enemy.world_pos.x].get(&tile).copied(),
+                    &world.balance.world,
                 )
-                && world.enemies_map[world.world_position.y][world.world_position.x][index].world_pos == world.world_position
-            {
-                moves.push(new_pos);
+            } else {
+                Self::step_cost(world, enemy.world_pos, tile, can_dodge_projectiles)
+            }
+        };
+
+        // an enemy with a slot next to the player walks around the player to get to it
+        if let Some(slot) = crowd.slot(index) {
+            if slot != enemy.pos[0] {
+                let around_player =
+                    |tile: Position| if tile == world.player.pos { None } else { step_cost(tile) };
+                if let Some(mut path) =
+                    pathfinder.find_path(enemy.pos[0], Footprint::SINGLE, &[slot], around_player)
+                {
+                    while path.len() > enemy.speed {
+                        path.pop_back();
+                    }
+                    return path;
+                }
+            }
+        }
+
+        let mut pos = enemy.pos[0];
+        for _ in 0..enemy.speed {
+            match field.next_step(pos, &step_cost) {
+                Some(next) => {
+                    path.push_back(next);
+                    pos = next;
+                }
+                None => {
+                    // blocked, so it gets around whoever is in the way if it can. Only as its
+                    // first step, so it doesn't wander off sideways
+                    if path.is_empty() {
+                        path.extend(field.sidestep(pos, &step_cost));
+                    }
+                    break;
+                }
             }
         }
-        return moves;
+        path
+    }
+
+    // What stepping onto the tile costs an enemy, None if it can't. The player's tile counts as
+    // passable since that is where the path ends
+    pub fn step_cost(
+        world: &World,
+        room: Position,
+        tile: Position,
+        can_dodge_projectiles: bool,
+    ) -> Option<usize> {
+        if room != world.world_position
+            || !Self::can_travel_to(
+                world,
+                (tile, room),
+                &world.entity_map,
+                &world.terrain_map,
+                &world.atmosphere_map,
+                can_dodge_projectiles,
+            )
+        {
+            return None;
+        }
+        pathfinding::terrain_cost(
+            world.terrain_map[room.y][room.x].get(&tile).copied(),
+            &world.balance.world,
+        )
     }
 
     pub fn can_travel_to(
         // this is the (position_in_world, position_of_world)
         world: &World,
         position_info: (Position, Position),
-        entity_map: &[[HashMap<Position, ([f32; 4], Entity)>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
+        entity_map: &[[HashMap<Position, (TileKind, Entity)>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
              (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
-        terrain_map: &[[HashMap<Position, [f32; 4]>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
+        terrain_map: &[[HashMap<Position, TerrainKind>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
              (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
-        atmosphere_map: &[[HashMap<Position, [f32; 4]>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
+        atmosphere_map: &[[HashMap<Position, TileKind>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
              (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
         can_dodge_projectiles: bool,
     ) -> bool {
-        // check if there are any static or dynamic entities in the position
-        if terrain_map[position_info.1.y][position_info.1.x].contains_key(&position_info.0) {
-            let info = terrain_map[position_info.1.y][position_info.1.x].get(&position_info.0);
-            if let Some(info_under) = info {
-                if can_dodge_projectiles {
-                    if PERMISSIBLE_TILES_DODGING.contains(&info_under) {
-                        return true;
-                    }
-                } else {
-                    if PERMISSIBLE_TILES.contains(&info_under) {
-                        return true;
-                    }
-                }
+        // check if there are any static or dynamic entities in the position. Terrain that can be
+        // entered can still have something standing on it, now that enemies wade into water
+        if let Some(info_under) =
+            terrain_map[position_info.1.y][position_info.1.x].get(&position_info.0)
+        {
+            if !Self::can_enter_terrain(*info_under) {
+                return false;
             }
-            return false;
-        } else if entity_map[position_info.1.y][position_info.1.x].contains_key(&position_info.0) {
+        }
+        if entity_map[position_info.1.y][position_info.1.x].contains_key(&position_info.0) {
             let info = entity_map[position_info.1.y][position_info.1.x].get(&position_info.0);
             if let Some(info_under) = info {
-                if can_dodge_projectiles {
-                    if PERMISSIBLE_TILES_DODGING.contains(&info_under.0)
-                        || info_under.1 == Entity::Player
-                    {
-                        return true;
-                    }
-                } else {
-                    if PERMISSIBLE_TILES.contains(&info_under.0) || info_under.1 == Entity::Player {
-                        return true;
-                    }
+                if Self::can_enter_tile(info_under.0, can_dodge_projectiles)
+                    || info_under.1 == Entity::Player
+                {
+                    return true;
                 }
             }
             return false;
@@ -705,14 +1122,8 @@ impl Enemy {
         if atmosphere_map[position_info.1.y][position_info.1.x].contains_key(&position_info.0) {
             let info = atmosphere_map[position_info.1.y][position_info.1.x].get(&position_info.0);
             if let Some(info_under) = info {
-                if can_dodge_projectiles {
-                    if PERMISSIBLE_TILES_DODGING.contains(&info_under) {
-                        return true;
-                    }
-                } else {
-                    if PERMISSIBLE_TILES.contains(&info_under) {
-                        return true;
-                    }
+                if Self::can_enter_tile(*info_under, can_dodge_projectiles) {
+                    return true;
                 }
             }
             return false;
@@ -733,11 +1144,21 @@ impl Enemy {
         self.is_boss
     }
 
-    pub fn match_color(a: &[f32; 4], b: &[f32; 4]) -> bool {
-        const EPSILON: f32 = 0.000001;
-        return (a[0] - b[0]).abs() < EPSILON
-            && (a[1] - b[1]).abs() < EPSILON
-            && (a[2] - b[2]).abs() < EPSILON;
+    // Enemies can walk over grass and wade through water, but no other terrain
+    fn can_enter_terrain(kind: TerrainKind) -> bool {
+        kind == TerrainKind::Grass || kind == TerrainKind::Water
+    }
+
+    // Enemies walk through lightning that has already struck, and into the player's projectiles
+    // unless they dodge them. Anything else in the entity or atmosphere map blocks them
+    fn can_enter_tile(kind: TileKind, can_dodge_projectiles: bool) -> bool {
+        match kind {
+            TileKind::Projectile(ProjectileKind::Player) => !can_dodge_projectiles,
+            TileKind::Projectile(
+                ProjectileKind::LightningInitial | ProjectileKind::LightningSecondary,
+            ) => true,
+            _ => false,
+        }
     }
 
     pub fn player_within_spaces(pos: &Position, world: &World, spaces: i16) -> bool {
@@ -746,7 +1167,7 @@ impl Enemy {
             <= spaces as usize
     }
 
-    pub fn draw_bomber_explosion(world: &mut World, canvas: &mut graphics::Canvas) {
+    pub fn draw_bomber_explosion(world: &mut World, canvas: &mut graphics::Canvas, palette: &Palette) {
         let curr_world =
             &mut world.bomber_explosions[world.world_position.y][world.world_position.x];
         for tile in curr_world {
@@ -761,14 +1182,46 @@ impl Enemy {
                         TILE_SIZE.0 as i32,
                         TILE_SIZE.1 as i32,
                     ))
-                    .color(tile.1),
+                    .color(palette.bomber_explosion[tile.1]),
             )
         }
         world.bomber_explosions[world.world_position.y][world.world_position.x].clear();
     }
 
+    // Shows the tiles of every lunge that knights in the room have telegraphed
+    pub fn draw_knight_lunges(world: &World, canvas: &mut graphics::Canvas, palette: &Palette) {
+        let reach = world.balance.enemies.knight_reach;
+        for enemy in &world.enemies_map[world.world_position.y][world.world_position.x] {
+            if let Some(direction) = enemy.lunge {
+                let mut tile = enemy.pos[0];
+                for _ in 0..reach {
+                    tile = match Self::next_tile(tile, direction) {
+                        Some(tile) => tile,
+                        None => break,
+                    };
+                    canvas.draw(
+                        &graphics::Quad,
+                        graphics::DrawParam::new()
+                            .dest_rect(graphics::Rect::new_i32(
+                                tile.x as i32 * TILE_SIZE.0 as i32,
+                                (tile.y as i32 + UNIVERSAL_OFFSET as i32) * TILE_SIZE.1 as i32,
+                                TILE_SIZE.0 as i32,
+                                TILE_SIZE.1 as i32,
+                            ))
+                            .color(palette.knight_lunge),
+                    )
+                }
+            }
+        }
+    }
+
     pub fn create_bomber_explosion(index: usize, world: &mut World) {
         let pos = world.enemies_map[world.world_position.y][world.world_position.x][index].pos[0];
+        Self::create_explosion(pos, world);
+    }
+
+    // The blast of a bomber, or an explosive elite, going off at pos in the current room
+    pub fn create_explosion(pos: Position, world: &mut World) {
         for i in -2..=2_i16 {
             for j in -(2 - i.abs())..=(2 - i.abs()) {
                 let x = pos.x as i16 + i;
@@ -776,10 +1229,71 @@ impl Enemy {
                 if x >= 0 && x < WORLD_SIZE.0 && y >= 0 && y < WORLD_SIZE.1 {
                     world.bomber_explosions[world.world_position.y][world.world_position.x].push((
                         Position::new(x as usize, y as usize),
-                        tile::BOMBER_EXPLOSION[((i.abs() + j.abs()) / 2) as usize],
+                        ((i.abs() + j.abs()) / 2) as usize,
                     ));
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    // A major with its top left tile at anchor, already after the player. It walks into the
+    // player's projectiles rather than around them, so it can be made to run into one
+    fn major_at(world: &mut World, anchor: Position) {
+        let balance = world.balance.enemies.clone();
+        let mut major = Enemy::major_enemy(anchor.x, anchor.y, world.world_position, &balance);
+        major.can_dodge_projectiles = false;
+        major.ai = AiState::Chase;
+        major.last_seen = Some(world.player.pos);
+        world.place_enemy(major);
+    }
+
+    fn shoot_at(world: &mut World, pos: Position) {
+        let room = world.world_position;
+        let balance = &world.balance.projectiles;
+        let projectile =
+            Projectile::player_projectile(pos.x, pos.y, Direction::East, room, balance);
+        world.entity_map[room.y][room.x]
+            .insert(pos, (TileKind::Projectile(ProjectileKind::Player), Entity::Projectile));
+        world.projectiles.push(projectile);
+    }
+
+    fn turn(world: &mut World) {
+        Enemy::update(world, &mut Navigation::default(), &mut ChaCha8Rng::seed_from_u64(0));
+    }
+
+    #[test]
+    fn major_runs_into_projectiles_on_the_tiles_it_enters() {
+        let mut world = World::empty_room(Position::new(10, 21));
+        major_at(&mut world, Position::new(20, 20));
+        shoot_at(&mut world, Position::new(19, 21));
+        let health = world.enemies_map[0][0][0].health();
+
+        turn(&mut world);
+        let major = &world.enemies_map[0][0][0];
+        assert_eq!(major.pos[0], Position::new(19, 20));
+        assert_eq!(major.health(), health - world.balance.projectiles.player_damage);
+        assert!(world.projectiles.is_empty());
+    }
+
+    #[test]
+    fn blocked_major_leaves_projectiles_alone() {
+        // the player stands in the way of its next step, so it hits them instead of moving
+        let mut world = World::empty_room(Position::new(19, 21));
+        major_at(&mut world, Position::new(20, 20));
+        shoot_at(&mut world, Position::new(19, 20));
+        let health = world.enemies_map[0][0][0].health();
+
+        turn(&mut world);
+        let major = &world.enemies_map[0][0][0];
+        assert_eq!(major.pos[0], Position::new(20, 20));
+        assert_eq!(major.health(), health);
+        assert_eq!(world.projectiles.len(), 1);
+        assert!(world.player.health() < world.balance.player.max_health);
+    }
+}
@@ -1,7 +1,15 @@
-#[derive(PartialEq, Eq, Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(PartialEq, Eq, Hash, Clone, Debug, serde::Serialize, serde::Deserialize)]
 
 pub enum Entity {
     Player,
     Enemy,
     Projectile,
 }
+
+// Who a projectile was fired by. Projectiles only hurt the other side
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug, Default, serde::Serialize, serde::Deserialize)]
+pub enum Faction {
+    #[default]
+    Player,
+    Enemies,
+}
@@ -0,0 +1,220 @@
+use crate::{
+    action::Action,
+    balance::Balance,
+    pathfinding::Navigation,
+    replay::Replay,
+    save::{SaveError, SaveFile},
+    scheduler,
+    world::World,
+};
+
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use std::fs;
+use std::path::Path;
+
+// Name of the replay inside the save directory, it always ends at the turn of the save next to it
+pub const REPLAY_FILE: &'static str = "replay";
+// Name of the replay written when the player dies. It runs on past the save, so it is kept apart
+// from the replay that is continued when the save is loaded
+pub const DEATH_REPLAY_FILE: &'static str = "death_replay";
+
+// What happened when one action was fed into the game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurnOutcome {
+    // The action did not consume a turn (ability on cooldown, not enough energy...), so nothing
+    // else in the world moved
+    Ignored,
+    // A full turn was played: the player acted, then the bosses, projectiles and enemies (see
+    // scheduler::TURN_ORDER)
+    Played,
+    // A turn was played and the player died during it
+    PlayerDied,
+}
+
+// Things that happened during the last turn that the frontend may want to react to, e.g. by
+// autosaving
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TurnEvents {
+    // The player moved into another room
+    pub room_changed: bool,
+    // The boss of the current room was defeated
+    pub boss_killed: bool,
+}
+
+// The whole simulation without any window, audio or rendering attached. Everything that happens
+// in a turn goes through here, so it can be driven by the ggez frontend, a bot, or a test
+pub struct Game {
+    pub world: World,
+    pub rng: ChaCha8Rng,
+    // Every action accepted since the world was generated. None for games loaded from a save that
+    // was written before replays existed, since those can't be reproduced from a seed
+    pub replay: Option<Replay>,
+    // Turns played since the world was generated
+    pub turn: usize,
+    // Seed the world was generated from, None for saves from before it was recorded
+    pub seed: Option<u64>,
+    // What happened during the last turn that was played
+    pub events: TurnEvents,
+    // What enemies and missiles work out to find their way, kept from turn to turn
+    pub navigation: Navigation,
+}
+
+impl Game {
+    pub fn new(seed: u64, balance: &Balance) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let world = World::new(&mut rng, balance);
+        Game {
+            world,
+            rng,
+            replay: Some(Replay::new(seed, balance)),
+            turn: 0,
+            seed: Some(seed),
+            events: TurnEvents::default(),
+            navigation: Navigation::default(),
+        }
+    }
+
+    // Plays out a single turn for the given action, see scheduler for who acts when
+    pub fn step(&mut self, action: Action) -> TurnOutcome {
+        if !self.world.player.is_alive() {
+            return TurnOutcome::Ignored;
+        }
+        let world_position = self.world.world_position;
+        let bosses_defeated = self.bosses_defeated();
+        if !scheduler::play_turn(action, &mut self.world, &mut self.navigation, &mut self.rng) {
+            return TurnOutcome::Ignored;
+        }
+
+        self.turn += 1;
+        self.events = TurnEvents {
+            room_changed: self.world.world_position != world_position,
+            boss_killed: self.bosses_defeated() > bosses_defeated,
+        };
+        if let Some(replay) = &mut self.replay {
+            replay.record(action, &self.world);
+        }
+
+        if self.world.player.is_alive() {
+            TurnOutcome::Played
+        } else {
+            TurnOutcome::PlayerDied
+        }
+    }
+
+    pub fn bosses_defeated(&self) -> usize {
+        self.world
+            .boss_defeated
+            .iter()
+            .flatten()
+            .filter(|defeated| **defeated)
+            .count()
+    }
+
+    // Saves the game and its replay into the save directory. A dead player has nothing left to
+    // continue, so only the death replay is written then
+    pub fn save(&mut self, save_dir: &Path) -> Result<(), SaveError> {
+        if !self.world.player.is_alive() {
+            return self.save_replay(save_dir, DEATH_REPLAY_FILE);
+        }
+        SaveFile::write(self, save_dir)?;
+        self.save_replay(save_dir, REPLAY_FILE)
+    }
+
+    // Writes the replay of the run up to the player's death, since that is when a replay is most
+    // useful. The save and its replay are left as they were
+    pub fn save_death_replay(&mut self, save_dir: &Path) -> Result<(), SaveError> {
+        self.save_replay(save_dir, DEATH_REPLAY_FILE)
+    }
+
+    // Writes the replay of this run into the save directory, so a bug report only needs that one
+    // file
+    fn save_replay(&mut self, save_dir: &Path, file: &str) -> Result<(), SaveError> {
+        if let Some(replay) = &mut self.replay {
+            replay.checkpoint(&self.world);
+            fs::create_dir_all(save_dir)?;
+            replay.save(&save_dir.join(file))?;
+        }
+        Ok(())
+    }
+
+    // The balance isn't part of the save, the game continues with whatever balance is loaded now
+    pub fn load(save_dir: &Path, balance: &Balance) -> Result<Game, SaveError> {
+        let mut save = SaveFile::read_unmigrated(save_dir)?;
+        // set before upgrading it, older saves are compared with the balance they continue with
+        save.world.balance = balance.clone();
+        save.migrate();
+        // saves from before replays were recorded simply don't have this file
+        let mut replay = Replay::load(&save_dir.join(REPLAY_FILE)).ok();
+        // a replay only reproduces the run with the balance it was recorded with
+        if replay.as_ref().map_or(false, |replay| replay.balance != *balance) {
+            eprintln!("The balance changed since this game was started, its replay is not kept");
+            replay = None;
+        }
+        // the replay has to end at the save to be continued from it. One that runs past it (saves
+        // before the death replay had its own file overwrote the replay on death) is cut back to
+        // the save, one that ends before it can't reproduce the save
+        if let Some(recorded) = &mut replay {
+            if recorded.turn() > save.turn {
+                recorded.truncate(save.turn);
+            } else if recorded.turn() < save.turn {
+                eprintln!("The replay of this game ends before its save, it is not kept");
+                replay = None;
+            }
+        }
+        Ok(Game {
+            world: save.world,
+            rng: save.rng.restore(),
+            replay,
+            turn: save.turn,
+            seed: save.seed,
+            events: TurnEvents::default(),
+            navigation: Navigation::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ai::AiState, damage::Damage, direction::Direction, enemy::Enemy, utils::Position};
+
+    #[test]
+    fn steps_play_turns_until_the_player_dies() {
+        let mut game = Game::new(3, &Balance::default());
+        let actions = [
+            Action::Wait,
+            Action::Move(Direction::South),
+            Action::Face(Direction::North),
+            Action::Melee,
+            Action::Wait,
+        ];
+        for (turn, action) in actions.into_iter().enumerate() {
+            assert_eq!(game.step(action), TurnOutcome::Played);
+            assert_eq!(game.turn, turn + 1);
+        }
+        assert_eq!(game.replay.as_ref().map(Replay::turn), Some(actions.len()));
+
+        // a chaser right next to a player on their last health point finishes them off
+        let max_health = game.world.balance.player.max_health;
+        game.world.player.damage(Damage::physical(max_health - 1));
+        let (player, room) = (game.world.player.pos, game.world.world_position);
+        let next_to = [(1, 0), (0, 1), (-1, 0), (0, -1)]
+            .iter()
+            .map(|(x, y)| (player.x as i16 + x, player.y as i16 + y))
+            .filter(|(x, y)| *x >= 0 && *y >= 0)
+            .map(|(x, y)| Position::new(x as usize, y as usize))
+            .find(|tile| Enemy::step_cost(&game.world, room, *tile, false).is_some())
+            .unwrap();
+        let mut chaser = Enemy::chasing(next_to.x, next_to.y, room, &game.world.balance.enemies);
+        chaser.ai = AiState::Chase;
+        chaser.last_seen = Some(player);
+        game.world.place_enemy(chaser);
+        assert_eq!(game.step(Action::Wait), TurnOutcome::PlayerDied);
+        assert!(!game.world.player.is_alive());
+
+        // and nothing happens after that
+        assert_eq!(game.step(Action::Wait), TurnOutcome::Ignored);
+        assert_eq!(game.turn, actions.len() + 1);
+    }
+}
@@ -0,0 +1,35 @@
+use crate::{action::Action, direction::Direction, keymap::Command, utils::Position};
+
+// Translates a command into an action. The targeted abilities use the position queued by the
+// last Target, so they translate to nothing until a tile has been picked. Commands that don't
+// play a turn (targeting, saving, the controls and settings screens) are handled by the frontend
+// instead
+pub fn action_for(command: Command, queued_position: Option<Position>) -> Option<Action> {
+    match command {
+        Command::MoveDown => Some(Action::Move(Direction::South)),
+        Command::MoveUp => Some(Action::Move(Direction::North)),
+        Command::MoveLeft => Some(Action::Move(Direction::West)),
+        Command::MoveRight => Some(Action::Move(Direction::East)),
+        Command::FaceUp => Some(Action::Face(Direction::North)),
+        Command::FaceDown => Some(Action::Face(Direction::South)),
+        Command::FaceRight => Some(Action::Face(Direction::East)),
+        Command::FaceLeft => Some(Action::Face(Direction::West)),
+        Command::Melee => Some(Action::Melee),
+        Command::Projectile => Some(Action::Projectile),
+        Command::Heal => Some(Action::Heal),
+        Command::Build => queued_position.map(Action::Build),
+        Command::Lightning => queued_position.map(Action::Lightning),
+        Command::Teleport => queued_position.map(Action::Teleport),
+        Command::Slam => Some(Action::Slam),
+        Command::Fire => Some(Action::Fire),
+        Command::Invisibility => Some(Action::Invisibility),
+        Command::Missile => Some(Action::Missile),
+        Command::Wait => Some(Action::Wait),
+        Command::Target
+        | Command::Controls
+        | Command::Settings
+        | Command::CommandPrefix
+        | Command::Save
+        | Command::SaveAndQuit => None,
+    }
+}
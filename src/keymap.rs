@@ -0,0 +1,391 @@
+use crate::save;
+
+use ggez::event::MouseButton;
+use ggez::input::keyboard::KeyCode;
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+// Name of the keymap inside the save directory
+const KEYMAP_FILE: &'static str = "keymap.ron";
+
+// Every key that can be bound. Keys are written to the keymap by the name of their KeyCode
+const KEYS: [KeyCode; 95] = [
+    KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5,
+    KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9, KeyCode::Key0,
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G,
+    KeyCode::H, KeyCode::I, KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N,
+    KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R, KeyCode::S, KeyCode::T, KeyCode::U,
+    KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Escape, KeyCode::F1, KeyCode::F2, KeyCode::F3, KeyCode::F4, KeyCode::F5,
+    KeyCode::F6, KeyCode::F7, KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11,
+    KeyCode::F12, KeyCode::Insert, KeyCode::Home, KeyCode::Delete, KeyCode::End,
+    KeyCode::PageDown, KeyCode::PageUp, KeyCode::Left, KeyCode::Up, KeyCode::Right,
+    KeyCode::Down, KeyCode::Back, KeyCode::Return, KeyCode::Space, KeyCode::Tab,
+    KeyCode::Numpad0, KeyCode::Numpad1, KeyCode::Numpad2, KeyCode::Numpad3,
+    KeyCode::Numpad4, KeyCode::Numpad5, KeyCode::Numpad6, KeyCode::Numpad7,
+    KeyCode::Numpad8, KeyCode::Numpad9, KeyCode::Apostrophe, KeyCode::Backslash,
+    KeyCode::Colon, KeyCode::Comma, KeyCode::Equals, KeyCode::Grave, KeyCode::LAlt,
+    KeyCode::LBracket, KeyCode::LControl, KeyCode::LShift, KeyCode::Minus,
+    KeyCode::Period, KeyCode::RAlt, KeyCode::RBracket, KeyCode::RControl, KeyCode::RShift,
+    KeyCode::Semicolon, KeyCode::Slash, KeyCode::NumpadEnter, KeyCode::NumpadAdd,
+    KeyCode::NumpadSubtract, KeyCode::NumpadMultiply,
+];
+
+// Everything a key or mouse button can be bound to. The order here is the order of the controls
+// screen and of the keymap file
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub enum Command {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    FaceUp,
+    FaceDown,
+    FaceLeft,
+    FaceRight,
+    Melee,
+    Projectile,
+    Slam,
+    Fire,
+    Lightning,
+    Teleport,
+    Build,
+    Heal,
+    Invisibility,
+    Missile,
+    Wait,
+    // Picks the tile under the mouse for lightning, teleport and build
+    Target,
+    // Opens the controls screen
+    Controls,
+    // Opens the settings screen
+    Settings,
+    // Starts a chord, the next key is then looked up among the chord commands below
+    CommandPrefix,
+    Save,
+    SaveAndQuit,
+}
+
+impl Command {
+    pub const ALL: [Command; 25] = [
+        Command::MoveUp,
+        Command::MoveDown,
+        Command::MoveLeft,
+        Command::MoveRight,
+        Command::FaceUp,
+        Command::FaceDown,
+        Command::FaceLeft,
+        Command::FaceRight,
+        Command::Melee,
+        Command::Projectile,
+        Command::Slam,
+        Command::Fire,
+        Command::Lightning,
+        Command::Teleport,
+        Command::Build,
+        Command::Heal,
+        Command::Invisibility,
+        Command::Missile,
+        Command::Wait,
+        Command::Target,
+        Command::Controls,
+        Command::Settings,
+        Command::CommandPrefix,
+        Command::Save,
+        Command::SaveAndQuit,
+    ];
+
+    // Chord commands only come after CommandPrefix, so they can share keys with everything else
+    pub fn is_chord(&self) -> bool {
+        matches!(self, Command::Save | Command::SaveAndQuit)
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Command::MoveUp => "Move up",
+            Command::MoveDown => "Move down",
+            Command::MoveLeft => "Move left",
+            Command::MoveRight => "Move right",
+            Command::FaceUp => "Face up",
+            Command::FaceDown => "Face down",
+            Command::FaceLeft => "Face left",
+            Command::FaceRight => "Face right",
+            Command::Melee => "Melee",
+            Command::Projectile => "Projectile",
+            Command::Slam => "Slam",
+            Command::Fire => "Fire",
+            Command::Lightning => "Lightning",
+            Command::Teleport => "Teleport",
+            Command::Build => "Build",
+            Command::Heal => "Heal",
+            Command::Invisibility => "Invisibility",
+            Command::Missile => "Missile",
+            Command::Wait => "Wait",
+            Command::Target => "Target tile",
+            Command::Controls => "Controls",
+            Command::Settings => "Settings",
+            Command::CommandPrefix => "Command",
+            Command::Save => "Save (after Command)",
+            Command::SaveAndQuit => "Save and quit (after Command)",
+        }
+    }
+
+    fn default_bindings(&self) -> Vec<Binding> {
+        use Binding::{Key, Mouse};
+        match self {
+            Command::MoveUp => vec![Key(KeyCode::Up)],
+            Command::MoveDown => vec![Key(KeyCode::Down)],
+            Command::MoveLeft => vec![Key(KeyCode::Left)],
+            Command::MoveRight => vec![Key(KeyCode::Right)],
+            Command::FaceUp => vec![Key(KeyCode::W)],
+            Command::FaceDown => vec![Key(KeyCode::S)],
+            Command::FaceLeft => vec![Key(KeyCode::A)],
+            Command::FaceRight => vec![Key(KeyCode::D)],
+            Command::Melee => vec![Key(KeyCode::M)],
+            Command::Projectile => vec![Key(KeyCode::Space)],
+            Command::Slam => vec![Key(KeyCode::Z)],
+            Command::Fire => vec![Key(KeyCode::F)],
+            Command::Lightning => vec![Key(KeyCode::L)],
+            Command::Teleport => vec![Key(KeyCode::T)],
+            Command::Build => vec![Key(KeyCode::B)],
+            Command::Heal => vec![Key(KeyCode::H)],
+            Command::Invisibility => vec![Key(KeyCode::I)],
+            Command::Missile => vec![Key(KeyCode::X)],
+            Command::Wait => vec![],
+            Command::Target => vec![
+                Mouse(MouseButton::Left),
+                Mouse(MouseButton::Right),
+                Mouse(MouseButton::Middle),
+            ],
+            Command::Controls => vec![Key(KeyCode::F1)],
+            Command::Settings => vec![Key(KeyCode::F2)],
+            Command::CommandPrefix => vec![Key(KeyCode::Colon)],
+            Command::Save => vec![Key(KeyCode::W)],
+            Command::SaveAndQuit => vec![Key(KeyCode::Q)],
+        }
+    }
+}
+
+// A key or mouse button. Written to the keymap as a string: the name of the key (e.g. "Space",
+// "W", "Numpad8") or "MouseLeft", "MouseRight", "MouseMiddle", "Mouse4"...
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{:?}", key),
+            Binding::Mouse(MouseButton::Left) => write!(f, "MouseLeft"),
+            Binding::Mouse(MouseButton::Right) => write!(f, "MouseRight"),
+            Binding::Mouse(MouseButton::Middle) => write!(f, "MouseMiddle"),
+            Binding::Mouse(MouseButton::Other(button)) => write!(f, "Mouse{}", button),
+        }
+    }
+}
+
+impl std::str::FromStr for Binding {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let mouse = match name {
+            "MouseLeft" => Some(MouseButton::Left),
+            "MouseRight" => Some(MouseButton::Right),
+            "MouseMiddle" => Some(MouseButton::Middle),
+            _ => name
+                .strip_prefix("Mouse")
+                .and_then(|button| button.parse().ok())
+                .map(MouseButton::Other),
+        };
+        if let Some(button) = mouse {
+            return Ok(Binding::Mouse(button));
+        }
+        KEYS.iter()
+            .find(|key| format!("{:?}", key) == name)
+            .map(|key| Binding::Key(*key))
+            .ok_or_else(|| format!("unknown key {:?}", name))
+    }
+}
+
+impl serde::Serialize for Binding {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Binding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(de::Error::custom)
+    }
+}
+
+// Which keys and mouse buttons do what, read from keymap.ron in the save directory. The file is
+// written with the defaults the first time the game starts so it can be edited, or the keys can
+// be changed from the controls screen
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct Keymap {
+    bindings: BTreeMap<Command, Vec<Binding>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap {
+            bindings: Command::ALL
+                .iter()
+                .map(|command| (*command, command.default_bindings()))
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    // Reads the keymap, falling back to the defaults (and writing them out) if there is none.
+    // Commands missing from the file keep their default keys
+    pub fn load(save_dir: &Path) -> Self {
+        let path = save_dir.join(KEYMAP_FILE);
+        let keymap = match fs::read_to_string(&path) {
+            Ok(keymap_str) => match ron::from_str::<Keymap>(&keymap_str) {
+                Ok(mut keymap) => {
+                    for command in Command::ALL {
+                        keymap
+                            .bindings
+                            .entry(command)
+                            .or_insert_with(|| command.default_bindings());
+                    }
+                    keymap
+                }
+                Err(e) => {
+                    eprintln!("Couldn't parse {}, using the defaults: {}", path.display(), e);
+                    Keymap::default()
+                }
+            },
+            Err(_) => {
+                let keymap = Keymap::default();
+                if let Err(e) = keymap.save(save_dir) {
+                    eprintln!("Couldn't write {}: {}", path.display(), e);
+                }
+                keymap
+            }
+        };
+        for conflict in keymap.conflicts() {
+            eprintln!("{}", conflict);
+        }
+        keymap
+    }
+
+    pub fn save(&self, save_dir: &Path) -> std::io::Result<()> {
+        let serialized = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(save::serialize_error)?;
+        fs::create_dir_all(save_dir)?;
+        save::write_atomic(&save_dir.join(KEYMAP_FILE), serialized.as_bytes())
+    }
+
+    pub fn bindings(&self, command: Command) -> &[Binding] {
+        self.bindings
+            .get(&command)
+            .map(|bindings| bindings.as_slice())
+            .unwrap_or(&[])
+    }
+
+    // The command a key or mouse button is bound to. After CommandPrefix only the chord commands
+    // are looked at, otherwise only the others
+    pub fn command(&self, binding: Binding, chord: bool) -> Option<Command> {
+        Command::ALL
+            .iter()
+            .copied()
+            .filter(|command| command.is_chord() == chord)
+            .find(|command| self.bindings(*command).contains(&binding))
+    }
+
+    // Every other command the binding would clash with if it were bound to this command. An
+    // edited keymap file can have a binding on any number of them
+    pub fn conflicts_with(&self, command: Command, binding: Binding) -> Vec<Command> {
+        Command::ALL
+            .iter()
+            .copied()
+            .filter(|other| *other != command && other.is_chord() == command.is_chord())
+            .filter(|other| self.bindings(*other).contains(&binding))
+            .collect()
+    }
+
+    // Every binding that is used by two commands at once, only the first of which can ever run
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let mut conflicts = Vec::new();
+        for (index, command) in Command::ALL.iter().enumerate() {
+            for other in &Command::ALL[index + 1..] {
+                if command.is_chord() != other.is_chord() {
+                    continue;
+                }
+                for binding in self.bindings(*command) {
+                    if self.bindings(*other).contains(binding) {
+                        conflicts.push(Conflict {
+                            binding: *binding,
+                            first: *command,
+                            second: *other,
+                        });
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+
+    // Binds the command to only this key or button, taking it away from every command it
+    // conflicted with
+    pub fn rebind(&mut self, command: Command, binding: Binding) {
+        for other in self.conflicts_with(command, binding) {
+            if let Some(bindings) = self.bindings.get_mut(&other) {
+                bindings.retain(|other_binding| *other_binding != binding);
+            }
+        }
+        self.bindings.insert(command, vec![binding]);
+    }
+
+    pub fn unbind(&mut self, command: Command) {
+        self.bindings.insert(command, Vec::new());
+    }
+
+    // e.g. "Up, Numpad8", or "-" if nothing is bound
+    pub fn describe(&self, command: Command) -> String {
+        let bindings = self.bindings(command);
+        if bindings.is_empty() {
+            return "-".to_string();
+        }
+        bindings
+            .iter()
+            .map(|binding| binding.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conflict {
+    pub binding: Binding,
+    pub first: Command,
+    pub second: Command,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} is bound to both {} and {}, it only does {}",
+            self.binding,
+            self.first.describe(),
+            self.second.describe(),
+            self.first.describe()
+        )
+    }
+}
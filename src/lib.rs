@@ -0,0 +1,59 @@
+// The game itself. The command line and opening the window are in main.rs, and the benchmarks in
+// benches/ use the game from here too
+
+pub mod action;
+pub mod ai;
+pub mod autosave;
+pub mod balance;
+pub mod crowd;
+pub mod damage;
+pub mod direction;
+pub mod elite;
+pub mod enemy;
+pub mod entity;
+pub mod game;
+pub mod input;
+pub mod keymap;
+pub mod offscreen;
+pub mod pathfinding;
+pub mod player;
+pub mod projectile;
+pub mod random;
+pub mod replay;
+pub mod save;
+pub mod scheduler;
+pub mod slot;
+pub mod state;
+pub mod status;
+pub mod theme;
+pub mod tile;
+pub mod utils;
+pub mod world;
+
+// Constants that determine tile size and world size, where the world is a 2 dimensional array of
+// tiles
+
+//Offset to leave extra space on top of screen for health/energy indicators
+pub const UNIVERSAL_OFFSET: i16 = 5;
+
+// Define the world size which is (width, height)
+pub const WORLD_SIZE: (i16, i16) = (50, 50);
+// Define the board size; for now, doubled dimensions of WORLD_SIZE
+pub const BOARD_SIZE: (i16, i16) = (350, 350);
+// define the size of each tile which a square of pixels, size: (x, y) pixels.
+pub const TILE_SIZE: (i16, i16) = (16, 16);
+// define screen size in pixels. Will be grid size * tile size
+
+// Default directory for the save and replay, can be changed with --save-dir
+pub const SAVE_PATH: &'static str = "./serialization/";
+// Default seed the world is generated from, can be changed with --seed or --random-seed
+pub const RNG_SEED: u64 = 0;
+// Default file the balance is read from, can be changed with --balance
+pub const BALANCE_PATH: &'static str = "./balance.ron";
+// Directory the color themes that can be picked on the settings screen are read from
+pub const THEMES_PATH: &'static str = "./themes/";
+
+pub const SCREEN_SIZE: (f32, f32) = (
+    (WORLD_SIZE.0 as f32) * TILE_SIZE.0 as f32,
+    (WORLD_SIZE.1 as f32 + UNIVERSAL_OFFSET as f32) * TILE_SIZE.1 as f32,
+);
@@ -3,41 +3,138 @@ use std::env;
 use std::path;
 use std::fs::{OpenOptions, self};
 
-mod direction;
-mod enemy;
-mod entity;
-mod player;
-mod projectile;
-mod random;
-mod state;
-mod tile;
-mod utils;
-mod world;
+use hoffman_final_project::balance::Balance;
+use hoffman_final_project::replay::Replay;
+use hoffman_final_project::slot::{self, SLOT_COUNT};
+use hoffman_final_project::state::State;
+use hoffman_final_project::{BALANCE_PATH, RNG_SEED, SAVE_PATH, SCREEN_SIZE};
 
-use crate::state::State;
+const USAGE: &'static str = "\
+Usage: Hoffman-Final-Project [OPTIONS]
 
-// Constants that determine tile size and world size, where the world is a 2 dimensional array of
-// tiles
+Options:
+    --seed <N>         Generate new worlds from seed N (default 0)
+    --random-seed      Generate new worlds from a random seed, printed on startup
+    --save-dir <DIR>   Keep the save slots in DIR (default ./serialization/)
+    --balance <FILE>   Read the balance from FILE (default ./balance.ron)
+    --slot <N>         Save slot to use with --new-game or --continue (1 to 5)
+    --new-game         Skip the title screen and start a new game
+    --continue         Skip the title screen and load the slot, or the most recent save
+    --replay <FILE>    Re-run a replay file without opening a window and check it
+    --help             Print this message";
 
-//Offset to leave extra space on top of screen for health/energy indicators
-pub const UNIVERSAL_OFFSET: i16 = 5;
-
-// Define the world size which is (width, height)
-pub const WORLD_SIZE: (i16, i16) = (50, 50);
-// Define the board size; for now, doubled dimensions of WORLD_SIZE
-pub const BOARD_SIZE: (i16, i16) = (350, 350);
-// define the size of each tile which a square of pixels, size: (x, y) pixels.
-pub const TILE_SIZE: (i16, i16) = (16, 16);
-// define screen size in pixels. Will be grid size * tile size
+// Where the game starts once the window is open
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StartMode {
+    TitleScreen,
+    NewGame,
+    Continue,
+}
 
-pub const SAVE_PATH: &'static str = "./serialization/";
+#[derive(Debug, Clone, PartialEq)]
+struct Options {
+    seed: u64,
+    save_dir: path::PathBuf,
+    // None reads BALANCE_PATH, writing it with the defaults if it isn't there
+    balance: Option<path::PathBuf>,
+    // Counted from 0, None picks a slot depending on the start mode
+    slot: Option<usize>,
+    start: StartMode,
+    replay: Option<path::PathBuf>,
+}
 
-pub const SCREEN_SIZE: (f32, f32) = (
-    (WORLD_SIZE.0 as f32) * TILE_SIZE.0 as f32,
-    (WORLD_SIZE.1 as f32 + UNIVERSAL_OFFSET as f32) * TILE_SIZE.1 as f32,
-);
+impl Options {
+    // Parses the command line, the first argument being the program name. On a bad argument
+    // returns the message to show instead of starting
+    fn parse(args: &[String]) -> Result<Options, String> {
+        let mut options = Options {
+            seed: RNG_SEED,
+            save_dir: path::PathBuf::from(SAVE_PATH),
+            balance: None,
+            slot: None,
+            start: StartMode::TitleScreen,
+            replay: None,
+        };
+        let mut args = args.iter().skip(1);
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let value = args.next().ok_or("--seed needs a number")?;
+                    options.seed = value
+                        .parse()
+                        .map_err(|_| format!("--seed needs a number, got {}", value))?;
+                }
+                "--random-seed" => options.seed = rand::random(),
+                "--save-dir" => {
+                    let value = args.next().ok_or("--save-dir needs a directory")?;
+                    options.save_dir = path::PathBuf::from(value);
+                }
+                "--balance" => {
+                    let value = args.next().ok_or("--balance needs a file")?;
+                    options.balance = Some(path::PathBuf::from(value));
+                }
+                "--slot" => {
+                    let value = args.next().ok_or("--slot needs a number")?;
+                    match value.parse::<usize>() {
+                        Ok(slot) if slot >= 1 && slot <= SLOT_COUNT => options.slot = Some(slot - 1),
+                        _ => {
+                            return Err(format!(
+                                "--slot needs a number from 1 to {}, got {}",
+                                SLOT_COUNT, value
+                            ))
+                        }
+                    }
+                }
+                "--new-game" => options.start = StartMode::NewGame,
+                "--continue" => options.start = StartMode::Continue,
+                "--replay" => {
+                    let value = args.next().ok_or("--replay needs a replay file")?;
+                    options.replay = Some(path::PathBuf::from(value));
+                }
+                "--help" | "-h" => return Err(USAGE.to_string()),
+                _ => return Err(format!("Unknown option {}\n\n{}", arg, USAGE)),
+            }
+        }
+        Ok(options)
+    }
+}
 
 fn main() -> GameResult {
+    let args: Vec<String> = env::args().collect();
+    let options = match Options::parse(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+
+    // re-runs a recorded game without opening a window and checks that it ends up in exactly
+    // the same world. Replays carry the balance they were recorded with, so the balance file is
+    // neither read nor written
+    if let Some(replay) = &options.replay {
+        run_replay(replay);
+    }
+
+    // a balance file with a mistake in it should stop the game rather than quietly play with the
+    // defaults
+    let balance = match &options.balance {
+        Some(path) => Balance::load(path),
+        None => Balance::load_or_create(path::Path::new(BALANCE_PATH)),
+    };
+    let balance = match balance {
+        Ok(balance) => balance,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+
+    println!("Seed: {}", options.seed);
+    if let Err(e) = slot::adopt_unslotted_save(&options.save_dir) {
+        eprintln!("Couldn't move the save into a slot: {}", e);
+    }
+
     let resource_dir = if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
         let mut path = path::PathBuf::from(manifest_dir);
         path.push("songs");
@@ -56,13 +153,48 @@ fn main() -> GameResult {
         .add_resource_path(resource_dir)
         .build()?;
 
-    // let state = if (save) {
-    //     let
-    //     State::from()
-    // }
     // Next we create a new instance of our GameState struct, which implements EventHandler
-    let state = State::title_screen(&mut ctx)?;
+    let state = match options.start {
+        StartMode::TitleScreen => {
+            let slot = options.slot.unwrap_or(0);
+            State::title_screen(&mut ctx, options.seed, balance, options.save_dir, slot)?
+        }
+        StartMode::NewGame => {
+            let slot = options.slot.unwrap_or(0);
+            State::new(&mut ctx, false, options.seed, balance, options.save_dir, slot)?
+        }
+        // falls back to the title screen, explaining why, if there is nothing to continue
+        StartMode::Continue => {
+            let (seed, save_dir, slot) = (options.seed, options.save_dir, options.slot);
+            State::load_save(&mut ctx, seed, balance, save_dir, slot)?
+        }
+    };
 
     // And finally we actually run our game, passing in our context and state.
     event::run(ctx, events_loop, state)
 }
+
+fn run_replay(path: &path::Path) -> ! {
+    let replay = match Replay::load(path) {
+        Ok(replay) => replay,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    match replay.verify() {
+        Ok(game) => {
+            println!(
+                "Replay OK: {} turns, {} checkpoints matched, player {}",
+                replay.turn(),
+                replay.checkpoints.len(),
+                if game.world.player.is_alive() { "alive" } else { "dead" }
+            );
+            std::process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("Replay diverged at {}", e);
+            std::process::exit(1);
+        }
+    }
+}
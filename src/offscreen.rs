@@ -0,0 +1,194 @@
+use crate::{
+    direction::Direction,
+    enemy::Enemy,
+    random,
+    tile::EnemyKind,
+    utils::Position,
+    world::{World, BOSS_ROOMS},
+    BOARD_SIZE, WORLD_SIZE,
+};
+
+use rand_chacha::ChaCha8Rng;
+use std::cmp::min;
+
+// Rooms on each side of the board
+const ROOMS: (i32, i32) = (
+    (BOARD_SIZE.0 / WORLD_SIZE.0) as i32,
+    (BOARD_SIZE.1 / WORLD_SIZE.1) as i32,
+);
+const DIRECTIONS: [Direction; 4] = [
+    Direction::North,
+    Direction::South,
+    Direction::West,
+    Direction::East,
+];
+
+/*
+ * The rooms around the player keep going while the player isn't in them, but only coarsely so it
+ * stays cheap:
+ *      - Only rooms within balance.world.offscreen_radius of the player's room are simulated, and
+ *        boss rooms never are, their enemies belong to the fight
+ *      - In each room at most offscreen_moves_per_room enemies move a turn, starting from a random
+ *        one so every enemy gets its turn over time
+ *      - A move is a single step, towards the player (even though they are in another room) or in
+ *        a random direction, always a random one while the player is invisible. Enemies don't
+ *        fight, shoot or explode while off screen, and majors and lit bombers wait for the player
+ *        to come back
+ *      - An enemy heading for the player from the edge of its room crosses into the next room
+ *        (see Enemy::migrate), which is how enemies follow the player into their room. Elites
+ *        don't, they keep to their rooms
+ * Everything random comes from the game's RNG, so replays still reproduce the simulation
+ */
+pub fn simulate(world: &mut World, rng: &mut ChaCha8Rng) {
+    let radius = world.balance.world.offscreen_radius as i32;
+    let current = world.world_position;
+    for delta_y in -radius..=radius {
+        for delta_x in -radius..=radius {
+            let (x, y) = (current.x as i32 + delta_x, current.y as i32 + delta_y);
+            if (delta_x == 0 && delta_y == 0) || x < 0 || y < 0 || x >= ROOMS.0 || y >= ROOMS.1 {
+                continue;
+            }
+            let room = Position::new(x as usize, y as usize);
+            if !BOSS_ROOMS.contains(&room) {
+                simulate_room(world, room, rng);
+            }
+        }
+    }
+}
+
+fn simulate_room(world: &mut World, room: Position, rng: &mut ChaCha8Rng) {
+    let count = world.enemies_map[room.y][room.x].len();
+    if count == 0 {
+        return;
+    }
+    let moves = min(count, world.balance.world.offscreen_moves_per_room);
+    let start = random::rand_range(rng, 0, count as i16) as usize;
+    for i in 0..moves {
+        // an enemy leaving shifts the ones after it, the rest of the room waits for next turn
+        if coarse_move(world, room, (start + i) % count, rng) {
+            return;
+        }
+    }
+}
+
+// Returns if the enemy left the room
+fn coarse_move(world: &mut World, room: Position, index: usize, rng: &mut ChaCha8Rng) -> bool {
+    let enemy = &world.enemies_map[room.y][room.x][index];
+    if !matches!(
+        enemy.kind,
+        EnemyKind::Chaser | EnemyKind::Bomber | EnemyKind::Knight | EnemyKind::Shooter
+    ) {
+        return false;
+    }
+    let pos = enemy.pos[0];
+    // nobody can track an invisible player, so everyone wanders
+    let pursuing = world.player.is_visible()
+        && random::bernoulli(rng, world.balance.world.offscreen_pursuit);
+    let direction = if pursuing {
+        towards_player(world, room, pos)
+    } else {
+        DIRECTIONS[random::rand_range(rng, 0, DIRECTIONS.len() as i16) as usize]
+    };
+    let new_pos = match Enemy::next_tile(pos, direction) {
+        Some(new_pos) => new_pos,
+        // enemies tracking the player follow them over the edge of the room, wandering ones stay
+        None => return pursuing && Enemy::migrate(world, room, index, direction),
+    };
+    if world.entity_map[room.y][room.x].contains_key(&new_pos)
+        || !Enemy::can_travel_to(
+            world,
+            (new_pos, room),
+            &world.entity_map,
+            &world.terrain_map,
+            &world.atmosphere_map,
+            true,
+        )
+    {
+        return false;
+    }
+    World::update_position(world, pos, (new_pos, room));
+    let enemy = &mut world.enemies_map[room.y][room.x][index];
+    enemy.pos[0] = new_pos;
+    enemy.direction = direction;
+    false
+}
+
+// The way from pos to the player, with the player's position counted from the top left of the
+// enemy's room so it can lie outside of it
+fn towards_player(world: &World, room: Position, pos: Position) -> Direction {
+    let target_x = (world.world_position.x as i32 - room.x as i32) * WORLD_SIZE.0 as i32
+        + world.player.pos.x as i32;
+    let target_y = (world.world_position.y as i32 - room.y as i32) * WORLD_SIZE.1 as i32
+        + world.player.pos.y as i32;
+    let (delta_x, delta_y) = (target_x - pos.x as i32, target_y - pos.y as i32);
+    if delta_x.abs() > delta_y.abs() {
+        if delta_x > 0 {
+            Direction::East
+        } else {
+            Direction::West
+        }
+    } else if delta_y > 0 {
+        Direction::South
+    } else {
+        Direction::North
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        elite::Affix,
+        status::{StatusEffect, StatusKind},
+    };
+    use rand::SeedableRng;
+
+    #[test]
+    fn elites_stay_in_their_room() {
+        let mut world = World::empty_room(Position::new(5, 25));
+        let (room, next) = (Position::new(0, 0), Position::new(1, 0));
+        let balance = world.balance.enemies.clone();
+        world.place_enemy(Enemy::chasing(49, 25, room, &balance));
+        let elite = Enemy::chasing(49, 26, room, &balance).elite(vec![Affix::Fast], &balance);
+        world.place_enemy(elite);
+
+        assert!(!Enemy::migrate(&mut world, room, 1, Direction::East));
+        assert!(Enemy::migrate(&mut world, room, 0, Direction::East));
+        let left: Vec<_> = world.enemies_map[room.y][room.x].iter().map(Enemy::is_elite).collect();
+        assert_eq!(left, [true]);
+        let arrived = &world.enemies_map[next.y][next.x];
+        assert!(arrived
+            .iter()
+            .any(|enemy| !enemy.is_elite() && enemy.pos[0] == Position::new(0, 25)));
+    }
+
+    // A chaser on the edge of the room next to the player's, that always goes after the player
+    fn chaser_next_door() -> (World, Position) {
+        let mut world = World::empty_room(Position::new(5, 25));
+        world.balance.world.offscreen_pursuit = 1.0;
+        let next = Position::new(1, 0);
+        let chaser = Enemy::chasing(0, 25, next, &world.balance.enemies);
+        world.place_enemy(chaser);
+        (world, next)
+    }
+
+    #[test]
+    fn enemies_follow_the_player_next_door() {
+        let (mut world, next) = chaser_next_door();
+        simulate(&mut world, &mut ChaCha8Rng::seed_from_u64(0));
+        assert!(world.enemies_map[next.y][next.x].is_empty());
+        assert_eq!(world.enemies_map[0][0].len(), 1);
+    }
+
+    #[test]
+    fn nobody_follows_an_invisible_player() {
+        let (mut world, next) = chaser_next_door();
+        world.player.status.apply(StatusEffect::new(StatusKind::Invisible, 50));
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        for _ in 0..50 {
+            simulate(&mut world, &mut rng);
+            assert!(world.enemies_map[0][0].is_empty());
+        }
+        assert_eq!(world.enemies_map[next.y][next.x].len(), 1);
+    }
+}
@@ -0,0 +1,520 @@
+use crate::{
+    balance::WorldBalance, crowd::Crowd, direction::Direction, tile::TerrainKind, utils::Position,
+    WORLD_SIZE,
+};
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, LinkedList},
+};
+
+const TILES: usize = WORLD_SIZE.0 as usize * WORLD_SIZE.1 as usize;
+const DIRECTIONS: [Direction; 4] = [
+    Direction::North,
+    Direction::South,
+    Direction::West,
+    Direction::East,
+];
+
+/*
+ * The shortest paths enemies and missiles take through a room. Every step costs what the caller
+ * says the tile it steps onto costs:
+ *      - 1 is a plain step. Anything walking has to pay more to wade through water (see
+ *        terrain_cost), so it goes around a lake when that's not much longer
+ *      - None can't be stepped on at all, like lava, walls or another enemy
+ * Paths never leave the room, and the mover doesn't have to be one tile big: a footprint moves
+ * its top left tile and needs every tile it covers to be passable
+ */
+
+// Tiles something covers, counted from its top left tile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Footprint {
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Default for Footprint {
+    fn default() -> Self {
+        Footprint::SINGLE
+    }
+}
+
+impl Footprint {
+    pub const SINGLE: Footprint = Footprint::new(1, 1);
+
+    // Majors, with their top left tile first in Enemy::pos
+    pub const MAJOR: Footprint = Footprint::new(3, 3);
+
+    pub const fn new(width: usize, height: usize) -> Self {
+        Footprint { width, height }
+    }
+
+    pub fn tiles(self, anchor: Position) -> impl Iterator<Item = Position> {
+        (0..self.height).flat_map(move |y| {
+            (0..self.width).map(move |x| Position::new(anchor.x + x, anchor.y + y))
+        })
+    }
+
+    // Whether the footprint fits in the room with its top left tile there
+    fn fits(self, anchor: Position) -> bool {
+        anchor.x + self.width <= WORLD_SIZE.0 as usize
+            && anchor.y + self.height <= WORLD_SIZE.1 as usize
+    }
+
+    fn covers(self, anchor: Position, tile: Position) -> bool {
+        tile.x >= anchor.x
+            && tile.x < anchor.x + self.width
+            && tile.y >= anchor.y
+            && tile.y < anchor.y + self.height
+    }
+
+    // What stepping there costs: a footprint is as slow as the slowest tile it steps onto, and
+    // can't go there if any of them can't be stepped on
+    pub fn cost(
+        self,
+        anchor: Position,
+        step_cost: &impl Fn(Position) -> Option<usize>,
+    ) -> Option<usize> {
+        let mut cost = 0;
+        for tile in self.tiles(anchor) {
+            cost = cost.max(step_cost(tile)?);
+        }
+        Some(cost)
+    }
+
+    // Fewest steps until it covers the tile
+    fn distance(self, anchor: Position, tile: Position) -> usize {
+        let axis = |tile: usize, start: usize, size: usize| {
+            if tile < start {
+                start - tile
+            } else {
+                tile.saturating_sub(start + size - 1)
+            }
+        };
+        axis(tile.x, anchor.x, self.width) + axis(tile.y, anchor.y, self.height)
+    }
+}
+
+// How many plain steps a step onto the terrain is worth for anything walking, None if it can't
+// be walked on. Positions missing from the terrain map are plain floor
+pub fn terrain_cost(terrain: Option<TerrainKind>, balance: &WorldBalance) -> Option<usize> {
+    match terrain {
+        None | Some(TerrainKind::Grass) => Some(1),
+        Some(TerrainKind::Water) => Some(balance.water_cost.max(1)),
+        Some(
+            TerrainKind::Lava | TerrainKind::Wall | TerrainKind::Portal | TerrainKind::Mountain(_),
+        ) => None,
+    }
+}
+
+// A* over one room. The buffers are kept between searches so that the dozens of searches every
+// turn don't each allocate a room's worth of them, only what the last search touched is reset
+#[derive(Debug, Clone, Default)]
+pub struct Pathfinder {
+    // Cheapest cost found so far to each tile, usize::MAX if it wasn't reached
+    cost: Vec<usize>,
+    // The tile each tile was reached from
+    previous: Vec<usize>,
+    closed: Vec<bool>,
+    // (estimated total cost, estimate left, tile) of the tiles still to look at
+    open: BinaryHeap<Reverse<(usize, usize, usize)>>,
+    touched: Vec<usize>,
+}
+
+impl Pathfinder {
+    // The cheapest path for the footprint from start until it covers one of the goals, without
+    // start itself. Returns None if none of the goals can be reached, and an empty path if one is
+    // already covered
+    pub fn find_path(
+        &mut self,
+        start: Position,
+        footprint: Footprint,
+        goals: &[Position],
+        step_cost: impl Fn(Position) -> Option<usize>,
+    ) -> Option<LinkedList<Position>> {
+        self.reset();
+        if goals.is_empty() || !footprint.fits(start) {
+            return None;
+        }
+        let estimate = |anchor: Position| {
+            goals
+                .iter()
+                .map(|goal| footprint.distance(anchor, *goal))
+                .min()
+                .unwrap_or(0)
+        };
+
+        let start_index = index(start);
+        self.visit(start_index, 0, start_index);
+        self.open.push(Reverse((estimate(start), estimate(start), start_index)));
+        while let Some(Reverse((_, _, current))) = self.open.pop() {
+            if self.closed[current] {
+                continue;
+            }
+            self.closed[current] = true;
+            let anchor = position(current);
+            if goals.iter().any(|goal| footprint.covers(anchor, *goal)) {
+                return Some(self.path(start_index, current));
+            }
+            for direction in DIRECTIONS {
+                let next = match step(anchor, direction) {
+                    Some(next) if footprint.fits(next) => next,
+                    _ => continue,
+                };
+                let next_index = index(next);
+                if self.closed[next_index] {
+                    continue;
+                }
+                let cost = match footprint.cost(next, &step_cost) {
+                    Some(cost) => cost,
+                    None => continue,
+                };
+                let total = self.cost[current] + cost;
+                if total < self.cost[next_index] {
+                    self.visit(next_index, total, current);
+                    let left = estimate(next);
+                    self.open.push(Reverse((total + left, left, next_index)));
+                }
+            }
+        }
+        None
+    }
+
+    fn reset(&mut self) {
+        if self.cost.len() != TILES {
+            self.cost = vec![usize::MAX; TILES];
+            self.previous = vec![0; TILES];
+            self.closed = vec![false; TILES];
+            self.touched.clear();
+        }
+        for tile in self.touched.drain(..) {
+            self.cost[tile] = usize::MAX;
+            self.closed[tile] = false;
+        }
+        self.open.clear();
+    }
+
+    fn visit(&mut self, tile: usize, cost: usize, previous: usize) {
+        if self.cost[tile] == usize::MAX {
+            self.touched.push(tile);
+        }
+        self.cost[tile] = cost;
+        self.previous[tile] = previous;
+    }
+
+    fn path(&self, start: usize, end: usize) -> LinkedList<Position> {
+        let mut path = LinkedList::new();
+        let mut tile = end;
+        while tile != start {
+            path.push_front(position(tile));
+            tile = self.previous[tile];
+        }
+        path
+    }
+}
+
+fn index(pos: Position) -> usize {
+    pos.y * WORLD_SIZE.0 as usize + pos.x
+}
+
+fn position(index: usize) -> Position {
+    Position::new(index % WORLD_SIZE.0 as usize, index / WORLD_SIZE.0 as usize)
+}
+
+// The next tile that way, None off the top or left edge of the room. Going off the other two
+// edges is caught by Footprint::fits
+fn step(pos: Position, direction: Direction) -> Option<Position> {
+    match direction {
+        Direction::North => pos.y.checked_sub(1).map(|y| Position::new(pos.x, y)),
+        Direction::South => Some(Position::new(pos.x, pos.y + 1)),
+        Direction::West => pos.x.checked_sub(1).map(|x| Position::new(x, pos.y)),
+        Direction::East => Some(Position::new(pos.x + 1, pos.y)),
+    }
+}
+
+// The cheapest way from every tile of a room to one goal, found with a single search out from the
+// goal. However many enemies are after the goal, each of them just walks downhill from where it
+// stands instead of searching for its own path. A field is for one footprint, its tiles being the
+// top left tiles of the footprint
+#[derive(Debug, Clone, Default)]
+pub struct FlowField {
+    footprint: Footprint,
+    // What it costs to get from each tile to the goal, usize::MAX where the goal can't be reached
+    distance: Vec<usize>,
+    open: BinaryHeap<Reverse<(usize, usize)>>,
+}
+
+impl FlowField {
+    // Dijkstra out from the goal, which is reached once the footprint covers it. step_cost is what
+    // stepping onto a tile costs, like for Pathfinder::find_path, and nothing gets through where
+    // the footprint can't stand
+    pub fn compute(
+        &mut self,
+        goal: Position,
+        footprint: Footprint,
+        step_cost: impl Fn(Position) -> Option<usize>,
+    ) {
+        self.footprint = footprint;
+        self.distance.clear();
+        self.distance.resize(TILES, usize::MAX);
+        self.open.clear();
+        if goal.x >= WORLD_SIZE.0 as usize || goal.y >= WORLD_SIZE.1 as usize {
+            return;
+        }
+        for y in goal.y.saturating_sub(footprint.height - 1)..=goal.y {
+            for x in goal.x.saturating_sub(footprint.width - 1)..=goal.x {
+                let anchor = Position::new(x, y);
+                if footprint.fits(anchor) {
+                    self.distance[index(anchor)] = 0;
+                    self.open.push(Reverse((0, index(anchor))));
+                }
+            }
+        }
+        while let Some(Reverse((distance, current))) = self.open.pop() {
+            if distance > self.distance[current] {
+                continue;
+            }
+            let anchor = position(current);
+            // the cost of stepping here from any of the neighbours. Wherever the goal is reached
+            // counts as a plain step, that is where the attack lands
+            let cost = match footprint.cost(anchor, &step_cost) {
+                Some(cost) => cost,
+                None if distance == 0 => 1,
+                None => continue,
+            };
+            for direction in DIRECTIONS {
+                let next = match step(anchor, direction) {
+                    Some(next) if footprint.fits(next) => next,
+                    _ => continue,
+                };
+                let next_index = index(next);
+                if distance + cost < self.distance[next_index] {
+                    self.distance[next_index] = distance + cost;
+                    self.open.push(Reverse((distance + cost, next_index)));
+                }
+            }
+        }
+    }
+
+    // What it costs to get from the tile to the goal, None if it can't
+    pub fn distance(&self, pos: Position) -> Option<usize> {
+        if !self.footprint.fits(pos) {
+            return None;
+        }
+        self.distance
+            .get(index(pos))
+            .copied()
+            .filter(|distance| *distance != usize::MAX)
+    }
+
+    // The best tile to step onto from pos, going by step_cost for what can be stepped on right
+    // now. Only steps that get closer to the goal count, None if every one of them is blocked
+    pub fn next_step(
+        &self,
+        pos: Position,
+        step_cost: impl Fn(Position) -> Option<usize>,
+    ) -> Option<Position> {
+        let here = self.distance(pos)?;
+        let mut best: Option<(usize, Position)> = None;
+        for direction in DIRECTIONS {
+            let next = match step(pos, direction) {
+                Some(next) => next,
+                None => continue,
+            };
+            let distance = match self.distance(next) {
+                Some(distance) if distance < here => distance,
+                _ => continue,
+            };
+            if let Some(cost) = self.footprint.cost(next, &step_cost) {
+                if best.map_or(true, |(best, _)| distance + cost < best) {
+                    best = Some((distance + cost, next));
+                }
+            }
+        }
+        best.map(|(_, next)| next)
+    }
+
+    // The step that gets furthest from the goal, for running from it. None if no step gets any
+    // further away
+    pub fn step_away(
+        &self,
+        pos: Position,
+        step_cost: impl Fn(Position) -> Option<usize>,
+    ) -> Option<Position> {
+        let here = self.distance(pos)?;
+        let mut best: Option<(usize, Position)> = None;
+        for direction in DIRECTIONS {
+            let next = match step(pos, direction) {
+                Some(next) => next,
+                None => continue,
+            };
+            let distance = match self.distance(next) {
+                Some(distance) if distance > here => distance,
+                _ => continue,
+            };
+            if self.footprint.cost(next, &step_cost).is_some()
+                && best.map_or(true, |(best, _)| distance > best)
+            {
+                best = Some((distance, next));
+            }
+        }
+        best.map(|(_, next)| next)
+    }
+
+    // A step onto a tile just as close to the goal, for getting around something in the way.
+    // None if there is no such tile or it is blocked too
+    pub fn sidestep(
+        &self,
+        pos: Position,
+        step_cost: impl Fn(Position) -> Option<usize>,
+    ) -> Option<Position> {
+        let here = self.distance(pos)?;
+        DIRECTIONS
+            .iter()
+            .filter_map(|direction| step(pos, *direction))
+            .find(|next| {
+                self.distance(*next) == Some(here)
+                    && self.footprint.cost(*next, &step_cost).is_some()
+            })
+    }
+}
+
+// The flow fields towards the player in the room they are in, one for every way enemies get
+// around. Worked out once at the start of the enemies' turn, see Enemy::update_flow_fields
+#[derive(Debug, Clone, Default)]
+pub struct FlowFields {
+    // Walks around terrain, runs into the player's projectiles
+    pub ground: FlowField,
+    // Walks around terrain and the player's projectiles
+    pub dodging: FlowField,
+    // Majors, which walk around terrain like the ground field but need room for all nine tiles
+    pub major: FlowField,
+}
+
+
+// Everything enemies and missiles work out on their turn to find their way. It is only scratch
+// space, kept between turns so it isn't allocated again every turn, which is why it is kept by
+// the Game rather than the World: none of it is saved, and all of it is worked out again before
+// it is used
+#[derive(Debug, Clone, Default)]
+pub struct Navigation {
+    pub pathfinder: Pathfinder,
+    pub flow_fields: FlowFields,
+    pub crowd: Crowd,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    // What a step costs in a room that is open floor except for the walls
+    fn open_except(walls: &HashSet<Position>) -> impl Fn(Position) -> Option<usize> + '_ {
+        move |tile| if walls.contains(&tile) { None } else { Some(1) }
+    }
+
+    // A wall down the middle of the room with a gap in it as many tiles wide as asked for
+    fn wall_with_gap(width: usize) -> HashSet<Position> {
+        (0..WORLD_SIZE.1 as usize)
+            .filter(|y| *y < 20 || *y >= 20 + width)
+            .map(|y| Position::new(25, y))
+            .collect()
+    }
+
+    fn major_field(goal: Position, walls: &HashSet<Position>) -> FlowField {
+        let mut field = FlowField::default();
+        field.compute(goal, Footprint::MAJOR, open_except(walls));
+        field
+    }
+
+    #[test]
+    fn major_covers_nine_tiles_inside_the_room() {
+        let tiles: Vec<_> = Footprint::MAJOR.tiles(Position::new(4, 7)).collect();
+        assert_eq!(tiles.len(), 9);
+        assert_eq!(tiles[0], Position::new(4, 7));
+        assert_eq!(tiles[8], Position::new(6, 9));
+        assert!(Footprint::MAJOR.fits(Position::new(47, 47)));
+        assert!(!Footprint::MAJOR.fits(Position::new(48, 47)));
+        assert!(!Footprint::MAJOR.fits(Position::new(47, 48)));
+    }
+
+    #[test]
+    fn major_reaches_a_goal_on_the_room_edge() {
+        let walls = HashSet::new();
+        let goal = Position::new(49, 25);
+        let field = major_field(goal, &walls);
+        // every anchor it can attack from still has the whole major in the room
+        for y in 23..=25 {
+            assert_eq!(field.distance(Position::new(47, y)), Some(0));
+            assert_eq!(field.distance(Position::new(48, y)), None);
+        }
+        assert_eq!(field.distance(Position::new(30, 24)), Some(17));
+
+        let path = Pathfinder::default()
+            .find_path(Position::new(30, 24), Footprint::MAJOR, &[goal], open_except(&walls))
+            .unwrap();
+        assert_eq!(path.len(), 17);
+        assert!(path.iter().all(|anchor| Footprint::MAJOR.fits(*anchor)));
+        assert!(Footprint::MAJOR.covers(*path.back().unwrap(), goal));
+    }
+
+    #[test]
+    fn major_reaches_a_goal_in_a_corner() {
+        let walls = HashSet::new();
+        for (goal, corner) in [
+            (Position::new(0, 0), Position::new(0, 0)),
+            (Position::new(49, 0), Position::new(47, 0)),
+            (Position::new(0, 49), Position::new(0, 47)),
+            (Position::new(49, 49), Position::new(47, 47)),
+        ] {
+            let field = major_field(goal, &walls);
+            // only one anchor covers a corner
+            assert_eq!(field.distance(corner), Some(0));
+            let start = Position::new(23, 23);
+            let steps = corner.x.abs_diff(start.x) + corner.y.abs_diff(start.y);
+            assert_eq!(field.distance(start), Some(steps));
+            let path = Pathfinder::default()
+                .find_path(start, Footprint::MAJOR, &[goal], open_except(&walls))
+                .unwrap();
+            assert_eq!(path.back(), Some(&corner));
+        }
+    }
+
+    #[test]
+    fn major_fits_through_a_three_wide_corridor_but_not_a_two_wide_one() {
+        let (start, goal) = (Position::new(10, 20), Position::new(40, 21));
+        let wide = wall_with_gap(3);
+        assert!(major_field(goal, &wide).distance(start).is_some());
+        let path = Pathfinder::default()
+            .find_path(start, Footprint::MAJOR, &[goal], open_except(&wide))
+            .unwrap();
+        // through the gap, all three rows of it
+        assert!(path.iter().any(|anchor| anchor.x == 24 && anchor.y == 20));
+
+        let narrow = wall_with_gap(2);
+        assert_eq!(major_field(goal, &narrow).distance(start), None);
+        assert!(Pathfinder::default()
+            .find_path(start, Footprint::MAJOR, &[goal], open_except(&narrow))
+            .is_none());
+        // while a single tile enemy still gets through
+        let mut single = FlowField::default();
+        single.compute(goal, Footprint::SINGLE, open_except(&narrow));
+        assert!(single.distance(start).is_some());
+    }
+
+    #[test]
+    fn major_spawned_against_a_wall_walks_away_from_it() {
+        // a wall right of a major standing at x 7 to 9
+        let walls: HashSet<_> = (0..WORLD_SIZE.1 as usize).map(|y| Position::new(10, y)).collect();
+        let anchor = Position::new(7, 20);
+        assert!(Footprint::MAJOR.cost(anchor, &open_except(&walls)).is_some());
+        assert!(Footprint::MAJOR.cost(Position::new(8, 20), &open_except(&walls)).is_none());
+
+        let field = major_field(Position::new(0, 21), &walls);
+        assert_eq!(field.distance(anchor), Some(7));
+        assert_eq!(field.next_step(anchor, open_except(&walls)), Some(Position::new(6, 20)));
+
+        // and can't go through it to a goal on the other side
+        assert_eq!(major_field(Position::new(30, 21), &walls).distance(anchor), None);
+    }
+}
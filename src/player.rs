@@ -1,9 +1,13 @@
 use crate::{
+    action::Action,
+    balance::PlayerBalance,
+    damage::Damage,
     direction::Direction,
     enemy::{self, Enemy},
     entity::Entity,
     projectile::Projectile,
-    tile,
+    status::{StatusEffect, StatusEffects, StatusKind},
+    tile::{ProjectileKind, TerrainKind, TileKind},
     utils::Boss,
     utils::Position,
     world::World,
@@ -13,55 +17,7 @@ use crate::{
 
 use std::cmp::{max, min};
 
-use ggez::graphics::{self, Canvas};
-use ggez::input::keyboard::{KeyCode, KeyInput};
-use ggez::winit::event::VirtualKeyCode;
-use rand_chacha::ChaCha8Rng;
-
-// Can change easily
-pub const MAX_PLAYER_HEALTH: usize = 100;
-const MAX_PLAYER_ENERGY: usize = 100;
-const PLAYER_MELEE_DAMAGE: usize = 30;
-const PLAYER_SLAM_DAMAGE: usize = 50;
-const TELEPORTATION_COST: usize = 5;
-const HEAL_COST: usize = 20;
-const FIRE_COST: usize = 30;
-const SLAM_COST: usize = 10;
-const HEAL_ABILITY_RETURN: usize = 10;
-const LIGHTNING_COST: usize = 25;
-const INVISIBILITY_COST: usize = 30;
-const TRACKING_PROJECTILE_COST: usize = 75;
-
-const INVISIBILITY_DURATION: usize = 10;
-
-const MELEE_ATTACK_KEYCODE: VirtualKeyCode = KeyCode::M;
-
-// TODO look over these values
-const DIRECTION_LEFT: VirtualKeyCode = KeyCode::A;
-const DIRECTION_DOWN: VirtualKeyCode = KeyCode::S;
-const DIRECTION_UP: VirtualKeyCode = KeyCode::W;
-const INVISIBILITY_KEYCODE: VirtualKeyCode = KeyCode::I;
-const DIRECTION_RIGHT: VirtualKeyCode = KeyCode::D;
-const HEAL_KEYCODE: VirtualKeyCode = KeyCode::H;
-const TELEPORT_KEYCODE: VirtualKeyCode = KeyCode::T;
-const LIGHTNING_KEYCODE: VirtualKeyCode = KeyCode::L;
-const SLAM_KEYCODE: VirtualKeyCode = KeyCode::Z;
-const FLAME_KEYCODE: VirtualKeyCode = KeyCode::F;
-const BUILD_KEYCODE: VirtualKeyCode = KeyCode::B;
-const TRACKING_MISSILE_KEYCODE: VirtualKeyCode = KeyCode::X;
-const PROJECTILE_ATTACK_KEYCODE: VirtualKeyCode = KeyCode::Space;
-const PLAYER_PROJECTILE_SPEED: usize = 1;
-pub const PLAYER_PROJECTILE_DAMAGE: usize = 10;
-const PLAYER_INITIAL_SPEED: usize = 1;
-const PLAYER_INITIAL_ENERGY: usize = 100;
-const PERMISSIBLE_TILES: [[f32; 4]; 1] = [tile::GRASS];
-const LIGHTNING_COOLDOWN: usize = 5;
-const TELEPORT_COOLDOWN: usize = 1;
-const FIRE_COOLDOWN: usize = 10;
-const SLAM_COOLDOWN: usize = 10;
-const PROJECTILE_COOLDOWN: usize = 1;
-const INVISIBILITY_COOLDOWN: usize = 25 + INVISIBILITY_DURATION;
-const TRACKING_PROJECTILE_COOLDOWN: usize = 20;
+use ggez::{glam::Vec2, graphics::{self, Canvas}};
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
 // This is with the covered tile model, but we could use the static/dynamic board paradighm or
@@ -79,10 +35,6 @@ pub struct Player {
     // This controls the number of tiles a player moves in a direction in a given keypress
     pub speed: usize,
 
-    // This is the player color. NOTE: both this and the previous attribute assume that the game
-    // world is a set of tiles and the player is represented as a solid color
-    pub color: [f32; 4],
-
     // Stores player health: for player death and such
     health: usize,
 
@@ -92,9 +44,6 @@ pub struct Player {
     // This is the position queued by mouse clicks, used for teleportation, etc
     pub queued_position: Option<Position>,
 
-    // duration of visibility: 0 means visible, N > 0 means invisible for N more turns
-    visible: i16,
-
     // Cooldowns for the various abilities
     projectile_cooldown: i16,
     lightning_cooldown: i16,
@@ -103,8 +52,18 @@ pub struct Player {
     teleport_cooldown: i16,
     invisiblity_cooldown: i16,
     tracking_projectile_cooldown: i16,
-    pub stun_timer: usize,
+
+    // Stuns, burns, invisibility and every other effect on the player
+    #[serde(default)]
+    pub status: StatusEffects,
     is_alive: bool,
+
+    // Saves up to version 6 kept invisibility and stuns in their own counters, the migration
+    // turns them into status effects
+    #[serde(rename = "visible", default, skip_serializing)]
+    pub legacy_visible: i16,
+    #[serde(rename = "stun_timer", default, skip_serializing)]
+    pub legacy_stun_timer: usize,
 }
 
 impl Player {
@@ -116,28 +75,31 @@ impl Player {
         self.health
     }
 
-    pub fn damage(&mut self, damage: usize) {
-        if (self.health as i32 - damage as i32 <= 0) {
+    pub fn energy(&self) -> usize {
+        self.energy
+    }
+
+    // The player has no resistances, so every kind of damage is taken in full
+    pub fn damage(&mut self, damage: Damage) {
+        if (self.health as i32 - damage.amount as i32 <= 0) {
             self.is_alive = false;
             return;
         }
-        self.health -= damage;
+        self.health -= damage.amount;
     }
 
     pub fn is_visible(&self) -> bool {
-        self.visible <= 0
+        !self.status.has(StatusKind::Invisible)
     }
 
-    pub fn new() -> Self {
+    pub fn new(balance: &PlayerBalance) -> Self {
         let temp = Self {
             pos: Position::new(0, 0),
             direction: Direction::South,
-            speed: PLAYER_INITIAL_SPEED,
-            color: tile::PLAYER,
-            health: MAX_PLAYER_HEALTH,
-            energy: PLAYER_INITIAL_ENERGY,
+            speed: balance.initial_speed,
+            health: balance.max_health,
+            energy: balance.initial_energy,
             queued_position: None,
-            visible: 0,
             projectile_cooldown: 0,
             slam_cooldown: 0,
             fire_cooldown: 0,
@@ -145,8 +107,10 @@ impl Player {
             teleport_cooldown: 0,
             invisiblity_cooldown: 0,
             tracking_projectile_cooldown: 0,
-            stun_timer: 0,
+            status: StatusEffects::default(),
             is_alive: true,
+            legacy_visible: 0,
+            legacy_stun_timer: 0,
         };
         temp
     }
@@ -265,6 +229,23 @@ impl Player {
         }
     }
 
+    //Lists the status effects on the player and their turns left in the gap between the hearts and
+    //the energy symbols
+    pub fn draw_status(&self, canvas: &mut graphics::Canvas) {
+        let mut line = 0;
+        for kind in StatusKind::ALL {
+            let turns = self.status.turns(kind);
+            if turns > 0 {
+                canvas.draw(
+                    &*(graphics::Text::new(format!("{} {}", kind.describe(), turns)).set_scale(13.)),
+                    graphics::DrawParam::from(Vec2::new(358., (8 + line * 13) as f32))
+                        .color([0.0, 0.0, 0.0, 1.0]),
+                );
+                line += 1;
+            }
+        }
+    }
+
     //Colors in the hearts based on current health
     pub fn color_heart(
         &self,
@@ -407,296 +388,170 @@ impl Player {
         }
     }
 
-    // eventually this should be the functionality to like shoot projectiles and stuff but for now
-    // it just handles like arrow keys
-    // Returns if the move should consume a turn
-    pub fn use_input(key: KeyInput, world: &mut World, rng: &mut ChaCha8Rng) -> bool {
-        match key.keycode {
-            Some(key_pressed) => match key_pressed {
-                KeyCode::Down => {
-                    // make sure moving doesn't change direction
-                    let old_direction = world.player.direction;
-                    world.player.direction = Direction::South;
-                    World::travel(world, Entity::Player, None);
-                    world.player.projectile_cooldown -= 1;
-                    world.player.slam_cooldown -= 1;
-                    world.player.fire_cooldown -= 1;
-                    world.player.lightning_cooldown -= 1;
-                    world.player.teleport_cooldown -= 1;
-                    world.player.invisiblity_cooldown -= 1;
-                    world.player.visible -= 1;
-                    world.player.tracking_projectile_cooldown -= 1;
-                    world.player.direction = old_direction;
-                }
-                KeyCode::Up => {
-                    let old_direction = world.player.direction;
-                    world.player.direction = Direction::North;
-                    World::travel(world, Entity::Player, None);
-                    world.player.projectile_cooldown -= 1;
-                    world.player.slam_cooldown -= 1;
-                    world.player.fire_cooldown -= 1;
-                    world.player.lightning_cooldown -= 1;
-                    world.player.teleport_cooldown -= 1;
-                    world.player.invisiblity_cooldown -= 1;
-                    world.player.visible -= 1;
-                    world.player.tracking_projectile_cooldown -= 1;
-                    world.player.direction = old_direction;
-                }
-                KeyCode::Left => {
-                    let old_direction = world.player.direction;
-                    world.player.direction = Direction::West;
-                    World::travel(world, Entity::Player, None);
-                    world.player.projectile_cooldown -= 1;
-                    world.player.slam_cooldown -= 1;
-                    world.player.fire_cooldown -= 1;
-                    world.player.lightning_cooldown -= 1;
-                    world.player.teleport_cooldown -= 1;
-                    world.player.invisiblity_cooldown -= 1;
-                    world.player.visible -= 1;
-                    world.player.tracking_projectile_cooldown -= 1;
-                    world.player.direction = old_direction;
-                }
-                KeyCode::Right => {
-                    let old_direction = world.player.direction;
-                    world.player.direction = Direction::East;
-                    World::travel(world, Entity::Player, None);
-                    world.player.projectile_cooldown -= 1;
-                    world.player.slam_cooldown -= 1;
-                    world.player.fire_cooldown -= 1;
-                    world.player.lightning_cooldown -= 1;
-                    world.player.teleport_cooldown -= 1;
-                    world.player.invisiblity_cooldown -= 1;
-                    world.player.visible -= 1;
-                    world.player.tracking_projectile_cooldown -= 1;
-                    world.player.direction = old_direction;
-                }
-
-                DIRECTION_UP => {
-                    world.player.direction = Direction::North;
-                    world.player.projectile_cooldown -= 1;
-                    world.player.slam_cooldown -= 1;
-                    world.player.fire_cooldown -= 1;
-                    world.player.lightning_cooldown -= 1;
-                    world.player.teleport_cooldown -= 1;
-                    world.player.invisiblity_cooldown -= 1;
-                    world.player.visible -= 1;
-                    world.player.tracking_projectile_cooldown -= 1;
-                }
-
-                DIRECTION_DOWN => {
-                    world.player.direction = Direction::South;
-                    world.player.projectile_cooldown -= 1;
-                    world.player.slam_cooldown -= 1;
-                    world.player.fire_cooldown -= 1;
-                    world.player.lightning_cooldown -= 1;
-                    world.player.teleport_cooldown -= 1;
-                    world.player.invisiblity_cooldown -= 1;
-                    world.player.visible -= 1;
-                    world.player.tracking_projectile_cooldown -= 1;
-                }
-
-                DIRECTION_RIGHT => {
-                    world.player.direction = Direction::East;
-                    world.player.projectile_cooldown -= 1;
-                    world.player.slam_cooldown -= 1;
-                    world.player.fire_cooldown -= 1;
-                    world.player.lightning_cooldown -= 1;
-                    world.player.teleport_cooldown -= 1;
-                    world.player.invisiblity_cooldown -= 1;
-                    world.player.visible -= 1;
-                    world.player.tracking_projectile_cooldown -= 1;
-                }
-
-                DIRECTION_LEFT => {
-                    world.player.direction = Direction::West;
-                    world.player.projectile_cooldown -= 1;
-                    world.player.slam_cooldown -= 1;
-                    world.player.fire_cooldown -= 1;
-                    world.player.lightning_cooldown -= 1;
-                    world.player.teleport_cooldown -= 1;
-                    world.player.invisiblity_cooldown -= 1;
-                    world.player.visible -= 1;
-                    world.player.tracking_projectile_cooldown -= 1;
-                }
-
-                // Arbitrarily chosen for attack, can change later
-                MELEE_ATTACK_KEYCODE => {
-                    Player::melee_attack(world);
-                    world.player.projectile_cooldown -= 1;
-                    world.player.slam_cooldown -= 1;
-                    world.player.fire_cooldown -= 1;
-                    world.player.lightning_cooldown -= 1;
-                    world.player.teleport_cooldown -= 1;
-                    world.player.invisiblity_cooldown -= 1;
-                    world.player.tracking_projectile_cooldown -= 1;
-                    world.player.visible -= 1;
-                }
-                PROJECTILE_ATTACK_KEYCODE => {
-                    if world.player.energy > 0 && world.player.projectile_cooldown <= 0 {
-                        Player::projectile_attack(world);
-                        world.player.energy -= 1;
-                        world.player.projectile_cooldown = PROJECTILE_COOLDOWN as i16;
-                        world.player.slam_cooldown -= 1;
-                        world.player.fire_cooldown -= 1;
-                        world.player.lightning_cooldown -= 1;
-                        world.player.teleport_cooldown -= 1;
-                        world.player.invisiblity_cooldown -= 1;
-                        world.player.tracking_projectile_cooldown -= 1;
-                        world.player.visible -= 1;
-                    } else {
-                        return false;
-                    }
+    // Applies a single action for the player
+    // Returns if the action should consume a turn
+    pub fn use_input(action: Action, world: &mut World) -> bool {
+        // a stunned or slowed player loses whatever they tried to do, but the turn still passes
+        let action = if world.player.status.can_act() {
+            action
+        } else {
+            Action::Wait
+        };
+        match action {
+            Action::Move(direction) => {
+                // make sure moving doesn't change direction
+                let old_direction = world.player.direction;
+                world.player.direction = direction;
+                World::travel(world, Entity::Player, None);
+                world.player.direction = old_direction;
+                world.player.tick_cooldowns();
+            }
+            Action::Face(direction) => {
+                world.player.direction = direction;
+                world.player.tick_cooldowns();
+            }
+            Action::Wait => {
+                world.player.tick_cooldowns();
+            }
+            Action::Melee => {
+                Player::melee_attack(world);
+                world.player.tick_cooldowns();
+            }
+            Action::Projectile => {
+                if world.player.energy > 0 && world.player.projectile_cooldown <= 0 {
+                    Player::projectile_attack(world);
+                    world.player.energy -= 1;
+                    world.player.tick_cooldowns();
+                    world.player.projectile_cooldown = world.balance.player.projectile_cooldown as i16;
+                } else {
+                    return false;
                 }
-                HEAL_KEYCODE => {
-                    if world.player.energy >= HEAL_COST && world.player.health < 100 {
-                        world.player.health += HEAL_ABILITY_RETURN;
-                        world.player.energy -= HEAL_COST;
-                        world.player.projectile_cooldown -= 1;
-                        world.player.slam_cooldown -= 1;
-                        world.player.fire_cooldown -= 1;
-                        world.player.lightning_cooldown -= 1;
-                        world.player.tracking_projectile_cooldown -= 1;
-                        world.player.teleport_cooldown -= 1;
-                        world.player.invisiblity_cooldown -= 1;
-                        world.player.visible -= 1;
-                    } else {
-                        return false;
-                    }
+            }
+            Action::Heal => {
+                if world.player.energy >= world.balance.player.heal_cost
+                    && world.player.health < world.balance.player.max_health
+                {
+                    world.player.health += world.balance.player.heal_amount;
+                    world.player.energy -= world.balance.player.heal_cost;
+                    world.player.tick_cooldowns();
+                } else {
+                    return false;
                 }
-                BUILD_KEYCODE => {
-                    if world.player.energy > 2 && Player::build(world) {
-                        world.player.projectile_cooldown -= 1;
-                        world.player.slam_cooldown -= 1;
-                        world.player.fire_cooldown -= 1;
-                        world.player.lightning_cooldown -= 1;
-                        world.player.teleport_cooldown -= 1;
-                        world.player.invisiblity_cooldown -= 1;
-                        world.player.tracking_projectile_cooldown -= 1;
-                        world.player.visible -= 1;
-                    } else {
-                        return false;
-                    }
+            }
+            Action::Build(pos) => {
+                if world.player.energy > 2 && Player::build(world, pos) {
+                    world.player.tick_cooldowns();
+                } else {
+                    return false;
                 }
-                LIGHTNING_KEYCODE => {
-                    if world.player.energy >= LIGHTNING_COST
-                        && world.player.lightning_cooldown <= 0
-                        && world.player.queued_position.is_some()
-                    {
-                        Player::lightning(world);
-                        world.player.projectile_cooldown -= 1;
-                        world.player.slam_cooldown -= 1;
-                        world.player.fire_cooldown -= 1;
-                        world.player.lightning_cooldown = LIGHTNING_COOLDOWN as i16;
-                        world.player.teleport_cooldown -= 1;
-                        world.player.tracking_projectile_cooldown -= 1;
-                        world.player.invisiblity_cooldown -= 1;
-                        world.player.visible -= 1;
-                    } else {
-                        return false;
-                    }
+            }
+            Action::Lightning(pos) => {
+                if world.player.energy >= world.balance.player.lightning_cost
+                    && world.player.lightning_cooldown <= 0
+                {
+                    Player::lightning(world, pos);
+                    world.player.tick_cooldowns();
+                    world.player.lightning_cooldown = world.balance.player.lightning_cooldown as i16;
+                } else {
+                    return false;
                 }
+            }
 
-                // TODO FINISH COSTS REFACTORING
-                TELEPORT_KEYCODE => {
-                    if world.player.energy >= TELEPORTATION_COST
-                        && world.player.queued_position.is_some()
-                        && world.player.teleport_cooldown <= 0
-                    {
-                        Self::teleport(world);
-                        world.player.projectile_cooldown -= 1;
-                        world.player.slam_cooldown -= 1;
-                        world.player.fire_cooldown -= 1;
-                        world.player.lightning_cooldown -= 1;
-                        world.player.teleport_cooldown = TELEPORT_COOLDOWN as i16;
-                        world.player.tracking_projectile_cooldown -= 1;
-                        world.player.invisiblity_cooldown -= 1;
-                        world.player.visible -= 1;
-                    } else {
-                        return false;
-                    }
-                }
-                SLAM_KEYCODE => {
-                    if world.player.slam_cooldown <= 0 && world.player.energy >= SLAM_COST {
-                        world.player.change_energy(-(SLAM_COST as i32));
-                        Self::slam(world);
-                        world.player.projectile_cooldown -= 1;
-                        world.player.slam_cooldown = SLAM_COOLDOWN as i16;
-                        world.player.fire_cooldown -= 1;
-                        world.player.lightning_cooldown -= 1;
-                        world.player.teleport_cooldown -= 1;
-                        world.player.tracking_projectile_cooldown -= 1;
-                        world.player.invisiblity_cooldown -= 1;
-                        world.player.visible -= 1;
-                    } else {
-                        return false;
-                    }
+            // TODO FINISH COSTS REFACTORING
+            Action::Teleport(pos) => {
+                if world.player.energy >= world.balance.player.teleport_cost
+                    && world.player.teleport_cooldown <= 0
+                {
+                    Self::teleport(world, pos);
+                    world.player.tick_cooldowns();
+                    world.player.teleport_cooldown = world.balance.player.teleport_cooldown as i16;
+                } else {
+                    return false;
                 }
-                FLAME_KEYCODE => {
-                    if world.player.fire_cooldown <= 0 && world.player.energy >= FIRE_COST {
-                        world.player.change_energy(-(FIRE_COST as i32));
-                        Self::fire_attack(world);
-                        world.player.projectile_cooldown -= 1;
-                        world.player.slam_cooldown -= 1;
-                        world.player.fire_cooldown = FIRE_COOLDOWN as i16;
-                        world.player.lightning_cooldown -= 1;
-                        world.player.teleport_cooldown -= 1;
-                        world.player.tracking_projectile_cooldown -= 1;
-                        world.player.invisiblity_cooldown -= 1;
-                        world.player.visible -= 1;
-                    } else {
-                        return false;
-                    }
+            }
+            Action::Slam => {
+                let slam_cost = world.balance.player.slam_cost;
+                if world.player.slam_cooldown <= 0 && world.player.energy >= slam_cost {
+                    world.player.change_energy(-(slam_cost as i32), world.balance.player.max_energy);
+                    Self::slam(world);
+                    world.player.tick_cooldowns();
+                    world.player.slam_cooldown = world.balance.player.slam_cooldown as i16;
+                } else {
+                    return false;
                 }
-                INVISIBILITY_KEYCODE => {
-                    if world.player.energy >= INVISIBILITY_COST
-                        && world.player.invisiblity_cooldown <= 0
-                    {
-                        world.player.visible = INVISIBILITY_DURATION as i16;
-                        world.player.invisiblity_cooldown = INVISIBILITY_COOLDOWN as i16;
-                        world.player.change_energy(-(INVISIBILITY_COST as i32));
-                        world.player.projectile_cooldown -= 1;
-                        world.player.slam_cooldown -= 1;
-                        world.player.fire_cooldown -= 1;
-                        world.player.lightning_cooldown -= 1;
-                        world.player.teleport_cooldown -= 1;
-                        world.player.tracking_projectile_cooldown -= 1;
-                    } else {
-                        return false;
-                    }
+            }
+            Action::Fire => {
+                let fire_cost = world.balance.player.fire_cost;
+                if world.player.fire_cooldown <= 0 && world.player.energy >= fire_cost {
+                    world.player.change_energy(-(fire_cost as i32), world.balance.player.max_energy);
+                    Self::fire_attack(world);
+                    world.player.tick_cooldowns();
+                    world.player.fire_cooldown = world.balance.player.fire_cooldown as i16;
+                } else {
+                    return false;
                 }
-                TRACKING_MISSILE_KEYCODE => {
-                    if world.player.energy >= TRACKING_PROJECTILE_COST
-                        && world.player.tracking_projectile_cooldown <= 0
-                    {
-                        Self::tracking_projectile_attack(world);
-                        world
-                            .player
-                            .change_energy(-(TRACKING_PROJECTILE_COST as i32));
-                        world.player.tracking_projectile_cooldown =
-                            TRACKING_PROJECTILE_COOLDOWN as i16;
-                        world.player.projectile_cooldown -= 1;
-                        world.player.slam_cooldown -= 1;
-                        world.player.fire_cooldown -= 1;
-                        world.player.lightning_cooldown -= 1;
-                        world.player.teleport_cooldown -= 1;
-                        world.player.invisiblity_cooldown -= 1;
-                        world.player.visible -= 1;
-                    }
+            }
+            Action::Invisibility => {
+                let invisibility_cost = world.balance.player.invisibility_cost;
+                if world.player.energy >= invisibility_cost
+                    && world.player.invisiblity_cooldown <= 0
+                {
+                    world.player.change_energy(
+                        -(invisibility_cost as i32),
+                        world.balance.player.max_energy,
+                    );
+                    world.player.tick_cooldowns();
+                    world.player.status.apply(StatusEffect::new(
+                        StatusKind::Invisible,
+                        world.balance.player.invisibility_duration,
+                    ));
+                    world.player.invisiblity_cooldown =
+                        world.balance.player.invisibility_cooldown as i16;
+                } else {
+                    return false;
                 }
-                _ => {
+            }
+            Action::Missile => {
+                let tracking_projectile_cost = world.balance.player.tracking_projectile_cost;
+                if world.player.energy >= tracking_projectile_cost
+                    && world.player.tracking_projectile_cooldown <= 0
+                {
+                    Self::tracking_projectile_attack(world);
+                    world.player.change_energy(
+                        -(tracking_projectile_cost as i32),
+                        world.balance.player.max_energy,
+                    );
+                    world.player.tick_cooldowns();
+                    world.player.tracking_projectile_cooldown =
+                        world.balance.player.tracking_projectile_cooldown as i16;
+                } else {
                     return false;
                 }
-            },
-            None => {
-                return false;
             }
         }
-        if BOSS_ROOMS.contains(&world.world_position) {
-            Boss::update(world, rng);
-        }
         return true;
     }
 
+    // Every turn the player takes counts down all of the ability cooldowns and the status effects,
+    // with burns and poison hurting as they do. Abilities that were just used set their own
+    // cooldown (or effect) again afterwards
+    fn tick_cooldowns(&mut self) {
+        for cooldown in [
+            &mut self.projectile_cooldown,
+            &mut self.slam_cooldown,
+            &mut self.fire_cooldown,
+            &mut self.lightning_cooldown,
+            &mut self.teleport_cooldown,
+            &mut self.invisiblity_cooldown,
+            &mut self.tracking_projectile_cooldown,
+        ] {
+            *cooldown = max(*cooldown - 1, 0);
+        }
+        for damage in self.status.tick() {
+            self.damage(damage);
+        }
+    }
+
     pub fn tracking_projectile_attack(world: &mut World) {
         let (pos, world_pos) = World::new_position(
             world.player.pos,
@@ -709,10 +564,15 @@ impl Player {
 
         world
             .projectiles
-            .push(Projectile::tracking_projectile(pos.x, pos.y, world_pos));
+            .push(Projectile::tracking_projectile(
+                pos.x,
+                pos.y,
+                world_pos,
+                &world.balance.projectiles,
+            ));
 
         // Queue it to draw
-        world.atmosphere_map[world_pos.y][world_pos.x].insert(pos, tile::TRACKING_PROJECTILE);
+        world.atmosphere_map[world_pos.y][world_pos.x].insert(pos, TileKind::Projectile(ProjectileKind::Tracking));
     }
 
     pub fn fire_attack(world: &mut World) {
@@ -731,29 +591,32 @@ impl Player {
             pos.y,
             world.player.direction,
             world_pos,
+            &world.balance.projectiles,
         ));
 
         // Queue it to draw
-        world.atmosphere_map[world_pos.y][world_pos.x].insert(pos, tile::FIRE_PLACEHOLDER);
+        world.atmosphere_map[world_pos.y][world_pos.x].insert(pos, TileKind::Projectile(ProjectileKind::FirePending));
     }
 
     pub fn slam(world: &mut World) {
         // this gets the deltas which allow us to generate the positions around the player
         const deltas: [i16; 3] = [0, -1, 1];
-
-        // check all the enemies
+        let slam_damage = Damage::physical(world.balance.player.slam_damage);
+
+        // it's fine if a position is out of bounds, because we aren't indexing anything
+        let mut positions = Vec::new();
+        for delta_x in deltas {
+            for delta_y in deltas {
+                positions.push(Position::new(
+                    (world.player.pos.x as i16 + delta_x) as usize,
+                    (world.player.pos.y as i16 + delta_y) as usize,
+                ));
+            }
+        }
+        // check all the enemies, each is hit once however many of its tiles are next to the player
         for enemy in &mut world.enemies_map[world.world_position.y][world.world_position.x] {
-            // it's fine if the position is out of bounds, because we aren't indexing anything
-            for delta_x in deltas {
-                for delta_y in deltas {
-                    let position = Position::new(
-                        (world.player.pos.x as i16 + delta_x) as usize,
-                        (world.player.pos.y as i16 + delta_y) as usize,
-                    );
-                    if enemy.pos.contains(&position) {
-                        enemy.damage(PLAYER_SLAM_DAMAGE);
-                    }
-                }
+            if enemy.pos.iter().any(|tile| positions.contains(tile)) {
+                enemy.damage(slam_damage, &world.balance.enemies);
             }
         }
 
@@ -766,7 +629,7 @@ impl Player {
                     );
                     let hit_info = Boss::can_hit_boss(world, position, world.world_position);
                     if hit_info.0 && hit_info.1 {
-                        Boss::damage(world, PLAYER_SLAM_DAMAGE, world.world_position);
+                        Boss::damage(world, slam_damage, world.world_position);
                         return;
                     }
                 }
@@ -774,61 +637,54 @@ impl Player {
         }
     }
 
-    pub fn lightning(world: &mut World) {
-        let pos = world
-            .player
-            .queued_position
-            .expect("This method should never be called without a queued position");
-
+    pub fn lightning(world: &mut World, pos: Position) {
         let world_pos = world.world_position;
         // queued positions are definitionally valid, so no checking needs to be done
         world
             .projectiles
-            .push(Projectile::lightning(pos.x, pos.y, world_pos));
+            .push(Projectile::lightning(pos.x, pos.y, world_pos, &world.balance.projectiles));
 
         // Queue it to draw
-        world.atmosphere_map[world_pos.y][world_pos.x].insert(pos, tile::LIGHTNING_PLACEHOLDER);
+        world.atmosphere_map[world_pos.y][world_pos.x].insert(pos, TileKind::Projectile(ProjectileKind::LightningPending));
     }
 
-    // THIS METHOD EXPECTS A QUEUED POSITION
-    pub fn teleport(world: &mut World) {
-        if let Some(pos) = world.player.queued_position {
-            if Player::can_travel_to(world, (pos, world.world_position)) {
-                World::update_position(world, world.player.pos, (pos, world.world_position));
-                world.player.pos = pos;
-                world.player.change_energy(-(TELEPORTATION_COST as i32));
-            }
+    pub fn teleport(world: &mut World, pos: Position) {
+        if Player::can_travel_to(world, (pos, world.world_position)) {
+            World::update_position(world, world.player.pos, (pos, world.world_position));
+            world.player.pos = pos;
+            world.player.change_energy(
+                -(world.balance.player.teleport_cost as i32),
+                world.balance.player.max_energy,
+            );
         }
     }
 
-    pub fn build(world: &mut World) -> bool {
-        if let Some(pos) = world.player.queued_position {
-            if (pos.x as i32 - world.player.pos.x as i32).abs() < 2
-                && (pos.y as i32 - world.player.pos.y as i32).abs() < 2
-            {
-                // get the things to check
-                let world_pos = world.world_position;
-                let terrain_map = &world.terrain_map[world_pos.y][world_pos.x];
-                let entity_map = &world.entity_map[world_pos.y][world_pos.x];
-                let atmosphere_map = &mut world.atmosphere_map[world_pos.y][world_pos.x];
-
-                // make sure build position has no terrain
-                if !terrain_map.contains_key(&pos) {
-                    // make sure there are no entities
-                    if !entity_map.contains_key(&pos) {
-                        // make sure the atmosphere doesn't contain anything
-                        if !atmosphere_map.contains_key(&pos) {
-                            atmosphere_map.insert(pos, tile::STRUCTURE);
-                            world.player.energy -= 2;
-                            return true;
-                        } else {
-                            match atmosphere_map.get(&pos).expect("This should be impossible because we checked that it contained a key before") {
-                                &tile::STRUCTURE => {
-                                    atmosphere_map.remove(&pos);
-                                    return true;
-                                }
-                                _ => {}
+    pub fn build(world: &mut World, pos: Position) -> bool {
+        if (pos.x as i32 - world.player.pos.x as i32).abs() < 2
+            && (pos.y as i32 - world.player.pos.y as i32).abs() < 2
+        {
+            // get the things to check
+            let world_pos = world.world_position;
+            let terrain_map = &world.terrain_map[world_pos.y][world_pos.x];
+            let entity_map = &world.entity_map[world_pos.y][world_pos.x];
+            let atmosphere_map = &mut world.atmosphere_map[world_pos.y][world_pos.x];
+
+            // make sure build position has no terrain
+            if !terrain_map.contains_key(&pos) {
+                // make sure there are no entities
+                if !entity_map.contains_key(&pos) {
+                    // make sure the atmosphere doesn't contain anything
+                    if !atmosphere_map.contains_key(&pos) {
+                        atmosphere_map.insert(pos, TileKind::Structure);
+                        world.player.energy -= 2;
+                        return true;
+                    } else {
+                        match atmosphere_map.get(&pos).expect("This should be impossible because we checked that it contained a key before") {
+                            &TileKind::Structure => {
+                                atmosphere_map.remove(&pos);
+                                return true;
                             }
+                            _ => {}
                         }
                     }
                 }
@@ -852,12 +708,14 @@ impl Player {
         //     // We do not know what enemies are on the tile being attacked, so we need to go through the
         //     // enemies and check if any of them are on the attacking tile, then damage them
         let world_pos = world.world_position;
+        let melee_damage = Damage::physical(world.balance.player.melee_damage);
+        let max_energy = world.balance.player.max_energy;
         if let Some(entity) = world.entity_map[world_pos.y][world_pos.x].get(&attacking_position) {
             if entity.1 == Entity::Enemy {
                 for enemy in &mut world.enemies_map[world.world_position.y][world.world_position.x] {
                     if enemy.pos.contains(&attacking_position) {
-                        enemy.damage(PLAYER_MELEE_DAMAGE);
-                        world.player.change_energy(2);
+                        enemy.damage(melee_damage, &world.balance.enemies);
+                        world.player.change_energy(2, max_energy);
                     }
                 }
             }
@@ -866,7 +724,7 @@ impl Player {
         if BOSS_ROOMS.contains(&world_pos) {
             let hit_info = Boss::can_hit_boss(world, attacking_position, world_pos);
             if hit_info.0 && hit_info.1 {
-                Boss::damage(world, PLAYER_MELEE_DAMAGE, world_pos);
+                Boss::damage(world, melee_damage, world_pos);
             }
         }
     }
@@ -890,13 +748,14 @@ impl Player {
                 projectile_spawn_pos.0.y,
                 world.player.direction.clone(),
                 world.world_position,
+                &world.balance.projectiles,
             );
             for index in 0..world.enemies_map[world.world_position.y][world.world_position.x].len() {
                 //Check if it's spawning on enemy, if so damage the enenmy and not spawn a projectile
                 if world.enemies_map[world.world_position.y][world.world_position.x][index].pos.contains(&projectile_spawn_pos.0)
                     && projectile_spawn_pos.1 == world.enemies_map[world.world_position.y][world.world_position.x][index].world_pos
                 {
-                    world.enemies_map[world.world_position.y][world.world_position.x][index].damage(projectile.damage);
+                    world.enemies_map[world.world_position.y][world.world_position.x][index].hit_by_projectile(projectile.hit(), &world.balance.enemies);
                     return;
                 }
             }
@@ -904,14 +763,15 @@ impl Player {
             if BOSS_ROOMS.contains(&world.world_position) {
                 let hit_info = Boss::can_hit_boss(world, projectile_spawn_pos.0, world.world_position);
                 if hit_info.0 && hit_info.0 {
-                    Boss::damage(world, PLAYER_MELEE_DAMAGE, world.world_position);
+                    let melee_damage = Damage::physical(world.balance.player.melee_damage);
+                    Boss::damage(world, melee_damage, world.world_position);
                     return;
                 }
             }
 
             world.entity_map[world.world_position.y][world.world_position.x].insert(
                 projectile.pos,
-                (tile::PROJECTILE_PLAYER, Entity::Projectile),
+                (TileKind::Projectile(ProjectileKind::Player), Entity::Projectile),
             );
             world.projectiles.push(projectile);
         }
@@ -928,21 +788,13 @@ impl Player {
         let curr_terrain_map = &terrain_map[position_info.1.y][position_info.1.x];
         let curr_entity_map = &entity_map[position_info.1.y][position_info.1.x];
         let curr_atmosphere_map = &atmosphere_map[position_info.1.y][position_info.1.x];
-        if world.player.stun_timer != 0 {
-            world.player.stun_timer -= 1;
-            return false;
-        }
         if curr_entity_map.contains_key(&position_info.0)
             || curr_terrain_map.contains_key(&position_info.0)
             || curr_atmosphere_map.contains_key(&position_info.0)
         {
-            if let Some(info) = curr_entity_map.get(&position_info.0) {
-                if PERMISSIBLE_TILES.contains(&info.0) {
-                    return true;
-                }
-            }
+            // the player can walk over grass, anything else in the way blocks them
             if let Some(info) = curr_terrain_map.get(&position_info.0) {
-                if PERMISSIBLE_TILES.contains(&info) {
+                if *info == TerrainKind::Grass {
                     return true;
                 }
             }
@@ -954,7 +806,30 @@ impl Player {
         true
     }
 
-    pub fn change_energy(&mut self, delta: i32) {
-        self.energy = max(0, min(self.energy as i32 + delta, MAX_PLAYER_ENERGY as i32)) as usize;
+    pub fn change_energy(&mut self, delta: i32, max_energy: usize) {
+        self.energy = max(0, min(self.energy as i32 + delta, max_energy as i32)) as usize;
+    }
+
+    pub fn heal(&mut self, amount: usize, max_health: usize) {
+        self.health = max(self.health, min(self.health + amount, max_health));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slam_hits_a_major_once() {
+        let mut world = World::empty_room(Position::new(18, 21));
+        let balance = world.balance.enemies.clone();
+        // its whole left column is next to the player
+        let major = Enemy::major_enemy(19, 20, world.world_position, &balance);
+        let mut expected = major.clone();
+        expected.damage(Damage::physical(world.balance.player.slam_damage), &balance);
+        world.place_enemy(major);
+
+        Player::slam(&mut world);
+        assert_eq!(world.enemies_map[0][0][0].health(), expected.health());
     }
 }
@@ -1,5 +1,14 @@
 use crate::{
-    direction::Direction, entity::Entity, player::Player, tile, utils::Position, world::World,
+    balance::{EnemyBalance, ProjectileBalance},
+    damage::{Damage, DamageKind},
+    direction::Direction,
+    entity::{Entity, Faction},
+    pathfinding::{Footprint, Pathfinder},
+    player::Player,
+    status::{StatusEffect, StatusKind},
+    tile::{EnemyKind, ProjectileKind, TerrainKind, TileKind},
+    utils::{Boss, Position},
+    world::{World, BOSS_ROOMS},
     BOARD_SIZE, TILE_SIZE, WORLD_SIZE,
 };
 use ggez::graphics::{self, Canvas};
@@ -8,53 +17,41 @@ use std::{
     collections::{HashMap, LinkedList},
 };
 
-const TRACKING_PROJECTILE_DAMAGE: usize = 100;
-const TRACKING_PROJECTILE_SPEED: usize = 3;
-const LIGHTNING_DAMAGE: usize = 80;
-const LIGHTNING_SPEED: usize = 0;
-const LIGHTNING_SIZE: i16 = 2;
-const PLAYER_PROJECTILE_DAMAGE: usize = 20;
-const PLAYER_PROJECTILE_SPEED: usize = 1;
-const FIRE_DAMAGE_INITIAL: usize = 60;
-const FIRE_DAMAGE_SECONDARY: usize = 45;
-const FIRE_DAMAGE_TERTIARY: usize = 30;
-const FIRE_DAMAGE_FINAL: usize = 15;
-const FIRE_SPEED: usize = 1;
-
-const PERMISSIBLE_TILES: [[f32; 4]; 9] = [
-    tile::WATER,
-    tile::GRASS,
-    tile::PLAYER,
-    // tile::PROJECTILE_PLAYER,
-    tile::CHASING_ENEMY,
-    tile::BOMBER_ENEMY,
-    tile::MAJOR_ENEMY,
-    tile::KNIGHT_ENEMY,
-    tile::SHOOTER_ENEMY,
-    tile::MAJOR_BOSS,
-];
-
 #[derive(Debug, serde::Deserialize, serde::Serialize, PartialEq)]
 pub struct Projectile {
     pub pos: Position,
     pub speed: usize,
     pub direction: Direction,
-    pub color: [f32; 4],
+    // What kind of projectile this is, lightning and fire move on to their next stage every turn
+    #[serde(default)]
+    pub kind: ProjectileKind,
     pub damage: usize,
     pub world_pos: Position,
-    // maybe add an alignment so projectiles from enemies cannot damage themselves and projectiles
-    // from players cannot damage themselves
+    // Saves up to version 2 stored the color instead of the kind, the migration converts it
+    #[serde(rename = "color", default, skip_serializing)]
+    pub legacy_color: [f32; 4],
+    // Who fired it, projectiles only hurt the other side. Saves up to version 3 only had the
+    // player's projectiles
+    #[serde(default)]
+    pub faction: Faction,
 }
 
 impl Projectile {
-    pub fn tracking_projectile(x: usize, y: usize, world_pos: Position) -> Self {
+    pub fn tracking_projectile(
+        x: usize,
+        y: usize,
+        world_pos: Position,
+        balance: &ProjectileBalance,
+    ) -> Self {
         Projectile {
             pos: Position::new(x, y),
-            speed: 2,
+            speed: balance.tracking_speed,
             direction: Direction::North,
-            color: tile::TRACKING_PROJECTILE,
-            damage: TRACKING_PROJECTILE_DAMAGE,
+            kind: ProjectileKind::Tracking,
+            damage: balance.tracking_damage,
             world_pos,
+            legacy_color: [0.0; 4],
+            faction: Faction::Player,
         }
     }
 
@@ -63,36 +60,69 @@ impl Projectile {
         y: usize,
         direction: Direction,
         world_pos: Position,
+        balance: &ProjectileBalance,
     ) -> Self {
         Projectile {
             pos: Position::new(x, y),
-            speed: PLAYER_PROJECTILE_SPEED,
+            speed: balance.player_speed,
             direction,
-            color: tile::PROJECTILE_PLAYER,
-            damage: PLAYER_PROJECTILE_DAMAGE,
+            kind: ProjectileKind::Player,
+            damage: balance.player_damage,
             world_pos,
+            legacy_color: [0.0; 4],
+            faction: Faction::Player,
         }
     }
 
-    pub fn lightning(x: usize, y: usize, world_pos: Position) -> Self {
+    // A shooter's shot, starting on the tile next to the shooter
+    pub fn enemy_shot(
+        x: usize,
+        y: usize,
+        direction: Direction,
+        world_pos: Position,
+        balance: &EnemyBalance,
+    ) -> Self {
         Projectile {
             pos: Position::new(x, y),
-            speed: LIGHTNING_SPEED,
+            speed: balance.shooter_shot_speed,
+            direction,
+            kind: ProjectileKind::Enemy,
+            damage: balance.shooter_damage,
+            world_pos,
+            legacy_color: [0.0; 4],
+            faction: Faction::Enemies,
+        }
+    }
+
+    pub fn lightning(x: usize, y: usize, world_pos: Position, balance: &ProjectileBalance) -> Self {
+        Projectile {
+            pos: Position::new(x, y),
+            speed: balance.lightning_speed,
             direction: Direction::North,
-            color: tile::LIGHTNING_PLACEHOLDER,
-            damage: LIGHTNING_DAMAGE,
+            kind: ProjectileKind::LightningPending,
+            damage: balance.lightning_damage,
             world_pos,
+            legacy_color: [0.0; 4],
+            faction: Faction::Player,
         }
     }
 
-    pub fn player_fire(x: usize, y: usize, direction: Direction, world_pos: Position) -> Self {
+    pub fn player_fire(
+        x: usize,
+        y: usize,
+        direction: Direction,
+        world_pos: Position,
+        balance: &ProjectileBalance,
+    ) -> Self {
         Projectile {
             pos: Position::new(x, y),
-            speed: FIRE_SPEED,
+            speed: balance.fire_speed,
             direction,
-            color: tile::FIRE_PLACEHOLDER,
-            damage: FIRE_DAMAGE_INITIAL,
+            kind: ProjectileKind::FirePending,
+            damage: balance.fire_damage_initial,
             world_pos,
+            legacy_color: [0.0; 4],
+            faction: Faction::Player,
         }
     }
 
@@ -102,7 +132,7 @@ impl Projectile {
         speed: usize,
         damage: usize,
         direction: Direction,
-        color: [f32; 4],
+        kind: ProjectileKind,
         player_pos: Position,
     ) -> Self {
         Projectile {
@@ -110,37 +140,89 @@ impl Projectile {
             speed,
             damage,
             direction,
-            color,
+            kind,
             world_pos: player_pos,
+            legacy_color: [0.0; 4],
+            faction: Faction::Player,
         }
     }
 
-    pub fn update(world: &mut World) {
+    // The damage it does to whatever it hits
+    pub fn hit(&self) -> Damage {
+        Damage::new(self.damage, DamageKind::of_projectile(self.kind))
+    }
+
+    // Fire and lightning hurt a vulnerable boss once a turn, however many of their tiles it is on,
+    // and fire leaves it burning too
+    fn damage_boss(
+        world: &mut World,
+        positions: &[Position],
+        damage: Damage,
+        effect: Option<StatusEffect>,
+    ) {
+        let world_pos = world.world_position;
+        if !BOSS_ROOMS.contains(&world_pos) || world.boss_defeated[world_pos.y][world_pos.x] {
+            return;
+        }
+        for pos in positions {
+            let hit_info = Boss::can_hit_boss(world, *pos, world_pos);
+            if hit_info.0 && hit_info.1 {
+                Boss::damage(world, damage, world_pos);
+                if let Some(effect) = effect {
+                    Boss::apply_status(world, effect, world_pos);
+                }
+                return;
+            }
+        }
+    }
+
+    // Fire and lightning hit each enemy of the room once, however many of their tiles it is on,
+    // so a major isn't hit once for every tile of it they cover
+    fn damage_enemies(
+        world: &mut World,
+        positions: &[Position],
+        damage: Damage,
+        effect: Option<StatusEffect>,
+    ) {
+        for enemy in &mut world.enemies_map[world.world_position.y][world.world_position.x] {
+            if enemy.pos.iter().any(|tile| positions.contains(tile)) {
+                enemy.hit_by_projectile(damage, &world.balance.enemies);
+                if let Some(effect) = effect {
+                    enemy.status.apply(effect);
+                }
+            }
+        }
+    }
+
+    pub fn update(world: &mut World, pathfinder: &mut Pathfinder) {
+        let balance = world.balance.projectiles.clone();
         let mut index: i32 = 0;
         for _ in 0..world.projectiles.len() {
-            match world.projectiles[index as usize].color {
-                tile::LIGHTNING_PLACEHOLDER => {
+            match world.projectiles[index as usize].kind {
+                ProjectileKind::LightningPending => {
                     let pos = world.projectiles[index as usize].pos;
                     let world_pos = world.projectiles[index as usize].world_pos;
-                    world.projectiles[index as usize].color = tile::LIGHTNING_INITIAL;
+                    world.projectiles[index as usize].kind = ProjectileKind::LightningInitial;
                     world.atmosphere_map[world_pos.y][world_pos.x]
-                        .insert(pos, tile::LIGHTNING_INITIAL);
+                        .insert(pos, TileKind::Projectile(ProjectileKind::LightningInitial));
                 }
-                tile::LIGHTNING_INITIAL => {
+                ProjectileKind::LightningInitial => {
                     let pos = world.projectiles[index as usize].pos;
                     let world_pos = world.projectiles[index as usize].world_pos;
-                    world.projectiles[index as usize].color = tile::LIGHTNING_SECONDARY;
+                    world.projectiles[index as usize].kind = ProjectileKind::LightningSecondary;
                     world.atmosphere_map[world_pos.y][world_pos.x]
-                        .insert(pos, tile::LIGHTNING_SECONDARY);
+                        .insert(pos, TileKind::Projectile(ProjectileKind::LightningSecondary));
                 }
-                tile::LIGHTNING_SECONDARY => {
+                ProjectileKind::LightningSecondary => {
                     const deltas: [i16; 3] = [0, 1, -1];
                     let pos = world.projectiles[index as usize].pos;
                     let world_pos = world.projectiles[index as usize].world_pos;
-                    world.projectiles[index as usize].color = tile::LIGHTNING_FINAL;
+                    world.projectiles[index as usize].kind = ProjectileKind::LightningFinal;
+                    let damage = Damage::new(balance.lightning_damage, DamageKind::Lightning);
+                    let mut struck = Vec::new();
                     // basically checks the 8 around and including the projectile and turns
                     // them to their original state
-                    for i in 1..=LIGHTNING_SIZE {
+                    for i in 1..=balance.lightning_size {
                         for x_delta in deltas {
                             for y_delta in deltas {
                                 if pos.x < (WORLD_SIZE.0 - x_delta * i) as usize
@@ -153,18 +235,16 @@ impl Projectile {
                                         (pos.y as i16 + (y_delta * i)) as usize,
                                     );
                                     world.atmosphere_map[world_pos.y][world_pos.x]
-                                        .insert(new_position, tile::LIGHTNING_FINAL);
-                                    for enemy in &mut world.enemies_map[world.world_position.y][world.world_position.x] {
-                                        if enemy.pos.contains(&new_position) {
-                                            enemy.damage(LIGHTNING_DAMAGE);
-                                        }
-                                    }
+                                        .insert(new_position, TileKind::Projectile(ProjectileKind::LightningFinal));
+                                    struck.push(new_position);
                                 }
                             }
                         }
                     }
+                    Self::damage_enemies(world, &struck, damage, None);
+                    Self::damage_boss(world, &struck, damage, None);
                 }
-                tile::LIGHTNING_FINAL => {
+                ProjectileKind::LightningFinal => {
                     const deltas: [i16; 3] = [0, 1, -1];
                     let pos = world.projectiles[index as usize].pos;
                     let world_pos = world.projectiles[index as usize].world_pos;
@@ -172,7 +252,7 @@ impl Projectile {
                     index -= 1;
                     // basically checks the 8 around and including the projectile and turns
                     // them to their original state
-                    for i in 1..=LIGHTNING_SIZE {
+                    for i in 1..=balance.lightning_size {
                         for x_delta in deltas {
                             for y_delta in deltas {
                                 if pos.x < (WORLD_SIZE.0 - x_delta * i) as usize
@@ -191,21 +271,24 @@ impl Projectile {
                         }
                     }
                 }
-                tile::FIRE_PLACEHOLDER => {
+                ProjectileKind::FirePending => {
                     let pos = world.projectiles[index as usize].pos;
                     let world_pos = world.projectiles[index as usize].world_pos;
-                    world.projectiles[index as usize].color = tile::FIRE_INITIAL;
-                    world.atmosphere_map[world_pos.y][world_pos.x].insert(pos, tile::FIRE_INITIAL);
-                    for enemy in &mut world.enemies_map[world.world_position.y][world.world_position.x] {
-                        if enemy.pos.contains(&pos) {
-                            enemy.damage(FIRE_DAMAGE_INITIAL);
-                        }
-                    }
+                    world.projectiles[index as usize].kind = ProjectileKind::FireInitial;
+                    world.atmosphere_map[world_pos.y][world_pos.x].insert(pos, TileKind::Projectile(ProjectileKind::FireInitial));
+                    let damage = Damage::new(balance.fire_damage_initial, DamageKind::Fire);
+                    let burn = StatusEffect::with_damage(
+                        StatusKind::Burn,
+                        balance.fire_burn_turns,
+                        balance.fire_burn_damage,
+                    );
+                    Self::damage_enemies(world, &[pos], damage, Some(burn));
+                    Self::damage_boss(world, &[pos], damage, Some(burn));
                 }
-                tile::FIRE_INITIAL => {
+                ProjectileKind::FireInitial => {
                     let world_pos = world.projectiles[index as usize].world_pos;
                     let old_pos = world.projectiles[index as usize].pos;
-                    world.projectiles[index as usize].color = tile::FIRE_SECONDARY;
+                    world.projectiles[index as usize].kind = ProjectileKind::FireSecondary;
                     world.atmosphere_map[world_pos.y][world_pos.x].remove(&old_pos);
                     let (new_pos, new_world_pos) = World::new_position(
                         old_pos,
@@ -243,23 +326,24 @@ impl Projectile {
                             new_positions.push(positive_new_pos);
                         }
                     }
-                    for enemy in &mut world.enemies_map[world.world_position.y][world.world_position.x] {
-                        for new_pos in &new_positions {
-                            if enemy.pos.contains(&new_pos) {
-                                enemy.damage(FIRE_DAMAGE_SECONDARY);
-                            }
-                        }
-                    }
+                    let damage = Damage::new(balance.fire_damage_secondary, DamageKind::Fire);
+                    let burn = StatusEffect::with_damage(
+                        StatusKind::Burn,
+                        balance.fire_burn_turns,
+                        balance.fire_burn_damage,
+                    );
+                    Self::damage_enemies(world, &new_positions, damage, Some(burn));
+                    Self::damage_boss(world, &new_positions, damage, Some(burn));
                     for new_position in &new_positions {
                         world.atmosphere_map[world_pos.y][world_pos.x]
-                            .insert(*new_position, tile::FIRE_SECONDARY);
+                            .insert(*new_position, TileKind::Projectile(ProjectileKind::FireSecondary));
                     }
                 }
                 // TODO: Copy paste code
-                tile::FIRE_SECONDARY => {
+                ProjectileKind::FireSecondary => {
                     let world_pos = world.projectiles[index as usize].world_pos;
                     let old_pos = world.projectiles[index as usize].pos;
-                    world.projectiles[index as usize].color = tile::FIRE_TERTIARY;
+                    world.projectiles[index as usize].kind = ProjectileKind::FireTertiary;
                     world.atmosphere_map[world_pos.y][world_pos.x].remove(&old_pos);
                     let (new_pos, new_world_pos) = World::new_position(
                         old_pos,
@@ -317,22 +401,23 @@ impl Projectile {
                             new_positions.push(positive_new_pos);
                         }
                     }
-                    for enemy in &mut world.enemies_map[world.world_position.y][world.world_position.x] {
-                        for new_pos in &new_positions {
-                            if enemy.pos.contains(&*new_pos) {
-                                enemy.damage(FIRE_DAMAGE_TERTIARY);
-                            }
-                        }
-                    }
+                    let damage = Damage::new(balance.fire_damage_tertiary, DamageKind::Fire);
+                    let burn = StatusEffect::with_damage(
+                        StatusKind::Burn,
+                        balance.fire_burn_turns,
+                        balance.fire_burn_damage,
+                    );
+                    Self::damage_enemies(world, &new_positions, damage, Some(burn));
+                    Self::damage_boss(world, &new_positions, damage, Some(burn));
                     for new_position in &new_positions {
                         world.atmosphere_map[world_pos.y][world_pos.x]
-                            .insert(*new_position, tile::FIRE_TERTIARY);
+                            .insert(*new_position, TileKind::Projectile(ProjectileKind::FireTertiary));
                     }
                 }
-                tile::FIRE_TERTIARY => {
+                ProjectileKind::FireTertiary => {
                     let world_pos = world.projectiles[index as usize].world_pos;
                     let old_pos = world.projectiles[index as usize].pos;
-                    world.projectiles[index as usize].color = tile::FIRE_FINAL;
+                    world.projectiles[index as usize].kind = ProjectileKind::FireFinal;
                     let (new_pos, new_world_pos) = World::new_position(
                         old_pos,
                         world.projectiles[index as usize].direction,
@@ -389,19 +474,20 @@ impl Projectile {
                             new_positions.push(positive_new_pos);
                         }
                     }
-                    for enemy in &mut world.enemies_map[world.world_position.y][world.world_position.x] {
-                        for new_pos in &new_positions {
-                            if enemy.pos.contains(&new_pos) {
-                                enemy.damage(FIRE_DAMAGE_FINAL);
-                            }
-                        }
-                    }
+                    let damage = Damage::new(balance.fire_damage_final, DamageKind::Fire);
+                    let burn = StatusEffect::with_damage(
+                        StatusKind::Burn,
+                        balance.fire_burn_turns,
+                        balance.fire_burn_damage,
+                    );
+                    Self::damage_enemies(world, &new_positions, damage, Some(burn));
+                    Self::damage_boss(world, &new_positions, damage, Some(burn));
                     for new_position in &new_positions {
                         world.atmosphere_map[world_pos.y][world_pos.x]
-                            .insert(*new_position, tile::FIRE_FINAL);
+                            .insert(*new_position, TileKind::Projectile(ProjectileKind::FireFinal));
                     }
                 }
-                tile::FIRE_FINAL => {
+                ProjectileKind::FireFinal => {
                     // TODO: get this dissapearing the thing properly
                     let world_pos = world.projectiles[index as usize].world_pos;
                     let mut new_positions = Vec::new();
@@ -438,10 +524,10 @@ impl Projectile {
                     Projectile::kill(index as usize, world);
                     index -= 1;
                 }
-                tile::TRACKING_PROJECTILE => {
+                ProjectileKind::Tracking => {
                     // move_tracking projectile(index, world);
                     let (found_path, collided) =
-                        Self::move_tracking_projectile(index as usize, world);
+                        Self::move_tracking_projectile(index as usize, world, pathfinder);
                     if (!found_path || collided) {
                         Projectile::kill(index as usize, world);
                         index -= 1;
@@ -467,10 +553,15 @@ impl Projectile {
 
     // returns (found_a_path, collided_with_enemy)
     // ASSUMES THAT THIS IS JUST FOR PLAYERS
-    pub fn move_tracking_projectile(index: usize, world: &mut World) -> (bool, bool) {
+    pub fn move_tracking_projectile(
+        index: usize,
+        world: &mut World,
+        pathfinder: &mut Pathfinder,
+    ) -> (bool, bool) {
         // This gets the shortest path
-        let mut travel_path = Self::get_best_path(index, world);
+        let mut travel_path = Self::get_best_path(index, world, pathfinder);
         let projectile = &world.projectiles[index];
+        let projectile_hit = projectile.hit();
         let mut cur_pos = projectile.pos;
         for _ in 0..projectile.speed {
             if let Some(new_pos) = travel_path.pop_front() {
@@ -481,7 +572,7 @@ impl Projectile {
                     let mut index_enemy: i32 = 0;
                     for _ in 0..world.enemies_map[world.world_position.y][world.world_position.x].len() {
                         if (world.enemies_map[world.world_position.y][world.world_position.x][index_enemy as usize].pos.contains(&new_pos)) {
-                            world.enemies_map[world.world_position.y][world.world_position.x][index_enemy as usize].damage(TRACKING_PROJECTILE_DAMAGE);
+                            world.enemies_map[world.world_position.y][world.world_position.x][index_enemy as usize].hit_by_projectile(projectile_hit, &world.balance.enemies);
                             return (true, true);
                         }
                         index_enemy += 1;
@@ -500,115 +591,40 @@ impl Projectile {
         (true, false)
     }
 
-    pub fn get_best_path(index: usize, world: &mut World) -> LinkedList<Position> {
-        // Used to check if the enemy should be able to dodge around player projectiles
-
-        let projectile = &world.projectiles[index];
-        let world_pos = world.world_position;
-        let mut target_pos =
-            Position::new((WORLD_SIZE.0 + 1) as usize, (WORLD_SIZE.1 + 1) as usize);
-        // this is a visited array to save if we have visited a location on the grid
-        let mut visited = [[false; WORLD_SIZE.0 as usize]; WORLD_SIZE.1 as usize];
-
-        // this stores every location's previous location so that we can reconstruct the best path
-        // given our start and end
-        let mut previous = [[Position::new(WORLD_SIZE.0 as usize + 1, WORLD_SIZE.1 as usize + 1);
-            WORLD_SIZE.0 as usize]; WORLD_SIZE.1 as usize];
-        let mut queue = LinkedList::new();
-        queue.push_back(projectile.pos);
-
-        visited[projectile.pos.y][projectile.pos.x] = true;
-        // visited[enemy.pos.y - (world.world_position.y * WORLD_SIZE.1 as usize)][enemy.pos.x - (world.world_position.x * WORLD_SIZE.0 as usize)] = true;
-        while !queue.is_empty() {
-            if let Some(node) = queue.pop_front() {
-                // if there is an entity at the new position and it's an enemy, we know we have
-                // found the shortest path to an enemy, so end immediately to reconstruct best path
-                if world.entity_map[world_pos.y][world_pos.x].contains_key(&node)
-                    && world.entity_map[world_pos.y][world_pos.x][&node].1 == Entity::Enemy
-                {
-                    // reached the goal location, break and reconstruct path
-                    target_pos = node;
-                    break;
-                }
-
-                // standard bfs stuff, for each neighbor, if it hasn't been visited, put it into
-                // the queue
-                let neighbors = Self::get_neighbors(world, node, index, Entity::Projectile);
-                for next in neighbors {
-                    if !visited[next.y][next.x] {
-                        queue.push_back(next);
-                        visited[next.y][next.x] = true;
-
-                        // mark the previous of the neighbor as the node to reconstruct the path
-                        previous[next.y][next.x] = node;
-                    }
-                }
-            }
-        }
-
-        // This uses the previous 2 dimensional array to reconstruct the best path
-        let mut path = LinkedList::new();
-        let projectile_pos = world.projectiles[index].pos;
-        while target_pos != projectile_pos {
-            path.push_front(target_pos);
-
-            // if the position's or y is greater than the world size, that means that a path wasn't
-            // found, as it means the previous position did not have a previous, so we break out
-            if target_pos.x as i16 >= WORLD_SIZE.0 {
-                break;
-            }
-            target_pos = previous[target_pos.y][target_pos.x];
-        }
-        path
-    }
-
-    pub fn get_neighbors(
-        world: &mut World,
-        position: Position,
+    // The cheapest path from the missile to the closest enemy, see pathfinding. Missiles fly, so
+    // every tile they can enter costs the same. When there is none it is a single position off the
+    // room, which is how the caller tells
+    pub fn get_best_path(
         index: usize,
-        entity_type: Entity,
-    ) -> Vec<Position> {
-        let directions = [
-            Direction::North,
-            Direction::South,
-            Direction::West,
-            Direction::East,
-        ];
-        let mut moves = Vec::new();
-
-        // loop through all the directions
-        for direction in directions {
-            let (new_pos, _) = World::new_position(
-                position,
-                direction,
-                world,
-                1,
-                entity_type.clone(),
-                Some(index),
-            );
-            // if the new position is valid(correct tiles & within bounds) add it to the potential
-            // neighbors
-            if new_pos != world.projectiles[index].pos
-                && Self::can_travel_to(world, (new_pos, world.projectiles[index].world_pos))
-                && world.projectiles[index].world_pos == world.world_position
-            {
-                moves.push(new_pos);
-            }
-        }
-        return moves;
+        world: &World,
+        pathfinder: &mut Pathfinder,
+    ) -> LinkedList<Position> {
+        let projectile = &world.projectiles[index];
+        let (start, room) = (projectile.pos, projectile.world_pos);
+        let targets: Vec<Position> = world.entity_map[room.y][room.x]
+            .iter()
+            .filter(|(_, (_, entity))| *entity == Entity::Enemy)
+            .map(|(pos, _)| *pos)
+            .collect();
+        let path = pathfinder.find_path(start, Footprint::SINGLE, &targets, |tile| {
+            (room == world.world_position && Self::can_travel_to(world, (tile, room))).then_some(1)
+        });
+        path.unwrap_or_else(|| {
+            LinkedList::from([Position::new(WORLD_SIZE.0 as usize + 1, WORLD_SIZE.1 as usize + 1)])
+        })
     }
 
     pub fn kill(index: usize, world: &mut World) {
-        match world.projectiles[index].color {
-            tile::TRACKING_PROJECTILE
-            | tile::FIRE_INITIAL
-            | tile::FIRE_SECONDARY
-            | tile::FIRE_TERTIARY
-            | tile::FIRE_PLACEHOLDER
-            | tile::LIGHTNING_FINAL
-            | tile::LIGHTNING_INITIAL
-            | tile::LIGHTNING_SECONDARY
-            | tile::LIGHTNING_PLACEHOLDER => {
+        match world.projectiles[index].kind {
+            ProjectileKind::Tracking
+            | ProjectileKind::FireInitial
+            | ProjectileKind::FireSecondary
+            | ProjectileKind::FireTertiary
+            | ProjectileKind::FirePending
+            | ProjectileKind::LightningFinal
+            | ProjectileKind::LightningInitial
+            | ProjectileKind::LightningSecondary
+            | ProjectileKind::LightningPending => {
                 world.atmosphere_map[world.projectiles[index].world_pos.y]
                     [world.projectiles[index].world_pos.x]
                     .remove(&world.projectiles[index].pos);
@@ -623,7 +639,7 @@ impl Projectile {
     }
 
     pub fn can_travel_to(
-        world: &mut World,
+        world: &World,
         position_info: (Position, Position), //Where .0 is the position, and .1 is the world_position
     ) -> bool {
         //Get the map on which the position is on
@@ -635,12 +651,12 @@ impl Projectile {
             || curr_terrain_map.contains_key(&position_info.0)
         {
             if let Some(info) = curr_entity_map.get(&position_info.0) {
-                if PERMISSIBLE_TILES.contains(&info.0) {
+                if Self::can_enter_tile(info.0) {
                     return true;
                 }
             }
             if let Some(info) = curr_terrain_map.get(&position_info.0) {
-                if PERMISSIBLE_TILES.contains(&info) {
+                if Self::can_enter_terrain(*info) {
                     return true;
                 }
             }
@@ -648,4 +664,70 @@ impl Projectile {
         }
         true
     }
+
+    // Projectiles fly over water and grass, but no other terrain
+    pub fn can_enter_terrain(kind: TerrainKind) -> bool {
+        kind == TerrainKind::Water || kind == TerrainKind::Grass
+    }
+
+    // Projectiles can move into the player and into enemies, which is where they hit them. A
+    // bomber that is about to explode or has exploded blocks them instead
+    fn can_enter_tile(kind: TileKind) -> bool {
+        match kind {
+            TileKind::Player => true,
+            TileKind::Enemy(EnemyKind::BomberActivated | EnemyKind::BomberDeactivated) => false,
+            TileKind::Enemy(_) => true,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::enemy::Enemy;
+
+    // A major with its top left tile at anchor, and what it looks like after one hit
+    fn major_hit_once(world: &mut World, anchor: Position, hit: Damage) -> Enemy {
+        let balance = world.balance.enemies.clone();
+        let major = Enemy::major_enemy(anchor.x, anchor.y, world.world_position, &balance);
+        let mut expected = major.clone();
+        expected.hit_by_projectile(hit, &balance);
+        world.place_enemy(major);
+        expected
+    }
+
+    fn launch(world: &mut World, mut projectile: Projectile, kind: ProjectileKind) {
+        projectile.kind = kind;
+        world.projectiles.push(projectile);
+        Projectile::update(world, &mut Pathfinder::default());
+    }
+
+    #[test]
+    fn fire_hits_a_major_once() {
+        let mut world = World::empty_room(Position::new(5, 5));
+        let balance = world.balance.projectiles.clone();
+        let hit = Damage::new(balance.fire_damage_secondary, DamageKind::Fire);
+        // the fire moves onto x 19 and spreads over y 20 to 22, the major's whole left column
+        let expected = major_hit_once(&mut world, Position::new(19, 20), hit);
+        let fire = Projectile::player_fire(18, 21, Direction::East, world.world_position, &balance);
+        launch(&mut world, fire, ProjectileKind::FireInitial);
+
+        let major = &world.enemies_map[0][0][0];
+        assert_eq!(major.health(), expected.health());
+        assert!(major.status.has(StatusKind::Burn));
+    }
+
+    #[test]
+    fn lightning_hits_a_major_once() {
+        let mut world = World::empty_room(Position::new(5, 5));
+        let balance = world.balance.projectiles.clone();
+        let hit = Damage::new(balance.lightning_damage, DamageKind::Lightning);
+        // striking the middle of the major covers all nine of its tiles
+        let expected = major_hit_once(&mut world, Position::new(19, 20), hit);
+        let lightning = Projectile::lightning(20, 21, world.world_position, &balance);
+        launch(&mut world, lightning, ProjectileKind::LightningSecondary);
+
+        assert_eq!(world.enemies_map[0][0][0].health(), expected.health());
+    }
 }
@@ -0,0 +1,343 @@
+use crate::{
+    action::Action,
+    balance::Balance,
+    game::Game,
+    game::TurnOutcome,
+    save,
+    tile::{TerrainKind, TileKind},
+    utils::Position,
+    world::World,
+};
+
+use std::fmt;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+// How many turns are played between two recorded hashes of the world
+const CHECKPOINT_INTERVAL: usize = 25;
+
+// A run is fully determined by the seed the world was generated from, the balance it was played
+// with and the actions the player took, so that is all a replay stores. The checkpoints are
+// (turn, hash of the world after that turn) pairs, used to check that re-running the actions
+// really ends up in the same world
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub actions: Vec<Action>,
+    pub checkpoints: Vec<(usize, u64)>,
+    // Replays from before the balance was configurable were played with the defaults
+    #[serde(default)]
+    pub balance: Balance,
+}
+
+// Why re-running a replay did not end up where the recording did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    // The action at this turn was accepted when recorded but not when replayed
+    Rejected { turn: usize, action: Action },
+    // The world after this turn does not hash to the recorded value
+    Mismatch { turn: usize, expected: u64, found: u64 },
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Rejected { turn, action } => {
+                write!(f, "turn {}: recorded action {:?} was not accepted", turn, action)
+            }
+            ReplayError::Mismatch {
+                turn,
+                expected,
+                found,
+            } => write!(
+                f,
+                "turn {}: world hash {:016x} does not match recorded {:016x}",
+                turn, found, expected
+            ),
+        }
+    }
+}
+
+impl Replay {
+    pub fn new(seed: u64, balance: &Balance) -> Self {
+        Replay {
+            seed,
+            actions: Vec::new(),
+            checkpoints: Vec::new(),
+            balance: balance.clone(),
+        }
+    }
+
+    // The number of turns recorded so far
+    pub fn turn(&self) -> usize {
+        self.actions.len()
+    }
+
+    // Forgets every turn after the given one
+    pub fn truncate(&mut self, turn: usize) {
+        self.actions.truncate(turn);
+        self.checkpoints.retain(|(checkpoint_turn, _)| *checkpoint_turn <= turn);
+    }
+
+    // Records an action that was accepted by the game, the world passed in should be the world
+    // after the whole turn was played
+    pub fn record(&mut self, action: Action, world: &World) {
+        self.actions.push(action);
+        if self.turn() % CHECKPOINT_INTERVAL == 0 {
+            self.checkpoint(world);
+        }
+    }
+
+    // Records the hash of the world at the current turn, if it isn't recorded already. Called
+    // before writing the replay so that the last turn is always checked
+    pub fn checkpoint(&mut self, world: &World) {
+        let turn = self.turn();
+        if self.checkpoints.last().map(|(t, _)| *t) != Some(turn) {
+            self.checkpoints.push((turn, world_hash(world)));
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        let serialized = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(save::serialize_error)?;
+        save::write_atomic(path, serialized.as_bytes())
+    }
+
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let replay_str = fs::read_to_string(path)
+            .map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+        ron::from_str(&replay_str).map_err(|e| format!("couldn't parse {}: {}", path.display(), e))
+    }
+
+    // Re-runs the whole replay without a window, checking every recorded hash along the way.
+    // Returns the game as it is after the last action
+    pub fn verify(&self) -> Result<Game, ReplayError> {
+        let mut game = Game::new(self.seed, &self.balance);
+        let mut checkpoints = self.checkpoints.iter().peekable();
+        // a checkpoint at turn 0 checks the freshly generated world
+        Self::check(&game.world, 0, &mut checkpoints)?;
+        for (index, action) in self.actions.iter().enumerate() {
+            let turn = index + 1;
+            if game.step(*action) == TurnOutcome::Ignored {
+                return Err(ReplayError::Rejected {
+                    turn,
+                    action: *action,
+                });
+            }
+            Self::check(&game.world, turn, &mut checkpoints)?;
+        }
+        Ok(game)
+    }
+
+    fn check<'a>(
+        world: &World,
+        turn: usize,
+        checkpoints: &mut std::iter::Peekable<impl Iterator<Item = &'a (usize, u64)>>,
+    ) -> Result<(), ReplayError> {
+        while let Some((checkpoint_turn, expected)) = checkpoints.peek() {
+            if *checkpoint_turn > turn {
+                break;
+            }
+            if *checkpoint_turn == turn {
+                let found = world_hash(world);
+                if found != *expected {
+                    return Err(ReplayError::Mismatch {
+                        turn,
+                        expected: *expected,
+                        found,
+                    });
+                }
+            }
+            checkpoints.next();
+        }
+        Ok(())
+    }
+}
+
+// FNV-1a, used instead of the std hasher because its output has to stay the same across
+// processes and compiler versions for recorded hashes to mean anything. Integers are written as
+// little endian, and usize and isize as 64 bits, so it is also the same on every platform. That
+// covers the kinds too: their derived Hash writes the index of their variant as an isize, which
+// stays the same as long as new variants are only ever added at the end
+struct StableHasher(u64);
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as i64 as u64);
+    }
+}
+
+// Hashes everything in the world that the game logic reads. The maps are hashed in sorted order
+// since iteration order of a HashMap changes from one process to the next
+pub fn world_hash(world: &World) -> u64 {
+    let mut hasher = StableHasher(0xcbf29ce484222325);
+
+    world.world_position.hash(&mut hasher);
+    let player = &world.player;
+    player.pos.hash(&mut hasher);
+    player.direction.hash(&mut hasher);
+    player.health().hash(&mut hasher);
+    player.energy().hash(&mut hasher);
+    player.is_alive().hash(&mut hasher);
+    player.status.hash(&mut hasher);
+
+    for row in &world.enemies_map {
+        for enemies in row {
+            for enemy in enemies {
+                enemy.pos.hash(&mut hasher);
+                enemy.direction.hash(&mut hasher);
+                enemy.health().hash(&mut hasher);
+                enemy.world_pos.hash(&mut hasher);
+                enemy.kind.hash(&mut hasher);
+                enemy.reload.hash(&mut hasher);
+                enemy.lunge.hash(&mut hasher);
+                enemy.status.hash(&mut hasher);
+                enemy.initiative.hash(&mut hasher);
+                enemy.ai.hash(&mut hasher);
+                enemy.ai_turns.hash(&mut hasher);
+                enemy.last_seen.hash(&mut hasher);
+                enemy.affixes.hash(&mut hasher);
+                enemy.shield.hash(&mut hasher);
+            }
+        }
+    }
+
+    for boss in &world.bosses {
+        boss.position.hash(&mut hasher);
+        boss.world_position.hash(&mut hasher);
+        boss.health.hash(&mut hasher);
+        boss.shield_health.hash(&mut hasher);
+        boss.status.hash(&mut hasher);
+    }
+    world.boss_defeated.hash(&mut hasher);
+
+    for projectile in &world.projectiles {
+        projectile.pos.hash(&mut hasher);
+        projectile.direction.hash(&mut hasher);
+        projectile.world_pos.hash(&mut hasher);
+        projectile.kind.hash(&mut hasher);
+    }
+
+    for row in &world.entity_map {
+        for entities in row {
+            let mut sorted: Vec<(&Position, &(TileKind, _))> = entities.iter().collect();
+            sorted.sort_by_key(|(pos, _)| (pos.y, pos.x));
+            for (pos, (kind, entity)) in sorted {
+                pos.hash(&mut hasher);
+                kind.hash(&mut hasher);
+                entity.hash(&mut hasher);
+            }
+        }
+    }
+
+    for row in &world.terrain_map {
+        for tiles in row {
+            let mut sorted: Vec<(&Position, &TerrainKind)> = tiles.iter().collect();
+            sorted.sort_by_key(|(pos, _)| (pos.y, pos.x));
+            for (pos, kind) in sorted {
+                pos.hash(&mut hasher);
+                kind.hash(&mut hasher);
+            }
+        }
+    }
+
+    for row in &world.atmosphere_map {
+        for tiles in row {
+            let mut sorted: Vec<(&Position, &TileKind)> = tiles.iter().collect();
+            sorted.sort_by_key(|(pos, _)| (pos.y, pos.x));
+            for (pos, kind) in sorted {
+                pos.hash(&mut hasher);
+                kind.hash(&mut hasher);
+            }
+        }
+    }
+
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::direction::Direction;
+
+    // A checkpoint's worth of turns and a few more, most of them spent waiting
+    fn actions() -> Vec<Action> {
+        let mut actions = vec![Action::Move(Direction::South), Action::Move(Direction::East)];
+        actions.resize(CHECKPOINT_INTERVAL + 5, Action::Wait);
+        actions
+    }
+
+    // Plays the actions and returns the game with its replay, checkpointed at the last turn the
+    // way saving it would
+    fn record(seed: u64, actions: &[Action]) -> (Game, Replay) {
+        let mut game = Game::new(seed, &Balance::default());
+        for action in actions {
+            assert_eq!(game.step(*action), TurnOutcome::Played);
+        }
+        let mut replay = game.replay.clone().unwrap();
+        replay.checkpoint(&game.world);
+        (game, replay)
+    }
+
+    #[test]
+    fn verify_reproduces_the_recorded_game() {
+        let (game, replay) = record(7, &actions());
+        assert_eq!(replay.turn(), game.turn);
+        assert_eq!(replay.checkpoints.len(), 2);
+
+        let replayed = replay.verify().unwrap();
+        assert_eq!(replayed.turn, game.turn);
+        assert_eq!(replayed.world.player.pos, game.world.player.pos);
+        assert_eq!(world_hash(&replayed.world), world_hash(&game.world));
+    }
+
+    #[test]
+    fn another_seed_fails_at_the_first_checkpoint() {
+        let (_, mut replay) = record(7, &actions());
+        replay.seed = 8;
+        assert!(matches!(
+            replay.verify(),
+            Err(ReplayError::Mismatch { turn: CHECKPOINT_INTERVAL, .. })
+        ));
+    }
+
+    #[test]
+    fn another_action_fails_at_the_first_checkpoint() {
+        let (game, mut replay) = record(7, &actions());
+        assert_ne!(game.world.player.pos, Position::new(0, 0));
+        // waiting instead of stepping leaves the player somewhere else
+        replay.actions[0] = Action::Wait;
+        assert!(matches!(
+            replay.verify(),
+            Err(ReplayError::Mismatch { turn: CHECKPOINT_INTERVAL, .. })
+        ));
+    }
+}
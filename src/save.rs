@@ -0,0 +1,438 @@
+use crate::{
+    ai::AiState,
+    game::Game,
+    status::{StatusEffect, StatusKind},
+    tile::{AttackStage, BossKind, EnemyKind, ProjectileKind, TerrainKind, TileKind},
+    world::World,
+};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+// Bumped whenever the layout of SaveFile, or of anything inside the World, changes. Version 0 is
+// the is_serialized/world/rng triple from before there was a save file
+pub const SAVE_VERSION: u32 = 10;
+// Name of the save inside the save directory
+const SAVE_FILE: &'static str = "save.ron";
+
+/* How to change anything that gets saved:
+ *      - Bump SAVE_VERSION and add a migration to the end of MIGRATIONS, which takes a save from
+ *        the previous version to the new one
+ *      - A save is always parsed with the current structs and migrated afterwards, so every field
+ *        added to World or anything inside it needs #[serde(default)] (or a default function) to
+ *        still parse old saves. The migration then fills in a proper value where the default
+ *        isn't good enough
+ *      - A field that is renamed keeps its old name as #[serde(alias = "...")]
+ *      - A field that is replaced stays behind as #[serde(default, skip_serializing)] so the
+ *        migration can read it and convert it into the new field. If the new field keeps the
+ *        Rust name, it is saved under a new name with #[serde(rename = "...")] and the old one
+ *        becomes legacy_<name>, renamed back to its old name in the save
+ *      - Removed fields need nothing, unknown fields are ignored when parsing
+ *      - Add a save written by the new version to fixtures/saves/ and a test for it to
+ *        tests/saves.rs, then check every fixture still loads with cargo test
+ */
+
+// MIGRATIONS[n] upgrades a save from version n to version n + 1
+const MIGRATIONS: [fn(&mut SaveFile); SAVE_VERSION as usize] = [
+    migrate_v0_to_v1,
+    migrate_v1_to_v2,
+    migrate_v2_to_v3,
+    migrate_v3_to_v4,
+    migrate_v4_to_v5,
+    migrate_v5_to_v6,
+    migrate_v6_to_v7,
+    migrate_v7_to_v8,
+    migrate_v8_to_v9,
+    migrate_v9_to_v10,
+];
+
+// Colors bosses and their attacks were saved with up to version 2. The attack stages are in the
+// order they are shown in, the last one being the attack itself
+const LEGACY_BOSS_COLORS: [(BossKind, [f32; 4]); 5] = [
+    (BossKind::Laser, [0.2, 0.8, 0.2, 1.0]),
+    (BossKind::Column, [0.0, 0.6, 0.99, 1.0]),
+    (BossKind::Chasing, [0.6, 0.0, 0.98, 1.0]),
+    (BossKind::BlackOut, [0.6, 0.09, 0.6, 1.0]),
+    (BossKind::Major, [0.8, 0.3, 0.3, 1.0]),
+];
+const LEGACY_LASER_STAGES: [[f32; 4]; 3] =
+    [[0.8, 0.6, 0.6, 0.3], [1.0, 0.0, 0.0, 0.5], [1.0, 0.0, 0.0, 1.0]];
+const LEGACY_ASTEROID_STAGES: [[f32; 4]; 4] = [
+    [0.99, 0.8, 0.7, 0.2],
+    [0.98, 0.8, 0.5, 0.3],
+    [1.0, 0.5, 0.15, 0.5],
+    [1.0, 0.5, 0.0, 1.0],
+];
+const LEGACY_STUN_WELL_STAGES: [[f32; 4]; 2] = [[1.0, 0.95, 0.7, 0.5], [0.99, 0.9, 0.1, 0.4]];
+
+// The triple only moved into a single file, the world itself has the same layout
+fn migrate_v0_to_v1(_save: &mut SaveFile) {}
+
+// Adds the turn count and seed. Neither was tracked before, so they stay at their defaults of 0
+// and unknown
+fn migrate_v1_to_v2(_save: &mut SaveFile) {}
+
+// Tiles, enemies, projectiles, bosses and boss attacks used to be told apart by the color they
+// were drawn with, now they have a kind. Anything with a color that no kind was ever drawn with
+// can't be converted and is dropped
+fn migrate_v2_to_v3(save: &mut SaveFile) {
+    let world = &mut save.world;
+    let mut dropped = 0;
+
+    for y in 0..world.legacy_entity_map.len() {
+        for x in 0..world.legacy_entity_map[y].len() {
+            for (pos, (color, entity)) in world.legacy_entity_map[y][x].drain() {
+                match TileKind::from_legacy_color(color) {
+                    Some(kind) => {
+                        world.entity_map[y][x].insert(pos, (kind, entity));
+                    }
+                    None => dropped += 1,
+                }
+            }
+            for (pos, color) in world.legacy_terrain_map[y][x].drain() {
+                match TerrainKind::from_legacy_color(color) {
+                    Some(kind) => {
+                        world.terrain_map[y][x].insert(pos, kind);
+                    }
+                    None => dropped += 1,
+                }
+            }
+            for (pos, color) in world.legacy_atmosphere_map[y][x].drain() {
+                match TileKind::from_legacy_color(color) {
+                    Some(kind) => {
+                        world.atmosphere_map[y][x].insert(pos, kind);
+                    }
+                    None => dropped += 1,
+                }
+            }
+        }
+    }
+
+    for row in world.enemies_map.iter_mut() {
+        for enemies in row.iter_mut() {
+            for enemy in enemies.iter_mut() {
+                enemy.kind = EnemyKind::from_legacy_color(enemy.legacy_color).unwrap_or_else(|| {
+                    dropped += 1;
+                    EnemyKind::Chaser
+                });
+            }
+        }
+    }
+    for projectile in world.projectiles.iter_mut() {
+        projectile.kind =
+            ProjectileKind::from_legacy_color(projectile.legacy_color).unwrap_or_else(|| {
+                dropped += 1;
+                ProjectileKind::Player
+            });
+    }
+
+    for boss in world.bosses.iter_mut() {
+        // the major boss is the only one that knows what it is without its color
+        boss.kind = match LEGACY_BOSS_COLORS.iter().find(|(_, color)| *color == boss.legacy_color) {
+            Some((kind, _)) => *kind,
+            None if boss.is_major => BossKind::Major,
+            None => {
+                dropped += 1;
+                BossKind::Laser
+            }
+        };
+        let (rushing, direction, color, length) = boss.legacy_rush_info;
+        let stage = legacy_stage(&LEGACY_LASER_STAGES, color).unwrap_or_default();
+        boss.rush_info = (rushing, direction, stage, length);
+    }
+
+    for (pos, color, duration) in world.legacy_boss_lasers.drain(..) {
+        match legacy_stage(&LEGACY_LASER_STAGES, color) {
+            Some(stage) => world.boss_lasers.push((pos, stage, duration)),
+            None => dropped += 1,
+        }
+    }
+    for (pos, color, duration) in world.legacy_boss_asteroids.drain(..) {
+        match legacy_stage(&LEGACY_ASTEROID_STAGES, color) {
+            Some(stage) => world.boss_asteroids.push((pos, stage, duration)),
+            None => dropped += 1,
+        }
+    }
+    for (pos, color, size, duration, in_well) in world.legacy_stun_wells.drain(..) {
+        match legacy_stage(&LEGACY_STUN_WELL_STAGES, color) {
+            Some(stage) => world.stun_wells.push((pos, stage, size, duration, in_well)),
+            None => dropped += 1,
+        }
+    }
+
+    if dropped > 0 {
+        eprintln!("{} tiles in the save had an unknown color and were dropped", dropped);
+    }
+}
+
+// Projectiles have a faction and shooters reload between shots. Every projectile in an older
+// save was the player's and no shooter had fired yet, which are both the defaults
+fn migrate_v3_to_v4(_save: &mut SaveFile) {}
+
+// Knights telegraph their lunges. Older saves never generated knights, so none of them has a
+// lunge coming
+fn migrate_v4_to_v5(_save: &mut SaveFile) {}
+
+// Enemies stopped saving their resistance, which is now looked up in the balance by their kind.
+// It was a resistance to the player's projectiles (0.5 for knights, 1.0 for everything else), so
+// an enemy whose resistance isn't what the balance gives its kind keeps it as its own. The rest go
+// by the balance, so changes to it reach them too
+fn migrate_v5_to_v6(save: &mut SaveFile) {
+    let balance = &save.world.balance.enemies;
+    for enemy in save.world.enemies_map.iter_mut().flatten().flatten() {
+        if enemy.legacy_resistance != balance.resistances(enemy.kind).projectile {
+            enemy.projectile_resistance = Some(enemy.legacy_resistance);
+        }
+    }
+}
+
+// The player's invisibility and stun counters became status effects, which enemies and bosses
+// have too. Enemies and bosses in older saves had no effects on them, which is the default
+fn migrate_v6_to_v7(save: &mut SaveFile) {
+    let player = &mut save.world.player;
+    if player.legacy_visible > 0 {
+        player.status.apply(StatusEffect::new(
+            StatusKind::Invisible,
+            player.legacy_visible as usize,
+        ));
+    }
+    if player.legacy_stun_timer > 0 {
+        player
+            .status
+            .apply(StatusEffect::new(StatusKind::Stun, player.legacy_stun_timer));
+    }
+}
+
+// Enemies save up initiative towards their next action. Every enemy in an older save acted once
+// a turn and had nothing saved up, which is the default
+fn migrate_v7_to_v8(_save: &mut SaveFile) {}
+
+// Enemies have an AI state and only chase the player once they have seen them. Before that every
+// enemy in the player's room went after them, so those carry on chasing from where the player is.
+// The rest start out idle without having seen anyone, which is the default
+fn migrate_v8_to_v9(save: &mut SaveFile) {
+    let world = &mut save.world;
+    let room = world.world_position;
+    for enemy in &mut world.enemies_map[room.y][room.x] {
+        enemy.ai = AiState::Chase;
+        enemy.last_seen = Some(world.player.pos);
+    }
+}
+
+// Elites wait in some of the rooms of new worlds. Older worlds were made without them, so every
+// enemy in an older save is a plain one without a shield, which is the default
+fn migrate_v9_to_v10(_save: &mut SaveFile) {}
+
+// The stage of a boss attack from the color it was drawn with, given the colors of every stage
+fn legacy_stage(stages: &[[f32; 4]], color: [f32; 4]) -> Option<AttackStage> {
+    let index = stages.iter().position(|stage| *stage == color)?;
+    if index == stages.len() - 1 {
+        Some(AttackStage::Active)
+    } else {
+        Some(AttackStage::Warning(index as u8))
+    }
+}
+
+// Everything needed to pick a game back up, written as one RON file so that the world and the
+// rng can never come from two different saves
+#[derive(serde::Deserialize, serde::Serialize)]
+pub struct SaveFile {
+    pub version: u32,
+    pub world: World,
+    pub rng: RngState,
+    // Turns played since the world was generated
+    #[serde(default)]
+    pub turn: usize,
+    // Seed the world was generated from, None if it isn't known
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+// Only the version of a save, read before the whole file so a save from a newer build is reported
+// as such instead of as a parse error somewhere in the world
+#[derive(serde::Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+// The state of a ChaCha8Rng. Its own serde impl stores the word position as a u128, which RON
+// can't represent, so it is split in two here
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct RngState {
+    seed: [u8; 32],
+    stream: u64,
+    word_pos_high: u64,
+    word_pos_low: u64,
+}
+
+impl RngState {
+    pub fn from(rng: &ChaCha8Rng) -> Self {
+        let word_pos = rng.get_word_pos();
+        RngState {
+            seed: rng.get_seed(),
+            stream: rng.get_stream(),
+            word_pos_high: (word_pos >> 64) as u64,
+            word_pos_low: word_pos as u64,
+        }
+    }
+
+    pub fn restore(&self) -> ChaCha8Rng {
+        let mut rng = ChaCha8Rng::from_seed(self.seed);
+        rng.set_stream(self.stream);
+        rng.set_word_pos(((self.word_pos_high as u128) << 64) | self.word_pos_low as u128);
+        rng
+    }
+}
+
+// Everything that can go wrong reading or writing a save
+#[derive(Debug)]
+pub enum SaveError {
+    // There is no save in the directory
+    NoSave,
+    Io(io::Error),
+    // The save exists but couldn't be parsed
+    Corrupt(String),
+    // The save was written by a newer version of the game
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::NoSave => write!(f, "there is no save yet"),
+            SaveError::Io(e) => write!(f, "{}", e),
+            SaveError::Corrupt(e) => write!(f, "the save is corrupted ({})", e),
+            SaveError::UnsupportedVersion(version) => write!(
+                f,
+                "the save is from a newer version of the game (save version {}, this game reads up to {})",
+                version, SAVE_VERSION
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(e: io::Error) -> Self {
+        SaveError::Io(e)
+    }
+}
+
+impl SaveFile {
+    // Writes the save into the directory, creating it if needed. The world is borrowed rather
+    // than owned since the game keeps running after a save
+    pub fn write(game: &Game, save_dir: &Path) -> Result<(), SaveError> {
+        #[derive(serde::Serialize)]
+        struct SaveFileRef<'a> {
+            version: u32,
+            world: &'a World,
+            rng: RngState,
+            turn: usize,
+            seed: Option<u64>,
+        }
+
+        let save = SaveFileRef {
+            version: SAVE_VERSION,
+            world: &game.world,
+            rng: RngState::from(&game.rng),
+            turn: game.turn,
+            seed: game.seed,
+        };
+        let serialized = ron::to_string(&save).map_err(serialize_error)?;
+        fs::create_dir_all(save_dir)?;
+        write_atomic(&save_dir.join(SAVE_FILE), serialized.as_bytes())?;
+        Ok(())
+    }
+
+    // Reads the save in the directory and upgrades it to the current version
+    pub fn read(save_dir: &Path) -> Result<SaveFile, SaveError> {
+        let mut save = Self::read_unmigrated(save_dir)?;
+        save.migrate();
+        Ok(save)
+    }
+
+    // Reads the save in the directory as it is on disk, keeping the version it was written with
+    pub fn read_unmigrated(save_dir: &Path) -> Result<SaveFile, SaveError> {
+        let path = save_dir.join(SAVE_FILE);
+        if !path.exists() {
+            return read_legacy(save_dir);
+        }
+        let save_str = fs::read_to_string(&path)?;
+
+        let header: SaveHeader =
+            ron::from_str(&save_str).map_err(|e| SaveError::Corrupt(e.to_string()))?;
+        if header.version > SAVE_VERSION {
+            return Err(SaveError::UnsupportedVersion(header.version));
+        }
+
+        ron::from_str(&save_str).map_err(|e| SaveError::Corrupt(e.to_string()))
+    }
+
+    // Runs every migration from the version of this save up to the current one
+    pub fn migrate(&mut self) {
+        while self.version < SAVE_VERSION {
+            MIGRATIONS[self.version as usize](self);
+            self.version += 1;
+        }
+    }
+}
+
+// Serializing only fails on a bug, which should fail the save rather than take the game down with
+// it
+pub fn serialize_error(e: ron::Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+// Writes to a temporary file next to the target and renames it over the target, so a crash or a
+// full disk halfway through leaves the previous file intact instead of half a file
+pub fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = Path::new(&tmp_path);
+
+    let mut file = fs::File::create(tmp_path)?;
+    file.write_all(bytes)?;
+    file.sync_all()?;
+    fs::rename(tmp_path, path)
+}
+
+// Saves from before the save file existed were three separate files:
+//      is_serialized:
+//          Contains either "0" or "1", where one is that there is a game serialized
+//          while zero means there is none
+//      world:
+//          Contains the actual world object, written to in RON
+//      rng:
+//          Contains the rng object, in JSON
+fn read_legacy(save_dir: &Path) -> Result<SaveFile, SaveError> {
+    let is_serialized = match fs::read_to_string(save_dir.join("is_serialized")) {
+        Ok(is_serialized) => is_serialized,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Err(SaveError::NoSave),
+        Err(e) => return Err(SaveError::Io(e)),
+    };
+    match is_serialized.trim() {
+        "0" => Err(SaveError::NoSave),
+        "1" => {
+            let world_str = fs::read_to_string(save_dir.join("world"))?;
+            let world: World =
+                ron::from_str(&world_str).map_err(|e| SaveError::Corrupt(e.to_string()))?;
+            let rng_str = fs::read_to_string(save_dir.join("rng"))?;
+            let rng: ChaCha8Rng =
+                serde_json::from_str(&rng_str).map_err(|e| SaveError::Corrupt(e.to_string()))?;
+            Ok(SaveFile {
+                version: 0,
+                world,
+                rng: RngState::from(&rng),
+                turn: 0,
+                seed: None,
+            })
+        }
+        other => Err(SaveError::Corrupt(format!(
+            "is_serialized should be 0 or 1, not {:?}",
+            other
+        ))),
+    }
+}
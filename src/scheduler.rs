@@ -0,0 +1,178 @@
+use crate::{
+    action::Action, enemy::Enemy, offscreen, pathfinding::Navigation, player::Player,
+    projectile::Projectile, utils::Boss, world::World, world::BOSS_ROOMS,
+};
+
+use rand_chacha::ChaCha8Rng;
+
+/*
+ * One turn is one action of the player, and everything else in the world is timed against it:
+ *      - The player acts first. An action that doesn't take a turn (an ability on cooldown, not
+ *        enough energy...) stops the turn right there and nothing else moves
+ *      - Then the boss of the room, if the player is in a boss room
+ *      - Then the projectiles, each moving its speed in tiles
+ *      - Then the enemies of the room, from the last one in the room to the first. Each gains
+ *        its initiative for the turn and acts once for every ACTION_COST it has saved up, so an
+ *        enemy can be faster or slower than the player by any fraction
+ *      - Last the enemies in the rooms around the player, which only move coarsely (see
+ *        offscreen)
+ */
+
+// Initiative an action costs. The player acts exactly once a turn, so an enemy gaining
+// ACTION_COST a turn keeps pace with the player, twice that acts twice a turn and half of it
+// every other turn
+pub const ACTION_COST: usize = 100;
+
+// Everything that acts during a turn, in the order they act in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Player,
+    Bosses,
+    Projectiles,
+    Enemies,
+    Offscreen,
+}
+
+pub const TURN_ORDER: [Phase; 5] = [
+    Phase::Player,
+    Phase::Bosses,
+    Phase::Projectiles,
+    Phase::Enemies,
+    Phase::Offscreen,
+];
+
+// Plays one turn of the action. Returns if the turn was played, which it isn't when the player's
+// action doesn't take a turn
+pub fn play_turn(
+    action: Action,
+    world: &mut World,
+    navigation: &mut Navigation,
+    rng: &mut ChaCha8Rng,
+) -> bool {
+    for phase in TURN_ORDER {
+        match phase {
+            Phase::Player => {
+                if !Player::use_input(action, world) {
+                    return false;
+                }
+            }
+            Phase::Bosses => {
+                if BOSS_ROOMS.contains(&world.world_position) {
+                    Boss::update(world, rng);
+                }
+            }
+            Phase::Projectiles => Projectile::update(world, &mut navigation.pathfinder),
+            Phase::Enemies => Enemy::update(world, navigation, rng),
+            Phase::Offscreen => offscreen::simulate(world, rng),
+        }
+    }
+    true
+}
+
+// Adds a turn's worth of initiative to what was saved up, and spends it on as many actions as it
+// pays for. Returns how many actions that is, the rest is kept for the next turn
+pub fn actions_this_turn(initiative: &mut usize, gained: usize) -> usize {
+    *initiative += gained;
+    let actions = *initiative / ACTION_COST;
+    *initiative %= ACTION_COST;
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ai::AiState, balance::EnemyBalance, tile::TerrainKind, utils::Position};
+    use rand::SeedableRng;
+
+    // The actions an enemy gaining this much initiative a turn gets over the turns
+    fn actions_over(turns: usize, gained: usize) -> Vec<usize> {
+        let mut initiative = 0;
+        (0..turns).map(|_| actions_this_turn(&mut initiative, gained)).collect()
+    }
+
+    // An enemy made by the constructor, already after the player
+    fn chasing(
+        make: fn(usize, usize, Position, &EnemyBalance) -> Enemy,
+        world: &World,
+        pos: Position,
+    ) -> Enemy {
+        let mut enemy = make(pos.x, pos.y, world.world_position, &world.balance.enemies);
+        enemy.ai = AiState::Chase;
+        enemy.last_seen = Some(world.player.pos);
+        enemy
+    }
+
+    // Plays the enemies' part of a turn
+    fn turn(world: &mut World) {
+        Enemy::update(world, &mut Navigation::default(), &mut ChaCha8Rng::seed_from_u64(0));
+    }
+
+    fn distance(a: Position, b: Position) -> usize {
+        a.x.abs_diff(b.x) + a.y.abs_diff(b.y)
+    }
+
+    #[test]
+    fn initiative_carries_over() {
+        assert_eq!(actions_over(4, ACTION_COST), [1, 1, 1, 1]);
+        assert_eq!(actions_over(4, 2 * ACTION_COST), [2, 2, 2, 2]);
+        assert_eq!(actions_over(4, ACTION_COST / 2), [0, 1, 0, 1]);
+        assert_eq!(actions_over(4, ACTION_COST * 3 / 2), [1, 2, 1, 2]);
+    }
+
+    #[test]
+    fn bombers_act_twice_a_turn() {
+        let mut world = World::empty_room(Position::new(5, 25));
+        let start = Position::new(30, 25);
+        let bomber = chasing(Enemy::bomber, &world, start);
+        assert_eq!(bomber.initiative_gain(&world), 2 * ACTION_COST);
+        world.place_enemy(bomber);
+        world.place_enemy(chasing(Enemy::chasing, &world, Position::new(30, 35)));
+
+        turn(&mut world);
+        let enemies = &world.enemies_map[0][0];
+        assert_eq!(distance(enemies[0].pos[0], start), 2);
+        assert_eq!(distance(enemies[1].pos[0], Position::new(30, 35)), 1);
+    }
+
+    #[test]
+    fn water_halves_actions() {
+        let mut world = World::empty_room(Position::new(5, 25));
+        let pos = Position::new(30, 25);
+        world.terrain_map[0][0].insert(pos, TerrainKind::Water);
+        let chaser = chasing(Enemy::chasing, &world, pos);
+        assert_eq!(world.balance.world.water_cost, 2);
+        assert_eq!(chaser.initiative_gain(&world), ACTION_COST / 2);
+        let bomber = chasing(Enemy::bomber, &world, pos);
+        assert_eq!(bomber.initiative_gain(&world), ACTION_COST);
+
+        // it has nothing saved up, so it spends its first turn in the water getting ready
+        world.place_enemy(chaser);
+        turn(&mut world);
+        assert_eq!(world.enemies_map[0][0][0].pos[0], pos);
+        turn(&mut world);
+        assert_eq!(distance(world.enemies_map[0][0][0].pos[0], pos), 1);
+    }
+
+    // Two chasers just as fast both want the only tile next to the player, the player being in
+    // the corner with a wall below them
+    fn contested_tile() -> World {
+        let mut world = World::empty_room(Position::new(0, 0));
+        world.terrain_map[0][0].insert(Position::new(0, 1), TerrainKind::Wall);
+        world.place_enemy(chasing(Enemy::chasing, &world, Position::new(2, 0)));
+        world.place_enemy(chasing(Enemy::chasing, &world, Position::new(1, 1)));
+        turn(&mut world);
+        world
+    }
+
+    #[test]
+    fn ties_resolve_the_same_way_every_time() {
+        let world = contested_tile();
+        let positions: Vec<_> = world.enemies_map[0][0].iter().map(|enemy| enemy.pos[0]).collect();
+        // between two enemies just as close to it the tile goes to the one that is first in the
+        // room, even though the second one acts first, and the second one waits
+        assert_eq!(positions, [Position::new(1, 0), Position::new(1, 1)]);
+        for _ in 0..10 {
+            assert_eq!(contested_tile().enemies_map, world.enemies_map);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+use crate::{
+    balance::Balance,
+    game::{self, Game, TurnEvents},
+    pathfinding::Navigation,
+    save::{self, SaveError, SaveFile},
+    utils::Position,
+};
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// How many save slots there are to pick from on the title screen
+pub const SLOT_COUNT: usize = 5;
+// Name of the metadata inside a slot's directory
+const META_FILE: &'static str = "meta.ron";
+// Files that make up a save, moved around together when a save is put into a slot
+const SAVE_FILES: [&'static str; 7] = [
+    "save.ron",
+    game::REPLAY_FILE,
+    game::DEATH_REPLAY_FILE,
+    "meta.ron",
+    "is_serialized",
+    "world",
+    "rng",
+];
+
+// A summary of the save in a slot, stored next to it so the title screen can list every slot
+// without parsing whole worlds
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+pub struct SlotMeta {
+    pub seed: Option<u64>,
+    pub turn: usize,
+    pub health: usize,
+    pub energy: usize,
+    pub world_position: Position,
+    pub bosses_defeated: usize,
+    // Seconds since the unix epoch when the slot was saved
+    pub timestamp: u64,
+}
+
+impl SlotMeta {
+    pub fn from(game: &Game) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+        SlotMeta {
+            seed: game.seed,
+            turn: game.turn,
+            health: game.world.player.health(),
+            energy: game.world.player.energy(),
+            world_position: game.world.world_position,
+            bosses_defeated: game.bosses_defeated(),
+            timestamp,
+        }
+    }
+
+    // One line for the slot picker, e.g. "Room (3, 1)  Turn 120  HP 80  EN 40  Bosses 1  Seed 0  2 h ago"
+    pub fn describe(&self) -> String {
+        let seed = match self.seed {
+            Some(seed) => seed.to_string(),
+            None => "?".to_string(),
+        };
+        format!(
+            "Room ({}, {})  Turn {}  HP {}  EN {}  Bosses {}  Seed {}  {}",
+            self.world_position.x,
+            self.world_position.y,
+            self.turn,
+            self.health,
+            self.energy,
+            self.bosses_defeated,
+            seed,
+            describe_age(self.timestamp)
+        )
+    }
+}
+
+fn describe_age(timestamp: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(timestamp);
+    let seconds = now.saturating_sub(timestamp);
+    if seconds < 60 {
+        "just now".to_string()
+    } else if seconds < 60 * 60 {
+        format!("{} min ago", seconds / 60)
+    } else if seconds < 60 * 60 * 24 {
+        format!("{} h ago", seconds / (60 * 60))
+    } else {
+        format!("{} days ago", seconds / (60 * 60 * 24))
+    }
+}
+
+// Slots are numbered from 0 in code and from 1 on screen
+pub fn slot_dir(save_dir: &Path, slot: usize) -> PathBuf {
+    save_dir.join(format!("slot_{}", slot + 1))
+}
+
+// Saves the game into the slot along with its metadata
+pub fn save(game: &mut Game, save_dir: &Path, slot: usize) -> Result<(), SaveError> {
+    save_in(game, &slot_dir(save_dir, slot))
+}
+
+// Saves the game and its metadata into any directory, the slot's own or one of its autosaves.
+// Like Game::save, a dead player only gets the replay written, so the metadata keeps describing
+// the last save that can be continued
+pub fn save_in(game: &mut Game, dir: &Path) -> Result<(), SaveError> {
+    game.save(dir)?;
+    if game.world.player.is_alive() {
+        write_meta(dir, &SlotMeta::from(game))?;
+    }
+    Ok(())
+}
+
+pub fn load(save_dir: &Path, slot: usize, balance: &Balance) -> Result<Game, SaveError> {
+    Game::load(&slot_dir(save_dir, slot), balance)
+}
+
+fn write_meta(dir: &Path, meta: &SlotMeta) -> io::Result<()> {
+    let serialized = ron::to_string(meta).map_err(save::serialize_error)?;
+    save::write_atomic(&dir.join(META_FILE), serialized.as_bytes())
+}
+
+// The metadata of a slot, None if the slot is empty
+pub fn read_meta(save_dir: &Path, slot: usize) -> Option<SlotMeta> {
+    read_meta_in(&slot_dir(save_dir, slot))
+}
+
+// The metadata of the save in a directory. A save without metadata (e.g. one that was moved into
+// a slot from an older version) has it rebuilt from the save itself
+pub fn read_meta_in(dir: &Path) -> Option<SlotMeta> {
+    if let Ok(meta_str) = fs::read_to_string(dir.join(META_FILE)) {
+        if let Ok(meta) = ron::from_str(&meta_str) {
+            return Some(meta);
+        }
+    }
+
+    let save = SaveFile::read(dir).ok()?;
+    let timestamp = fs::metadata(dir)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let game = Game {
+        world: save.world,
+        rng: save.rng.restore(),
+        replay: None,
+        turn: save.turn,
+        seed: save.seed,
+        events: TurnEvents::default(),
+        navigation: Navigation::default(),
+    };
+    let meta = SlotMeta {
+        timestamp,
+        ..SlotMeta::from(&game)
+    };
+    write_meta(dir, &meta).ok();
+    Some(meta)
+}
+
+// The metadata of every slot, in order
+pub fn list(save_dir: &Path) -> Vec<Option<SlotMeta>> {
+    (0..SLOT_COUNT).map(|slot| read_meta(save_dir, slot)).collect()
+}
+
+// The slot that was saved last, used by --continue
+pub fn most_recent(save_dir: &Path) -> Option<usize> {
+    list(save_dir)
+        .iter()
+        .enumerate()
+        .filter_map(|(slot, meta)| meta.as_ref().map(|meta| (slot, meta.timestamp)))
+        .max_by_key(|(_, timestamp)| *timestamp)
+        .map(|(slot, _)| slot)
+}
+
+pub fn delete(save_dir: &Path, slot: usize) -> io::Result<()> {
+    let dir = slot_dir(save_dir, slot);
+    if dir.exists() {
+        fs::remove_dir_all(dir)?;
+    }
+    Ok(())
+}
+
+// Copies a slot over another one, replacing whatever was in it
+pub fn copy(save_dir: &Path, from: usize, to: usize) -> io::Result<()> {
+    let from_dir = slot_dir(save_dir, from);
+    let to_dir = slot_dir(save_dir, to);
+    delete(save_dir, to)?;
+    fs::create_dir_all(&to_dir)?;
+    for file in SAVE_FILES {
+        let from_file = from_dir.join(file);
+        if from_file.exists() {
+            fs::copy(from_file, to_dir.join(file))?;
+        }
+    }
+    Ok(())
+}
+
+// Before there were slots the save sat directly in the save directory. If it is still there, it
+// is moved into the first slot (unless that one is taken) so it shows up in the picker
+pub fn adopt_unslotted_save(save_dir: &Path) -> io::Result<()> {
+    let first_slot = slot_dir(save_dir, 0);
+    let has_save = SAVE_FILES.iter().any(|file| save_dir.join(file).exists());
+    if !has_save || first_slot.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(&first_slot)?;
+    for file in SAVE_FILES {
+        let path = save_dir.join(file);
+        if path.exists() {
+            fs::rename(path, first_slot.join(file))?;
+        }
+    }
+    Ok(())
+}
@@ -1,6 +1,4 @@
 use crate::direction::Direction;
-use crate::enemy::Enemy;
-use crate::player::Player;
 use crate::utils::Boss;
 use crate::utils::Position;
 use crate::UNIVERSAL_OFFSET;
@@ -10,13 +8,23 @@ use rand_chacha::ChaChaRng;
 use serde::{Deserialize, Serialize};
 use std::fs::{self, OpenOptions};
 use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use crate::{
+    action::Action,
+    balance::Balance,
     entity::Entity,
+    game::{Game, TurnOutcome},
+    autosave::{self, AutosaveConfig, Autosaver},
+    input,
+    keymap::{Binding, Command, Keymap},
     projectile::Projectile,
-    tile,
+    save::SaveError,
+    slot::{self, SlotMeta, SLOT_COUNT},
+    theme::{self, Settings, Theme},
+    tile::{self, Palette},
     world::{World, BOSS_ROOMS, FINAL_BOSS_ROOM},
-    BOARD_SIZE, SCREEN_SIZE, TILE_SIZE, WORLD_SIZE,
+    BOARD_SIZE, SCREEN_SIZE, THEMES_PATH, TILE_SIZE, WORLD_SIZE,
 };
 
 use rand::prelude::*;
@@ -31,75 +39,189 @@ use ggez::{
     Context, GameError, GameResult,
 };
 
-pub const RNG_SEED: u64 = 0;
+// Seed for purely cosmetic randomness while drawing, kept apart from the game rng so that how
+// often a frame is drawn never changes the outcome of a turn
+const RENDER_RNG_SEED: u64 = 0;
 // const MOVES_TILL_ENERGY_REGEN: usize = 5;
 
+// The controls screen, where any command can be bound to another key or mouse button
+struct ControlsScreen {
+    // Index into Command::ALL
+    selected: usize,
+    // Waiting for the key or mouse button to bind the selected command to
+    listening: bool,
+    // A binding already used by another command that was pressed once while listening. Pressing
+    // it again moves it over to the selected command
+    conflict: Option<Binding>,
+}
+
+// The settings screen, for now only the color theme
+struct SettingsScreen {
+    // Index into the themes, the world is drawn with it while it is selected
+    selected: usize,
+}
+
 // #[derive(serde::Deserialize, serde::Serialize)]
 pub struct State {
     should_draw: bool,
+    // Set after CommandPrefix, the next key is looked up among the chord commands
     command: bool,
     songs: [audio::Source; 8],
-    // Abstraction for the world and what is contained within it
-    world: Option<World>,
+    // The actual simulation, None while on the title screen
+    game: Option<Game>,
     title_screen: bool,
-    pub rng: Option<ChaCha8Rng>,
+    render_rng: ChaCha8Rng,
     player_curr_world_position: Position,
     death_font_size: f32,
+    // Seed a new game started from the title screen is generated with
+    seed: u64,
+    // Balance every game started or loaded from here is played with
+    balance: Balance,
+    // Directory the save slots are kept in
+    save_dir: PathBuf,
+    // Slot the game is saved to, or the slot selected on the title screen
+    slot: usize,
+    // What is in each slot, only filled in on the title screen
+    slots: Vec<Option<SlotMeta>>,
+    // Set after pressing X on the title screen, the next X deletes the slot
+    confirm_delete: bool,
+    // Set after pressing N on a slot that has a save, the next N starts over in it
+    confirm_new_game: bool,
+    // Set after pressing C on the title screen, the next C copies this slot into the selected one
+    copy_from: Option<usize>,
+    // Autosaves the slot being played, None on the title screen
+    autosaver: Option<Autosaver>,
+    // Shown at the bottom of the screen, e.g. when a save couldn't be written or loaded
+    message: Option<String>,
+    // Which keys and mouse buttons do what
+    keymap: Keymap,
+    // Drawn over everything else while open, from the title screen or in game
+    controls: Option<ControlsScreen>,
+    // Drawn over everything else while open, from the title screen or in game
+    settings_screen: Option<SettingsScreen>,
+    settings: Settings,
+    // Every theme that can be picked on the settings screen, the default one first
+    themes: Vec<Theme>,
+    // The colors the world is drawn with
+    palette: Palette,
 }
 
 impl State {
     // just returns the default values
-    pub fn new(ctx: &mut Context, title_screen: bool) -> GameResult<State> {
-        let songs = [
-            audio::Source::new(ctx, "/overworld.ogg")?,
-            audio::Source::new(ctx, "/final_boss.ogg")?,
-            audio::Source::new(ctx, "/blackout_boss.ogg")?,
-            audio::Source::new(ctx, "/column_laser_boss.ogg")?,
-            audio::Source::new(ctx, "/chasing_boss.ogg")?,
-            audio::Source::new(ctx, "/laser_boss.ogg")?,
-            audio::Source::new(ctx, "/title_music.ogg")?,
-            audio::Source::new(ctx, "/Sad_Violin_-_Sound_Effect_(HD).ogg")?
-        ];
-        let mut rng = ChaCha8Rng::seed_from_u64(RNG_SEED);
-        let temp = State {
-            should_draw: true,
-            command: false,
-            songs,
-            world: Some(World::new(&mut rng)),
-            title_screen,
-            rng: Some(rng),
-            player_curr_world_position: Position::new(0, 0),
-            death_font_size: 0.
-        };
-        Ok(temp)
+    pub fn new(
+        ctx: &mut Context,
+        title_screen: bool,
+        seed: u64,
+        balance: Balance,
+        save_dir: PathBuf,
+        slot: usize,
+    ) -> GameResult<State> {
+        // the autosaves of whatever was played in the slot before have nothing to do with this game
+        let cleared = autosave::clear(&save_dir, slot);
+        let game = Game::new(seed, &balance);
+        Self::from(game, ctx, seed, balance, save_dir, slot).map(|mut state| {
+            state.title_screen = title_screen;
+            if let Err(e) = cleared {
+                state.show_message(format!("Couldn't delete the old autosaves: {}", e));
+            }
+            state
+        })
     }
 
-    pub fn title_screen(ctx: &mut Context) -> GameResult<State> {
+    pub fn title_screen(
+        ctx: &mut Context,
+        seed: u64,
+        balance: Balance,
+        save_dir: PathBuf,
+        slot: usize,
+    ) -> GameResult<State> {
         // TODO make this the title screen music
-        let songs = [
-            audio::Source::new(ctx, "/overworld.ogg")?,
-            audio::Source::new(ctx, "/final_boss.ogg")?,
-            audio::Source::new(ctx, "/blackout_boss.ogg")?,
-            audio::Source::new(ctx, "/column_laser_boss.ogg")?,
-            audio::Source::new(ctx, "/chasing_boss.ogg")?,
-            audio::Source::new(ctx, "/laser_boss.ogg")?,
-            audio::Source::new(ctx, "/title_music.ogg")?,
-            audio::Source::new(ctx, "/Sad_Violin_-_Sound_Effect_(HD).ogg")?
-        ];
+        let slots = slot::list(&save_dir);
+        let keymap = Keymap::load(&save_dir);
+        let (settings, themes, palette) = Self::load_theme(&save_dir);
         Ok(State {
             should_draw: true,
             command: false,
-            songs,
-            world: None,
+            songs: Self::load_songs(ctx)?,
+            game: None,
             title_screen: true,
-            rng: None,
+            render_rng: ChaCha8Rng::seed_from_u64(RENDER_RNG_SEED),
             player_curr_world_position: Position::new(0, 0),
-            death_font_size: 0.
+            death_font_size: 0.,
+            seed,
+            balance,
+            save_dir,
+            slot,
+            slots,
+            confirm_delete: false,
+            confirm_new_game: false,
+            copy_from: None,
+            autosaver: None,
+            message: None,
+            keymap,
+            controls: None,
+            settings_screen: None,
+            settings,
+            themes,
+            palette,
         })
     }
 
-    pub fn from(world: World, ctx: &mut Context, rng: ChaCha8Rng) -> GameResult<State> {
-        let songs = [
+    pub fn from(
+        game: Game,
+        ctx: &mut Context,
+        seed: u64,
+        balance: Balance,
+        save_dir: PathBuf,
+        slot: usize,
+    ) -> GameResult<State> {
+        let autosaver = Autosaver::new(AutosaveConfig::load(&save_dir), &save_dir, slot);
+        let keymap = Keymap::load(&save_dir);
+        let (settings, themes, palette) = Self::load_theme(&save_dir);
+        let temp = State {
+            should_draw: true,
+            command: false,
+            songs: Self::load_songs(ctx)?,
+            game: Some(game),
+            title_screen: false,
+            render_rng: ChaCha8Rng::seed_from_u64(RENDER_RNG_SEED),
+            player_curr_world_position: Position::new(0, 0),
+            death_font_size: 0.,
+            seed,
+            balance,
+            save_dir,
+            slot,
+            slots: Vec::new(),
+            confirm_delete: false,
+            confirm_new_game: false,
+            copy_from: None,
+            autosaver: Some(autosaver),
+            message: None,
+            keymap,
+            controls: None,
+            settings_screen: None,
+            settings,
+            themes,
+            palette,
+        };
+        Ok(temp)
+    }
+
+    // The settings and every theme, along with the palette of the chosen theme. A theme that is
+    // no longer there falls back to the default one
+    fn load_theme(save_dir: &Path) -> (Settings, Vec<Theme>, Palette) {
+        let settings = Settings::load(save_dir);
+        let themes = theme::list(&PathBuf::from(THEMES_PATH));
+        let index = theme::find(&themes, &settings.theme);
+        if themes[index].name != settings.theme {
+            eprintln!("Couldn't find the theme {}, using the default one", settings.theme);
+        }
+        let palette = themes[index].palette.clone();
+        (settings, themes, palette)
+    }
+
+    fn load_songs(ctx: &mut Context) -> GameResult<[audio::Source; 8]> {
+        Ok([
             audio::Source::new(ctx, "/overworld.ogg")?,
             audio::Source::new(ctx, "/final_boss.ogg")?,
             audio::Source::new(ctx, "/blackout_boss.ogg")?,
@@ -108,25 +230,14 @@ impl State {
             audio::Source::new(ctx, "/laser_boss.ogg")?,
             audio::Source::new(ctx, "/title_music.ogg")?,
             audio::Source::new(ctx, "/Sad_Violin_-_Sound_Effect_(HD).ogg")?
-        ];
-        let temp = State {
-            should_draw: true,
-            command: false,
-            songs,
-            world: Some(world),
-            title_screen: false,
-            rng: Some(rng),
-            player_curr_world_position: Position::new(0, 0),
-            death_font_size: 0.
-        };
-        Ok(temp)
+        ])
     }
 }
 
 impl ggez::event::EventHandler<GameError> for State {
     fn update(&mut self, ctx: &mut Context) -> GameResult {
         if !self.title_screen {
-            if !self.world.as_ref().unwrap().player.is_alive() {
+            if !self.game.as_ref().unwrap().world.player.is_alive() {
                 if !self.songs[7].playing() {
                     for song in &mut self.songs {
                         song.stop(ctx);
@@ -136,7 +247,7 @@ impl ggez::event::EventHandler<GameError> for State {
                     self.songs[7].play(ctx);
                 }
             } else {
-                let world_pos = self.world.as_mut().unwrap().world_position;
+                let world_pos = self.game.as_ref().unwrap().world.world_position;
                 let boss_rooms = BOSS_ROOMS;
                 if world_pos == boss_rooms[0] {
                     if !self.songs[5].playing() {
@@ -205,7 +316,11 @@ impl ggez::event::EventHandler<GameError> for State {
     }
 
     fn draw(&mut self, ctx: &mut Context) -> GameResult {
-        if self.title_screen {
+        if self.controls.is_some() {
+            self.draw_controls(ctx)?;
+        } else if self.settings_screen.is_some() {
+            self.draw_settings(ctx)?;
+        } else if self.title_screen {
             let mut canvas =
                 graphics::Canvas::from_frame(ctx, graphics::Color::from(tile::TITLE_SCREEN_FLOOR));
             let pos = Position::new(8, 2);
@@ -222,19 +337,37 @@ impl ggez::event::EventHandler<GameError> for State {
                     &*(graphics::Text::new(duration_left).set_scale(32.)),//.set_scale(48.),
                     graphics::DrawParam::from(text_spot).color(graphics::Color::from_rgb(164, 194, 244)),
                 );
-            let pos = Position::new(13, 22);
+            for (index, meta) in self.slots.iter().enumerate() {
+                let pos = Position::new(1, 16 + 4 * index);
+                let text_spot = Vec2::new((pos.x as f32 + 0.25) * TILE_SIZE.0 as f32,  (pos.y as f32 + UNIVERSAL_OFFSET as f32) * TILE_SIZE.1 as f32);
+                let marker = if index == self.slot { ">" } else { " " };
+                let description = match meta {
+                    Some(meta) => meta.describe(),
+                    None => "Empty".to_string(),
+                };
+                let color = if index == self.slot {
+                    graphics::Color::from_rgb(180, 167, 214)
+                } else {
+                    graphics::Color::from_rgb(159, 197, 232)
+                };
+                canvas.draw(
+                    &*(graphics::Text::new(format!("{} Slot {}: {}", marker, index + 1, description)).set_scale(20.)),
+                    graphics::DrawParam::from(text_spot).color(color),
+                );
+            }
+            let pos = Position::new(1, 35);
             let text_spot = Vec2::new((pos.x as f32 + 0.25) * TILE_SIZE.0 as f32,  (pos.y as f32 + UNIVERSAL_OFFSET as f32) * TILE_SIZE.1 as f32);
-            let duration_left = " New Game [N]";
+            let duration_left = "[Up/Down] Slot  [N] New  [L] Load  [A] Autosave  [C] Copy  [X] Delete";
                 canvas.draw(
-                    &*(graphics::Text::new(duration_left).set_scale(52.)),//.set_scale(48.),
-                    graphics::DrawParam::from(text_spot).color(graphics::Color::from_rgb(180, 167, 214)),
+                    &*(graphics::Text::new(duration_left).set_scale(20.)),
+                    graphics::DrawParam::from(text_spot).color(graphics::Color::from_rgb(255, 255, 255)),
                 );
-            let pos = Position::new(12, 28);
+            let pos = Position::new(1, 37);
             let text_spot = Vec2::new((pos.x as f32 + 0.25) * TILE_SIZE.0 as f32,  (pos.y as f32 + UNIVERSAL_OFFSET as f32) * TILE_SIZE.1 as f32);
-            let duration_left = " Load Save [L]";
+            let duration_left = "[K] Controls  [S] Settings";
                 canvas.draw(
-                    &*(graphics::Text::new(duration_left).set_scale(52.)),//.set_scale(48.),
-                    graphics::DrawParam::from(text_spot).color(graphics::Color::from_rgb(159, 197, 232)),
+                    &*(graphics::Text::new(duration_left).set_scale(20.)),
+                    graphics::DrawParam::from(text_spot).color(graphics::Color::from_rgb(255, 255, 255)),
                 );
             let pos = Position::new(12, 42);
             let text_spot = Vec2::new((pos.x as f32 + 0.25) * TILE_SIZE.0 as f32,  (pos.y as f32 + UNIVERSAL_OFFSET as f32) * TILE_SIZE.1 as f32);
@@ -243,8 +376,9 @@ impl ggez::event::EventHandler<GameError> for State {
                     &*(graphics::Text::new(duration_left).set_scale(26.)),//.set_scale(48.),
                     graphics::DrawParam::from(text_spot).color(graphics::Color::from_rgb(255, 255, 255)),
                 );
+            self.draw_message(&mut canvas, Position::new(1, 39));
             canvas.finish(ctx)?;
-        } else if !self.world.as_mut().unwrap().player.is_alive() {
+        } else if !self.game.as_ref().unwrap().world.player.is_alive() {
             let mut canvas = graphics::Canvas::from_frame(ctx, graphics::Color::from(tile::BLACK));
             let pos = Position::new(7, 15);
             let text_spot = Vec2::new((pos.x as f32 + 0.25) * TILE_SIZE.0 as f32,  (pos.y as f32 + UNIVERSAL_OFFSET as f32) * TILE_SIZE.1 as f32);
@@ -259,8 +393,8 @@ impl ggez::event::EventHandler<GameError> for State {
             canvas.finish(ctx)?;
         } else {
             if self.should_draw {
-                let world = self.world.as_mut().unwrap();
-                let rng = self.rng.as_mut().unwrap();
+                let world = &mut self.game.as_mut().unwrap().world;
+                let rng = &mut self.render_rng;
                 let mut boss_room = false;
                 let mut final_boss = false;
                 for boss_room_position in BOSS_ROOMS {
@@ -272,11 +406,11 @@ impl ggez::event::EventHandler<GameError> for State {
                     final_boss = true;
                 }
                 let mut canvas = if final_boss {
-                    graphics::Canvas::from_frame(ctx, graphics::Color::from(tile::BOSS_FLOOR))
+                    graphics::Canvas::from_frame(ctx, graphics::Color::from(self.palette.boss_floor))
                 } else if boss_room {
-                    graphics::Canvas::from_frame(ctx, graphics::Color::from(tile::FLOOR))
+                    graphics::Canvas::from_frame(ctx, graphics::Color::from(self.palette.floor))
                 } else {
-                    graphics::Canvas::from_frame(ctx, graphics::Color::from(tile::GRASS))
+                    graphics::Canvas::from_frame(ctx, graphics::Color::from(self.palette.grass))
                     // for x in 0..WORLD_SIZE.0 {
                     //     for y in 0..WORLD_SIZE.1 {
                     //         let mut rng = rand::thread_rng();
@@ -300,7 +434,7 @@ impl ggez::event::EventHandler<GameError> for State {
                     //     }
                     // }
                 };
-                world.draw(&mut canvas, rng);
+                world.draw(&mut canvas, rng, &self.palette);
 
                 //For Text
                 // let level_dest = bevy::math::Vec2::new(10.0, 10.0);
@@ -318,6 +452,7 @@ impl ggez::event::EventHandler<GameError> for State {
                 //     &graphics::Text::new(score_str),
                 //     graphics::DrawParam::from(score_dest).color(tile::PORTAL),
                 // );
+                self.draw_message(&mut canvas, Position::new(0, WORLD_SIZE.1 as usize - 2));
                 canvas.finish(ctx)?;
                 self.should_draw = false;
             }
@@ -332,42 +467,19 @@ impl ggez::event::EventHandler<GameError> for State {
         // _repeated: bool,
     ) -> Result<(), GameError> {
         // Just takes in the user input and makes an action based off of it
-        if self.title_screen {
-            if let Some(key) = input.keycode {
-                if key == KeyCode::N {
-                    // new game
-                    *self = Self::new(ctx, false)?;
-                } else if key == KeyCode::L {
-                    // load game
-                    *self = Self::load_save(ctx).unwrap();
-                }
-            }
-        } else {
-            if let Some(key) = input.keycode {
-                if key == KeyCode::Colon {
-                    self.command = true;
-                } else if self.command && key == KeyCode::W {
-                    self.save_state();
-                } else if self.command && key == KeyCode::Q {
-                    self.save_state();
-                    std::process::exit(0);
+        if let Some(key) = input.keycode {
+            if self.controls.is_some() {
+                self.controls_input(Binding::Key(key));
+            } else if self.settings_screen.is_some() {
+                self.settings_input(key);
+            } else if self.title_screen {
+                self.title_screen_key(ctx, key)?;
+            } else {
+                // a message stays up until the next key press
+                if self.message.take().is_some() {
+                    self.should_draw = true;
                 }
-            }
-
-            let world = self.world.as_mut().unwrap();
-
-            if Player::use_input(input, world, self.rng.as_mut().unwrap()) {
-                // self.player_move_count += 1;
-                // if self.player_move_count >= MOVES_TILL_ENERGY_REGEN {
-                //     self.world.player.change_energy(1);
-                //     self.player_move_count = 0;
-                // }
-                Projectile::update(world);
-
-                // updates all the enemies in the world, for now only removes them once their health is
-                // less than or equal to 0
-                Enemy::update(world);
-                self.should_draw = true;
+                self.game_input(ctx, Binding::Key(key));
             }
         }
         Ok(())
@@ -375,68 +487,507 @@ impl ggez::event::EventHandler<GameError> for State {
 
     fn mouse_button_down_event(
         &mut self,
-        _ctx: &mut Context,
-        _button: event::MouseButton,
+        ctx: &mut Context,
+        button: event::MouseButton,
         _x: f32,
         _y: f32,
     ) -> Result<(), GameError> {
-        if !self.title_screen && (_y / TILE_SIZE.1 as f32) as usize >= UNIVERSAL_OFFSET as usize {
-            self.world.as_mut().unwrap().player.queued_position = Some(Position::new(
-                (_x / TILE_SIZE.0 as f32) as usize,
-                (_y / TILE_SIZE.1 as f32) as usize - UNIVERSAL_OFFSET as usize,
-            ));
+        if self.controls.is_some() {
+            self.controls_input(Binding::Mouse(button));
+        } else if self.settings_screen.is_none() && !self.title_screen {
+            self.game_input(ctx, Binding::Mouse(button));
         }
         Ok(())
     }
 }
 
 impl State {
-    fn save_state(&self) {
-        if self.world.as_ref().unwrap().player.is_alive() {
-            let serialized_world = ron::to_string(self.world.as_ref().unwrap()).unwrap();
-            fs::write("./serialization/world", serialized_world.as_bytes());
-            let serialized_rng = serde_json::to_string(self.rng.as_ref().unwrap()).unwrap();
-            fs::write("./serialization/rng", serialized_rng.as_bytes());
-            fs::write("./serialization/is_serialized", b"1");
+    // Runs whatever the key or mouse button is bound to in game
+    fn game_input(&mut self, ctx: &mut Context, binding: Binding) {
+        let chord = std::mem::replace(&mut self.command, false);
+        let command = match self.keymap.command(binding, chord) {
+            Some(command) => command,
+            None => return,
+        };
+        match command {
+            Command::CommandPrefix => self.command = true,
+            Command::Save => {
+                if self.save_state() {
+                    self.show_message("Saved".to_string());
+                }
+            }
+            Command::SaveAndQuit => {
+                // stay in the game if the save failed, so the run isn't lost
+                if self.save_state() {
+                    std::process::exit(0);
+                }
+            }
+            Command::Controls => self.open_controls(),
+            Command::Settings => self.open_settings(),
+            Command::Target => {
+                let mouse = ctx.mouse.position();
+                self.target(mouse.x, mouse.y);
+            }
+            _ => {
+                let queued_position = self.game.as_ref().unwrap().world.player.queued_position;
+                if let Some(action) = input::action_for(command, queued_position) {
+                    self.play(action);
+                }
+            }
         }
     }
-    fn load_save(ctx: &mut Context) -> Option<State> {
-        /* Here is how serialization works:
-         * serialization
-         *      is_serialized:
-         *          Contains either "0" or "1", where one is that there is a game serialized
-         *          while zero means there is none
-         *      world:
-         *          Contains the actual world object, written to in RON
-         *      rng:
-         *          Contains the rng object, in JSON
-         *
-         */
-
-        let mut serialized_game_str = fs::read_to_string("./serialization/is_serialized")
-            .expect("Should have been able to read the file")
-            .to_string();
-        // pops the newline character
-        if serialized_game_str.len() > 1 {
-            serialized_game_str.pop();
+
+    // Queues the tile under the given point on the screen for the targeted abilities
+    fn target(&mut self, x: f32, y: f32) {
+        if (y / TILE_SIZE.1 as f32) as usize >= UNIVERSAL_OFFSET as usize {
+            self.game.as_mut().unwrap().world.player.queued_position = Some(Position::new(
+                (x / TILE_SIZE.0 as f32) as usize,
+                (y / TILE_SIZE.1 as f32) as usize - UNIVERSAL_OFFSET as usize,
+            ));
         }
+    }
 
-        let serialized_game = serialized_game_str
-            .parse::<u8>()
-            .expect("Save data corrupted");
-        return if serialized_game == 0 {
-            println!("No serialized game");
-            None
-        } else if serialized_game == 1 {
-            let world_str =
-                fs::read_to_string("./serialization/world").expect("Couldn't read world file");
-            let world: World = ron::from_str(&world_str).unwrap();
-            let rng_str =
-                fs::read_to_string("./serialization/rng").expect("Couldn't read rng file");
-            let rng: ChaCha8Rng = serde_json::from_str(&rng_str).unwrap();
-            return Some(State::from(world, ctx, rng).expect("couldn't do audio for some reason"));
-        } else {
-            panic!("Save data corrupted")
+    fn play(&mut self, action: Action) {
+        let game = self.game.as_mut().unwrap();
+        match game.step(action) {
+            TurnOutcome::Ignored => {}
+            TurnOutcome::Played => {
+                self.should_draw = true;
+                let autosaver = self.autosaver.as_mut().unwrap();
+                if let Err(e) = autosaver.after_turn(game, &self.save_dir, self.slot) {
+                    self.show_message(format!("Couldn't autosave: {}", e));
+                }
+            }
+            TurnOutcome::PlayerDied => {
+                let slot_dir = slot::slot_dir(&self.save_dir, self.slot);
+                if let Err(e) = game.save_death_replay(&slot_dir) {
+                    eprintln!("Couldn't write replay: {}", e);
+                }
+                self.should_draw = true;
+            }
+        }
+    }
+
+    fn open_controls(&mut self) {
+        self.controls = Some(ControlsScreen {
+            selected: 0,
+            listening: false,
+            conflict: None,
+        });
+        self.message = None;
+        self.should_draw = true;
+    }
+
+    // The controls screen: Up/Down choose a command, Enter rebinds it to the next key or mouse
+    // button pressed, Delete unbinds it, R resets every command and Escape closes the screen.
+    // These keys are fixed so the screen can always be used, however the commands are bound
+    fn controls_input(&mut self, binding: Binding) {
+        self.should_draw = true;
+        let controls = self.controls.as_mut().unwrap();
+        let command = Command::ALL[controls.selected];
+        if controls.listening {
+            if binding == Binding::Key(KeyCode::Escape) {
+                controls.listening = false;
+                controls.conflict = None;
+                self.message = None;
+                return;
+            }
+            let others = self.keymap.conflicts_with(command, binding);
+            if !others.is_empty() && controls.conflict != Some(binding) {
+                controls.conflict = Some(binding);
+                let others: Vec<_> = others.iter().map(|other| other.describe()).collect();
+                self.show_message(format!(
+                    "{} is already bound to {}, press it again to bind it to {} instead",
+                    binding,
+                    others.join(" and "),
+                    command.describe()
+                ));
+                return;
+            }
+            controls.listening = false;
+            controls.conflict = None;
+            self.message = None;
+            self.keymap.rebind(command, binding);
+            self.save_keymap();
+            return;
+        }
+
+        match binding {
+            Binding::Key(KeyCode::Up) => {
+                controls.selected = (controls.selected + Command::ALL.len() - 1) % Command::ALL.len()
+            }
+            Binding::Key(KeyCode::Down) => {
+                controls.selected = (controls.selected + 1) % Command::ALL.len()
+            }
+            Binding::Key(KeyCode::Return) => {
+                controls.listening = true;
+                self.show_message(format!(
+                    "Press the key or mouse button for {}, Escape to cancel",
+                    command.describe()
+                ));
+            }
+            Binding::Key(KeyCode::Delete) | Binding::Key(KeyCode::Back) => {
+                self.keymap.unbind(command);
+                self.save_keymap();
+            }
+            Binding::Key(KeyCode::R) => {
+                self.keymap = Keymap::default();
+                self.save_keymap();
+                self.show_message("Reset every command to its default".to_string());
+            }
+            Binding::Key(KeyCode::Escape) => {
+                self.controls = None;
+                self.message = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn save_keymap(&mut self) {
+        if let Err(e) = self.keymap.save(&self.save_dir) {
+            self.show_message(format!("Couldn't save the controls: {}", e));
+        }
+    }
+
+    fn draw_controls(&mut self, ctx: &mut Context) -> GameResult {
+        let controls = self.controls.as_ref().unwrap();
+        let mut canvas =
+            graphics::Canvas::from_frame(ctx, graphics::Color::from(tile::TITLE_SCREEN_FLOOR));
+        let pos = Position::new(1, 0);
+        let text_spot = Vec2::new((pos.x as f32 + 0.25) * TILE_SIZE.0 as f32,  (pos.y as f32 + UNIVERSAL_OFFSET as f32) * TILE_SIZE.1 as f32);
+        canvas.draw(
+            &*(graphics::Text::new("Controls").set_scale(32.)),
+            graphics::DrawParam::from(text_spot).color(graphics::Color::from_rgb(217, 234, 211)),
+        );
+        let conflicts = self.keymap.conflicts();
+        for (index, command) in Command::ALL.iter().enumerate() {
+            let y = (UNIVERSAL_OFFSET as f32 + 3.) * TILE_SIZE.1 as f32 + index as f32 * 24.;
+            let marker = if index == controls.selected { ">" } else { " " };
+            let bindings = if index == controls.selected && controls.listening {
+                "...".to_string()
+            } else {
+                self.keymap.describe(*command)
+            };
+            let color = if conflicts.iter().any(|conflict| conflict.first == *command || conflict.second == *command) {
+                graphics::Color::from_rgb(234, 153, 153)
+            } else if index == controls.selected {
+                graphics::Color::from_rgb(180, 167, 214)
+            } else {
+                graphics::Color::from_rgb(159, 197, 232)
+            };
+            canvas.draw(
+                &*(graphics::Text::new(format!("{} {}", marker, command.describe())).set_scale(20.)),
+                graphics::DrawParam::from(Vec2::new(1.25 * TILE_SIZE.0 as f32, y)).color(color),
+            );
+            canvas.draw(
+                &*(graphics::Text::new(bindings).set_scale(20.)),
+                graphics::DrawParam::from(Vec2::new(22.25 * TILE_SIZE.0 as f32, y)).color(color),
+            );
+        }
+        let pos = Position::new(1, 43);
+        let text_spot = Vec2::new((pos.x as f32 + 0.25) * TILE_SIZE.0 as f32,  (pos.y as f32 + UNIVERSAL_OFFSET as f32) * TILE_SIZE.1 as f32);
+        let duration_left = "[Up/Down] Command  [Enter] Rebind  [Delete] Unbind  [R] Reset all  [Esc] Back";
+        canvas.draw(
+            &*(graphics::Text::new(duration_left).set_scale(20.)),
+            graphics::DrawParam::from(text_spot).color(graphics::Color::from_rgb(255, 255, 255)),
+        );
+        self.draw_message(&mut canvas, Position::new(1, 45));
+        canvas.finish(ctx)
+    }
+
+    fn open_settings(&mut self) {
+        self.settings_screen = Some(SettingsScreen {
+            selected: theme::find(&self.themes, &self.settings.theme),
+        });
+        self.message = None;
+        self.should_draw = true;
+    }
+
+    // The settings screen: Up/Down choose a theme, which is shown right away, Enter keeps it and
+    // Escape goes back to the theme that was in use
+    fn settings_input(&mut self, key: KeyCode) {
+        self.should_draw = true;
+        let settings_screen = self.settings_screen.as_mut().unwrap();
+        match key {
+            KeyCode::Up => {
+                settings_screen.selected =
+                    (settings_screen.selected + self.themes.len() - 1) % self.themes.len();
+                self.palette = self.themes[settings_screen.selected].palette.clone();
+            }
+            KeyCode::Down => {
+                settings_screen.selected = (settings_screen.selected + 1) % self.themes.len();
+                self.palette = self.themes[settings_screen.selected].palette.clone();
+            }
+            KeyCode::Return => {
+                let theme = &self.themes[settings_screen.selected];
+                self.settings.theme = theme.name.clone();
+                let description = theme.describe();
+                match self.settings.save(&self.save_dir) {
+                    Ok(()) => self.show_message(format!("Now using the {} theme", description)),
+                    Err(e) => self.show_message(format!("Couldn't save the settings: {}", e)),
+                }
+            }
+            KeyCode::Escape => {
+                let index = theme::find(&self.themes, &self.settings.theme);
+                self.palette = self.themes[index].palette.clone();
+                self.settings_screen = None;
+                self.message = None;
+            }
+            _ => {}
+        }
+    }
+
+    fn draw_settings(&mut self, ctx: &mut Context) -> GameResult {
+        let settings_screen = self.settings_screen.as_ref().unwrap();
+        let mut canvas =
+            graphics::Canvas::from_frame(ctx, graphics::Color::from(tile::TITLE_SCREEN_FLOOR));
+        let pos = Position::new(1, 0);
+        let text_spot = Vec2::new((pos.x as f32 + 0.25) * TILE_SIZE.0 as f32,  (pos.y as f32 + UNIVERSAL_OFFSET as f32) * TILE_SIZE.1 as f32);
+        canvas.draw(
+            &*(graphics::Text::new("Settings").set_scale(32.)),
+            graphics::DrawParam::from(text_spot).color(graphics::Color::from_rgb(217, 234, 211)),
+        );
+        let pos = Position::new(1, 3);
+        let text_spot = Vec2::new((pos.x as f32 + 0.25) * TILE_SIZE.0 as f32,  (pos.y as f32 + UNIVERSAL_OFFSET as f32) * TILE_SIZE.1 as f32);
+        canvas.draw(
+            &*(graphics::Text::new("Theme").set_scale(24.)),
+            graphics::DrawParam::from(text_spot).color(graphics::Color::from_rgb(217, 234, 211)),
+        );
+        for (index, theme) in self.themes.iter().enumerate() {
+            let y = (UNIVERSAL_OFFSET as f32 + 6.) * TILE_SIZE.1 as f32 + index as f32 * 24.;
+            let marker = if index == settings_screen.selected { ">" } else { " " };
+            let in_use = if theme.name == self.settings.theme { " (in use)" } else { "" };
+            let color = if index == settings_screen.selected {
+                graphics::Color::from_rgb(180, 167, 214)
+            } else {
+                graphics::Color::from_rgb(159, 197, 232)
+            };
+            canvas.draw(
+                &*(graphics::Text::new(format!("{} {}{}", marker, theme.describe(), in_use)).set_scale(20.)),
+                graphics::DrawParam::from(Vec2::new(1.25 * TILE_SIZE.0 as f32, y)).color(color),
+            );
+        }
+
+        // a tile of each color that matters during a fight, on the ground it is usually seen on
+        let palette = &self.palette;
+        let swatches = [
+            ("Chasing enemy", palette.chasing_enemy, palette.grass),
+            ("Shooter", palette.shooter_enemy, palette.grass),
+            ("Knight", palette.knight_enemy, palette.grass),
+            ("Bomber", palette.bomber_enemy, palette.grass),
+            ("Activated bomber", palette.bomber_enemy_activated, palette.grass),
+            ("Major enemy", palette.major_enemy, palette.grass),
+            ("Knight lunge", palette.knight_lunge, palette.grass),
+            ("Elite health", palette.elite_health, palette.grass),
+            ("Elite shield", palette.elite_shield, palette.grass),
+            ("Player", palette.player, palette.grass),
+            ("Your projectile", palette.projectile_player, palette.grass),
+            ("Enemy shot", palette.projectile_enemy, palette.grass),
+            ("Structure", palette.structure, palette.grass),
+            ("Water", palette.water, palette.grass),
+            ("Lava", palette.lava, palette.grass),
+            ("Laser warning", palette.boss_laser[0], palette.floor),
+            ("Laser", palette.boss_laser[2], palette.floor),
+            ("Asteroid warning", palette.boss_asteroid[0], palette.floor),
+            ("Asteroid", palette.boss_asteroid[3], palette.floor),
+            ("Stun well", palette.stun_well[1], palette.floor),
+            ("Safe spot", palette.safe_spot, palette.floor),
+            ("Vulnerable boss", palette.boss_vulnerable, palette.floor),
+        ];
+        for (index, (name, color, ground)) in swatches.iter().enumerate() {
+            let y = (UNIVERSAL_OFFSET as i32 + 6) * TILE_SIZE.1 as i32 + index as i32 * 24;
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(graphics::Rect::new_i32(
+                        26 * TILE_SIZE.0 as i32,
+                        y - 4,
+                        3 * TILE_SIZE.0 as i32,
+                        24,
+                    ))
+                    .color(*ground),
+            );
+            canvas.draw(
+                &graphics::Quad,
+                graphics::DrawParam::new()
+                    .dest_rect(graphics::Rect::new_i32(
+                        27 * TILE_SIZE.0 as i32,
+                        y,
+                        TILE_SIZE.0 as i32,
+                        TILE_SIZE.1 as i32,
+                    ))
+                    .color(*color),
+            );
+            canvas.draw(
+                &*(graphics::Text::new(*name).set_scale(20.)),
+                graphics::DrawParam::from(Vec2::new(30.25 * TILE_SIZE.0 as f32, y as f32))
+                    .color(graphics::Color::from_rgb(255, 255, 255)),
+            );
+        }
+        let pos = Position::new(1, 43);
+        let text_spot = Vec2::new((pos.x as f32 + 0.25) * TILE_SIZE.0 as f32,  (pos.y as f32 + UNIVERSAL_OFFSET as f32) * TILE_SIZE.1 as f32);
+        let duration_left = "[Up/Down] Theme  [Enter] Use it  [Esc] Back";
+        canvas.draw(
+            &*(graphics::Text::new(duration_left).set_scale(20.)),
+            graphics::DrawParam::from(text_spot).color(graphics::Color::from_rgb(255, 255, 255)),
+        );
+        self.draw_message(&mut canvas, Position::new(1, 45));
+        canvas.finish(ctx)
+    }
+
+    // Returns if the save was written, showing why not otherwise
+    fn save_state(&mut self) -> bool {
+        match slot::save(self.game.as_mut().unwrap(), &self.save_dir, self.slot) {
+            Ok(()) => true,
+            Err(e) => {
+                self.show_message(format!("Couldn't save: {}", e));
+                false
+            }
+        }
+    }
+
+    // Loads the game in the slot, or the most recently saved slot if none is given. Stays on the
+    // title screen showing why if it couldn't
+    pub fn load_save(
+        ctx: &mut Context,
+        seed: u64,
+        balance: Balance,
+        save_dir: PathBuf,
+        slot: Option<usize>,
+    ) -> GameResult<State> {
+        let slot = match slot.or_else(|| slot::most_recent(&save_dir)) {
+            Some(slot) => slot,
+            None => {
+                let mut state = State::title_screen(ctx, seed, balance, save_dir, 0)?;
+                state.show_message(format!("Couldn't load save: {}", SaveError::NoSave));
+                return Ok(state);
+            }
         };
+        match slot::load(&save_dir, slot, &balance) {
+            Ok(game) => State::from(game, ctx, seed, balance, save_dir, slot),
+            Err(e) => {
+                let mut state = State::title_screen(ctx, seed, balance, save_dir, slot)?;
+                state.show_message(format!("Couldn't load slot {}: {}", slot + 1, e));
+                Ok(state)
+            }
+        }
+    }
+
+    // The slot picker: Up/Down choose a slot, N starts a new game in it, L or Enter loads it, A
+    // loads its most recent autosave, C copies it into another slot and X deletes it
+    fn title_screen_key(&mut self, ctx: &mut Context, key: KeyCode) -> GameResult {
+        let confirm_delete = std::mem::replace(&mut self.confirm_delete, false);
+        let confirm_new_game = std::mem::replace(&mut self.confirm_new_game, false);
+        match key {
+            KeyCode::Up => self.slot = (self.slot + SLOT_COUNT - 1) % SLOT_COUNT,
+            KeyCode::Down => self.slot = (self.slot + 1) % SLOT_COUNT,
+            // the save in the slot is overwritten by the new game, so that takes a second N
+            KeyCode::N if self.slots[self.slot].is_some() && !confirm_new_game => {
+                self.confirm_new_game = true;
+                self.show_message(format!(
+                    "Press N again to start over in slot {}, replacing its save",
+                    self.slot + 1
+                ));
+            }
+            KeyCode::N => {
+                let (balance, save_dir) = (self.balance.clone(), self.save_dir.clone());
+                *self = Self::new(ctx, false, self.seed, balance, save_dir, self.slot)?;
+            }
+            KeyCode::L | KeyCode::Return => {
+                match slot::load(&self.save_dir, self.slot, &self.balance) {
+                    Ok(game) => {
+                        let (balance, save_dir) = (self.balance.clone(), self.save_dir.clone());
+                        *self = Self::from(game, ctx, self.seed, balance, save_dir, self.slot)?;
+                    }
+                    Err(e) => {
+                        self.show_message(format!("Couldn't load slot {}: {}", self.slot + 1, e))
+                    }
+                }
+            }
+            KeyCode::A => {
+                let config = AutosaveConfig::load(&self.save_dir);
+                match autosave::most_recent(&config, &self.save_dir, self.slot) {
+                    Some((index, _)) => {
+                        let dir = autosave::autosave_dir(&self.save_dir, self.slot, index);
+                        match Game::load(&dir, &self.balance) {
+                            Ok(game) => {
+                                let (balance, save_dir) =
+                                    (self.balance.clone(), self.save_dir.clone());
+                                *self =
+                                    Self::from(game, ctx, self.seed, balance, save_dir, self.slot)?;
+                            }
+                            Err(e) => self.show_message(format!("Couldn't load autosave: {}", e)),
+                        }
+                    }
+                    None => {
+                        self.show_message(format!("Slot {} has no autosaves", self.slot + 1))
+                    }
+                }
+            }
+            KeyCode::C => match self.copy_from.take() {
+                None if self.slots[self.slot].is_some() => {
+                    self.copy_from = Some(self.slot);
+                    self.show_message(format!(
+                        "Choose a slot to copy slot {} into and press C again",
+                        self.slot + 1
+                    ));
+                }
+                None => self.show_message(format!("Slot {} is empty", self.slot + 1)),
+                Some(from) if from == self.slot => self.message = None,
+                Some(from) => match slot::copy(&self.save_dir, from, self.slot) {
+                    Ok(()) => {
+                        self.slots = slot::list(&self.save_dir);
+                        self.show_message(format!(
+                            "Copied slot {} into slot {}",
+                            from + 1,
+                            self.slot + 1
+                        ));
+                    }
+                    Err(e) => self.show_message(format!("Couldn't copy slot: {}", e)),
+                },
+            },
+            KeyCode::X | KeyCode::Delete => {
+                if self.slots[self.slot].is_none() {
+                    self.show_message(format!("Slot {} is empty", self.slot + 1));
+                } else if confirm_delete {
+                    match slot::delete(&self.save_dir, self.slot) {
+                        Ok(()) => {
+                            self.slots = slot::list(&self.save_dir);
+                            self.show_message(format!("Deleted slot {}", self.slot + 1));
+                        }
+                        Err(e) => self.show_message(format!("Couldn't delete slot: {}", e)),
+                    }
+                } else {
+                    self.confirm_delete = true;
+                    self.show_message(format!("Press X again to delete slot {}", self.slot + 1));
+                }
+            }
+            KeyCode::K => self.open_controls(),
+            KeyCode::S => self.open_settings(),
+            KeyCode::Escape => {
+                self.copy_from = None;
+                self.message = None;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn show_message(&mut self, message: String) {
+        self.message = Some(message);
+        self.should_draw = true;
+    }
+
+    fn draw_message(&self, canvas: &mut Canvas, pos: Position) {
+        if let Some(message) = &self.message {
+            let text_spot = Vec2::new(
+                (pos.x as f32 + 0.25) * TILE_SIZE.0 as f32,
+                (pos.y as f32 + UNIVERSAL_OFFSET as f32) * TILE_SIZE.1 as f32,
+            );
+            canvas.draw(
+                &*(graphics::Text::new(message.as_str()).set_scale(20.)),
+                graphics::DrawParam::from(text_spot).color(graphics::Color::WHITE),
+            );
+        }
     }
 }
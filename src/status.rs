@@ -0,0 +1,142 @@
+use crate::damage::{Damage, DamageKind};
+
+use std::cmp::max;
+
+// A lasting effect on the player, an enemy or a boss. Adding one is a new variant here plus
+// whatever applies it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum StatusKind {
+    // Takes fire damage every turn
+    Burn,
+    // Can't do anything
+    Stun,
+    // Only gets to act every other turn
+    Slow,
+    // Takes poison damage every turn
+    Poison,
+    // Can't be seen by enemies
+    Invisible,
+}
+
+impl StatusKind {
+    // The order effects are listed in on the HUD
+    pub const ALL: [StatusKind; 5] = [
+        StatusKind::Burn,
+        StatusKind::Stun,
+        StatusKind::Slow,
+        StatusKind::Poison,
+        StatusKind::Invisible,
+    ];
+
+    // Short name for the HUD
+    pub fn describe(self) -> &'static str {
+        match self {
+            StatusKind::Burn => "Burn",
+            StatusKind::Stun => "Stun",
+            StatusKind::Slow => "Slow",
+            StatusKind::Poison => "Poison",
+            StatusKind::Invisible => "Invis",
+        }
+    }
+
+    // The kind of damage it does every turn, None for the effects that don't hurt
+    fn damage_kind(self) -> Option<DamageKind> {
+        match self {
+            StatusKind::Burn => Some(DamageKind::Fire),
+            StatusKind::Poison => Some(DamageKind::Poison),
+            StatusKind::Stun | StatusKind::Slow | StatusKind::Invisible => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    // Turns left, the effect is gone once this reaches 0
+    pub turns: usize,
+    // Damage done every turn, only burns and poison use it
+    #[serde(default)]
+    pub damage: usize,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusKind, turns: usize) -> Self {
+        StatusEffect {
+            kind,
+            turns,
+            damage: 0,
+        }
+    }
+
+    pub fn with_damage(kind: StatusKind, turns: usize, damage: usize) -> Self {
+        StatusEffect {
+            kind,
+            turns,
+            damage,
+        }
+    }
+}
+
+// Every effect on one combatant, at most one of each kind
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct StatusEffects(Vec<StatusEffect>);
+
+impl StatusEffects {
+    // Effects don't stack, applying one that is already there keeps whichever lasts longer and
+    // whichever hurts more
+    pub fn apply(&mut self, effect: StatusEffect) {
+        if effect.turns == 0 {
+            return;
+        }
+        match self.0.iter_mut().find(|existing| existing.kind == effect.kind) {
+            Some(existing) => {
+                existing.turns = max(existing.turns, effect.turns);
+                existing.damage = max(existing.damage, effect.damage);
+            }
+            None => self.0.push(effect),
+        }
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.turns(kind) > 0
+    }
+
+    // Turns left of that effect, 0 if it isn't there
+    pub fn turns(&self, kind: StatusKind) -> usize {
+        self.0
+            .iter()
+            .find(|effect| effect.kind == kind)
+            .map_or(0, |effect| effect.turns)
+    }
+
+    pub fn remove(&mut self, kind: StatusKind) {
+        self.0.retain(|effect| effect.kind != kind);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &StatusEffect> {
+        self.0.iter()
+    }
+
+    // Whether its owner gets to act this turn: never while stunned, and only on even turns left
+    // while slowed
+    pub fn can_act(&self) -> bool {
+        !self.has(StatusKind::Stun) && self.turns(StatusKind::Slow) % 2 == 0
+    }
+
+    // Counts every effect down a turn, dropping the ones that ran out. Returns the damage burns
+    // and poison do this turn, for the owner to take with its own resistances
+    pub fn tick(&mut self) -> Vec<Damage> {
+        let mut damage = Vec::new();
+        for effect in &mut self.0 {
+            if let Some(kind) = effect.kind.damage_kind() {
+                if effect.damage > 0 {
+                    damage.push(Damage::new(effect.damage, kind));
+                }
+            }
+            effect.turns -= 1;
+        }
+        self.0.retain(|effect| effect.turns > 0);
+        damage
+    }
+}
@@ -0,0 +1,120 @@
+use crate::{save, tile::Palette};
+
+use std::fs;
+use std::path::Path;
+
+// Name of the settings inside the save directory
+const SETTINGS_FILE: &'static str = "settings.ron";
+// The theme built into the game, always listed first even if the themes directory is missing
+pub const DEFAULT_THEME: &'static str = "default";
+
+// What was chosen on the settings screen, kept in settings.ron in the save directory
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    // Name of the theme file without .ron, or "default"
+    pub theme: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            theme: DEFAULT_THEME.to_string(),
+        }
+    }
+}
+
+impl Settings {
+    // Reads the settings, falling back to the defaults if there are none or they can't be parsed
+    pub fn load(save_dir: &Path) -> Self {
+        let path = save_dir.join(SETTINGS_FILE);
+        match fs::read_to_string(&path) {
+            Ok(settings_str) => match ron::from_str(&settings_str) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    eprintln!("Couldn't parse {}, using the defaults: {}", path.display(), e);
+                    Settings::default()
+                }
+            },
+            Err(_) => Settings::default(),
+        }
+    }
+
+    pub fn save(&self, save_dir: &Path) -> std::io::Result<()> {
+        let serialized = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(save::serialize_error)?;
+        fs::create_dir_all(save_dir)?;
+        save::write_atomic(&save_dir.join(SETTINGS_FILE), serialized.as_bytes())
+    }
+}
+
+// The colors to draw the game with, read from a .ron file in the themes directory. Colors left
+// out of the file are the same as in the default theme
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    // Name of the file without .ron
+    pub name: String,
+    pub palette: Palette,
+}
+
+impl Theme {
+    pub fn default_theme() -> Self {
+        Theme {
+            name: DEFAULT_THEME.to_string(),
+            palette: Palette::default(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Theme, String> {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .ok_or_else(|| format!("{} isn't a theme file", path.display()))?;
+        let theme_str = fs::read_to_string(path)
+            .map_err(|e| format!("couldn't read {}: {}", path.display(), e))?;
+        let palette = ron::from_str(&theme_str)
+            .map_err(|e| format!("couldn't parse {}: {}", path.display(), e))?;
+        Ok(Theme { name, palette })
+    }
+
+    // e.g. "High contrast" for high_contrast.ron
+    pub fn describe(&self) -> String {
+        let name = self.name.replace('_', " ");
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => name,
+        }
+    }
+}
+
+// The default theme followed by every theme in the directory, sorted by name. A theme that can't
+// be read is left out, printing why
+pub fn list(themes_dir: &Path) -> Vec<Theme> {
+    let mut themes = vec![Theme::default_theme()];
+    let mut paths: Vec<_> = match fs::read_dir(themes_dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().map_or(false, |extension| extension == "ron"))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    paths.sort();
+    for path in paths {
+        match Theme::load(&path) {
+            // a file can't replace the built in theme
+            Ok(theme) if theme.name == DEFAULT_THEME => {}
+            Ok(theme) => themes.push(theme),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    themes
+}
+
+// Index of the theme with that name, or of the default theme if there is no such theme
+pub fn find(themes: &[Theme], name: &str) -> usize {
+    themes
+        .iter()
+        .position(|theme| theme.name == name)
+        .unwrap_or(0)
+}
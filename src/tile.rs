@@ -16,19 +16,18 @@ pub const MOUNTAIN: [[f32; 4]; 5] = [
 pub const PORTAL: [f32; 4] = [0.631, 0.012, 0.988, 1.0];
 pub const WALL: [f32; 4] = [0.3, 0.3, 0.3, 1.0];
 pub const PROJECTILE_PLAYER: [f32; 4] = [0.5, 0.0, 0.0, 1.0];
+pub const PROJECTILE_ENEMY: [f32; 4] = [0.9, 0.45, 0.0, 1.0];
 pub const GRASS: [f32; 4] = [0.0, 0.5, 0.0, 1.0];
 pub const STRUCTURE: [f32; 4] = [0.3, 0.0, 0.0, 1.0];
 
 // pub const LIGHTNING: [[f32; 4]; 4] = [
-// This is a random color, it just can't conflict with anything esle
-pub const LIGHTNING_PLACEHOLDER: [f32; 4] = [0.414, 0.0, 0.414, 0.414];
+pub const LIGHTNING_PENDING: [f32; 4] = [0.414, 0.0, 0.414, 0.414];
 pub const LIGHTNING_INITIAL: [f32; 4] = [0.0, 0.0, 0.5, 1.0];
 pub const LIGHTNING_SECONDARY: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
 pub const LIGHTNING_FINAL: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
 // ];
 
-// THIS COLOR MUST NOT CONFLICT WITH ANYTHING ELSE
-pub const FIRE_PLACEHOLDER: [f32; 4] = [0.732, 0.732, 0.732, 0.732];
+pub const FIRE_PENDING: [f32; 4] = [0.732, 0.732, 0.732, 0.732];
 pub const FIRE_INITIAL: [f32; 4] = [253.0 / 255.0, 249.0 / 255.0, 212.0 / 255.0, 1.0];
 pub const FIRE_SECONDARY: [f32; 4] = [250.0 / 255.0, 192.0 / 255.0, 0.0 / 255.0, 1.0];
 pub const FIRE_TERTIARY: [f32; 4] = [226.0 / 255.0, 88.0 / 255.0, 34.0 / 255.0, 1.0];
@@ -45,8 +44,8 @@ pub const BOMBER_EXPLOSION: [[f32; 4]; 3] = [
     [1.0, 0.3, 0.3, 1.0],
     [1.0, 0.6, 0.6, 1.0],
 ]; //Suicide bomber, can dodge projectiles, destroys structures (AOE), moves 2 tiles per turn
-pub const SHOOTER_ENEMY: [f32; 4] = [0.8, 0.3, 0.3, 1.0]; //shoots projectiels lower helath, cant move when shooting projectile
-pub const KNIGHT_ENEMY: [f32; 4] = [0.8, 0.3, 0.3, 1.0]; //Tanky regular enemy, 2 tile range of attack
+pub const SHOOTER_ENEMY: [f32; 4] = [0.9, 0.5, 0.6, 1.0]; //shoots projectiels lower helath, cant move when shooting projectile
+pub const KNIGHT_ENEMY: [f32; 4] = [0.55, 0.55, 0.75, 1.0]; //Tanky regular enemy, 2 tile range of attack
 pub const MAJOR_ENEMY: [f32; 4] = [0.6, 0.2, 0.2, 1.0]; //3x3 higher health than knight, lower damage, can shoot projectiles maybe
 
 pub const COLUMN_BOSS: [f32; 4] = [0.0, 0.6, 0.99, 1.0];
@@ -69,6 +68,13 @@ pub const BOSS_RECOVERY_TWO: [f32; 4] = [0.01, 0.9, 1.0, 1.0];
 pub const STUN_WELL_REAL: [f32; 4] = [0.99, 0.9, 0.1, 0.4];
 pub const STUN_WELL_INDICATOR: [f32; 4] = [1.0, 0.95, 0.7, 0.5];
 pub const SAFE_SPOT_INDICATOR: [f32; 4] = [0.05, 0.45, 0.15, 1.0];
+pub const KNIGHT_LUNGE_INDICATOR: [f32; 4] = [1.0, 0.85, 0.4, 0.4];
+pub const MAP_PLAYER: [f32; 4] = [0.9, 0.1, 0.1, 1.0];
+pub const MAP_BOSS_ROOM: [f32; 4] = [0.1, 0.5, 0.1, 1.0];
+pub const MAP_ELITE_ROOM: [f32; 4] = [0.6, 0.2, 0.7, 1.0];
+pub const ELITE_HEALTH: [f32; 4] = [0.85, 0.1, 0.1, 1.0];
+pub const ELITE_HEALTH_LOST: [f32; 4] = [0.2, 0.2, 0.2, 1.0];
+pub const ELITE_SHIELD: [f32; 4] = [0.3, 0.7, 1.0, 1.0];
 
 // pub const ENEMIES: [[f32; 4]; 7] = [
 //     // basic
@@ -93,3 +99,442 @@ const INVIS_TRANSPARENCY: f32 = 0.3;
 pub const PLAYER_INVISIBLE: [f32; 4] = [0.5, 0.5, 0.5, INVIS_TRANSPARENCY];
 pub const PLAYER_STUNNED: [f32; 4] = [1.0, 0.9, 0.3, 1.0];
 
+
+// What a tile is. The game logic only ever looks at kinds, and the palette decides what color
+// each kind is drawn in, so changing a color can't change how the game plays
+
+// The ground of a room. A tile without terrain is plain floor
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum TerrainKind {
+    Wall,
+    Portal,
+    Water,
+    Lava,
+    Grass,
+    // Mountains get lighter towards their edges, 0 is the darkest
+    Mountain(u8),
+}
+
+// Whatever stands on the terrain, kept in the entity map (the player, enemies, the player's
+// projectiles) and the atmosphere map (lightning, fire, missiles and built structures)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum TileKind {
+    Player,
+    Enemy(EnemyKind),
+    Projectile(ProjectileKind),
+    // A wall built by the player
+    Structure,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, serde::Serialize, serde::Deserialize)]
+pub enum EnemyKind {
+    #[default]
+    Chaser,
+    Bomber,
+    // A bomber in range of the player, it explodes next turn
+    BomberActivated,
+    // A bomber that exploded and is removed next turn
+    BomberDeactivated,
+    Shooter,
+    Knight,
+    // The 3x3 enemy
+    Major,
+    MajorBoss,
+}
+
+// Lightning and fire go through a few stages, one per turn. The pending stage is the turn they
+// are cast on, before they do anything
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, serde::Serialize, serde::Deserialize)]
+pub enum ProjectileKind {
+    #[default]
+    Player,
+    // The missile, which tracks the closest enemy
+    Tracking,
+    // Fired by shooters, flies straight and only hurts the player
+    Enemy,
+    LightningPending,
+    LightningInitial,
+    LightningSecondary,
+    LightningFinal,
+    FirePending,
+    FireInitial,
+    FireSecondary,
+    FireTertiary,
+    FireFinal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, serde::Serialize, serde::Deserialize)]
+pub enum BossKind {
+    #[default]
+    Laser,
+    Column,
+    Chasing,
+    BlackOut,
+    // The final boss in the middle room
+    Major,
+}
+
+// How far along a telegraphed boss attack (lasers, asteroids, stun wells, the chasing boss's
+// rush) is. It warns the player for a few turns and only does anything once active
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum AttackStage {
+    Warning(u8),
+    Active,
+}
+
+impl Default for AttackStage {
+    fn default() -> Self {
+        AttackStage::Warning(0)
+    }
+}
+
+impl AttackStage {
+    // The stage next turn, for an attack that warns for the given number of turns
+    pub fn advance(self, warning_turns: u8) -> AttackStage {
+        match self {
+            AttackStage::Warning(turn) if turn + 1 < warning_turns => AttackStage::Warning(turn + 1),
+            _ => AttackStage::Active,
+        }
+    }
+}
+
+// Up to save version 2 tiles had no kinds and were told apart by their color. These are the
+// colors they were saved with, frozen here so old saves can still be read after the palette
+// changes. The world hash uses them too (see replay::world_hash), so replays recorded back then
+// still check out. Never draw with them, that is what the palette is for
+impl TerrainKind {
+    pub const ALL: [TerrainKind; 10] = [
+        TerrainKind::Wall,
+        TerrainKind::Portal,
+        TerrainKind::Water,
+        TerrainKind::Lava,
+        TerrainKind::Grass,
+        TerrainKind::Mountain(0),
+        TerrainKind::Mountain(1),
+        TerrainKind::Mountain(2),
+        TerrainKind::Mountain(3),
+        TerrainKind::Mountain(4),
+    ];
+
+    pub fn legacy_color(&self) -> [f32; 4] {
+        match self {
+            TerrainKind::Wall => [0.3, 0.3, 0.3, 1.0],
+            TerrainKind::Portal => [0.631, 0.012, 0.988, 1.0],
+            TerrainKind::Water => [0.145, 0.588, 0.8, 1.0],
+            TerrainKind::Lava => [0.988, 0.612, 0.078, 1.0],
+            TerrainKind::Grass => [0.0, 0.5, 0.0, 1.0],
+            TerrainKind::Mountain(0) => [0.389, 0.289, 0.126, 1.0],
+            TerrainKind::Mountain(1) => [0.449, 0.373, 0.313, 1.0],
+            TerrainKind::Mountain(2) => [0.579, 0.403, 0.343, 1.0],
+            TerrainKind::Mountain(3) => [0.619, 0.463, 0.403, 1.0],
+            TerrainKind::Mountain(_) => [0.659, 0.523, 0.463, 1.0],
+        }
+    }
+
+    pub fn from_legacy_color(color: [f32; 4]) -> Option<TerrainKind> {
+        Self::ALL.into_iter().find(|kind| kind.legacy_color() == color)
+    }
+}
+
+impl TileKind {
+    pub fn legacy_color(&self) -> [f32; 4] {
+        match self {
+            TileKind::Player => [0.5, 0.5, 0.5, 1.0],
+            TileKind::Enemy(kind) => kind.legacy_color(),
+            TileKind::Projectile(kind) => kind.legacy_color(),
+            TileKind::Structure => [0.3, 0.0, 0.0, 1.0],
+        }
+    }
+
+    pub fn from_legacy_color(color: [f32; 4]) -> Option<TileKind> {
+        if color == TileKind::Player.legacy_color() {
+            Some(TileKind::Player)
+        } else if color == TileKind::Structure.legacy_color() {
+            Some(TileKind::Structure)
+        } else if let Some(kind) = EnemyKind::from_legacy_color(color) {
+            Some(TileKind::Enemy(kind))
+        } else {
+            ProjectileKind::from_legacy_color(color).map(TileKind::Projectile)
+        }
+    }
+}
+
+impl EnemyKind {
+    // Chasers come first, knights, shooters and the major boss enemy had the same color and were
+    // all treated as chasers
+    pub const ALL: [EnemyKind; 8] = [
+        EnemyKind::Chaser,
+        EnemyKind::Bomber,
+        EnemyKind::BomberActivated,
+        EnemyKind::BomberDeactivated,
+        EnemyKind::Shooter,
+        EnemyKind::Knight,
+        EnemyKind::Major,
+        EnemyKind::MajorBoss,
+    ];
+
+    pub fn legacy_color(&self) -> [f32; 4] {
+        match self {
+            EnemyKind::Chaser => [0.8, 0.3, 0.3, 1.0],
+            EnemyKind::Bomber => [0.0, 0.8, 0.3, 1.0],
+            EnemyKind::BomberActivated => [0.3, 0.1, 0.1, 1.0],
+            EnemyKind::BomberDeactivated => [1.0, 0.0, 0.0, 1.0],
+            EnemyKind::Shooter => [0.8, 0.3, 0.3, 1.0],
+            EnemyKind::Knight => [0.8, 0.3, 0.3, 1.0],
+            EnemyKind::Major => [0.6, 0.2, 0.2, 1.0],
+            EnemyKind::MajorBoss => [0.8, 0.3, 0.3, 1.0],
+        }
+    }
+
+    pub fn from_legacy_color(color: [f32; 4]) -> Option<EnemyKind> {
+        Self::ALL.into_iter().find(|kind| kind.legacy_color() == color)
+    }
+}
+
+impl ProjectileKind {
+    pub const ALL: [ProjectileKind; 12] = [
+        ProjectileKind::Player,
+        ProjectileKind::Tracking,
+        ProjectileKind::Enemy,
+        ProjectileKind::LightningPending,
+        ProjectileKind::LightningInitial,
+        ProjectileKind::LightningSecondary,
+        ProjectileKind::LightningFinal,
+        ProjectileKind::FirePending,
+        ProjectileKind::FireInitial,
+        ProjectileKind::FireSecondary,
+        ProjectileKind::FireTertiary,
+        ProjectileKind::FireFinal,
+    ];
+
+    pub fn legacy_color(&self) -> [f32; 4] {
+        match self {
+            ProjectileKind::Player => [0.5, 0.0, 0.0, 1.0],
+            ProjectileKind::Tracking => [4.0 / 255.0, 188.0 / 255.0, 253.0 / 255.0, 1.0],
+            // came after saves stopped storing colors, only hashed into replays
+            ProjectileKind::Enemy => [0.9, 0.45, 0.0, 1.0],
+            ProjectileKind::LightningPending => [0.414, 0.0, 0.414, 0.414],
+            ProjectileKind::LightningInitial => [0.0, 0.0, 0.5, 1.0],
+            ProjectileKind::LightningSecondary => [0.0, 0.0, 1.0, 1.0],
+            ProjectileKind::LightningFinal => [1.0, 1.0, 1.0, 1.0],
+            ProjectileKind::FirePending => [0.732, 0.732, 0.732, 0.732],
+            ProjectileKind::FireInitial => [253.0 / 255.0, 249.0 / 255.0, 212.0 / 255.0, 1.0],
+            ProjectileKind::FireSecondary => [250.0 / 255.0, 192.0 / 255.0, 0.0 / 255.0, 1.0],
+            ProjectileKind::FireTertiary => [226.0 / 255.0, 88.0 / 255.0, 34.0 / 255.0, 1.0],
+            ProjectileKind::FireFinal => [215.0 / 255.0, 53.0 / 255.0, 2.0 / 255.0, 1.0],
+        }
+    }
+
+    pub fn from_legacy_color(color: [f32; 4]) -> Option<ProjectileKind> {
+        Self::ALL.into_iter().find(|kind| kind.legacy_color() == color)
+    }
+}
+
+// The colors everything in the world is drawn with. Themes are palettes read from a file, any
+// color left out of the file keeps its default
+#[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Palette {
+    // The ground where there is no terrain, in boss rooms and the final boss room. Elsewhere the
+    // ground is grass
+    pub floor: [f32; 4],
+    pub boss_floor: [f32; 4],
+    pub wall: [f32; 4],
+    pub portal: [f32; 4],
+    pub water: [f32; 4],
+    pub lava: [f32; 4],
+    pub grass: [f32; 4],
+    pub mountain: [[f32; 4]; 5],
+    pub structure: [f32; 4],
+
+    pub player: [f32; 4],
+    pub player_invisible: [f32; 4],
+    pub player_stunned: [f32; 4],
+
+    pub chasing_enemy: [f32; 4],
+    pub bomber_enemy: [f32; 4],
+    pub bomber_enemy_activated: [f32; 4],
+    pub bomber_enemy_deactivated: [f32; 4],
+    pub shooter_enemy: [f32; 4],
+    pub knight_enemy: [f32; 4],
+    pub major_enemy: [f32; 4],
+    // The tiles a knight is about to lunge over
+    pub knight_lunge: [f32; 4],
+    // Closest ring first
+    pub bomber_explosion: [[f32; 4]; 3],
+    // The health bar over an elite, and its color while the elite's shield holds
+    pub elite_health: [f32; 4],
+    pub elite_health_lost: [f32; 4],
+    pub elite_shield: [f32; 4],
+
+    pub projectile_player: [f32; 4],
+    pub tracking_projectile: [f32; 4],
+    pub projectile_enemy: [f32; 4],
+    // One color per stage, pending first
+    pub lightning: [[f32; 4]; 4],
+    pub fire: [[f32; 4]; 5],
+
+    pub laser_boss: [f32; 4],
+    pub column_boss: [f32; 4],
+    pub chasing_boss: [f32; 4],
+    pub black_out_boss: [f32; 4],
+    pub major_boss: [f32; 4],
+    // The edge of a boss, which shows whether it can be hit
+    pub boss_surroundings: [f32; 4],
+    pub boss_vulnerable: [f32; 4],
+    // The last two turns a boss is vulnerable for
+    pub boss_recovery: [[f32; 4]; 2],
+    // One color per warning turn, the last one is the active attack
+    pub boss_laser: [[f32; 4]; 3],
+    pub boss_asteroid: [[f32; 4]; 4],
+    pub stun_well: [[f32; 4]; 2],
+    // The fire the chasing boss leaves behind after rushing
+    pub rush_trail: [f32; 4],
+    pub safe_spot: [f32; 4],
+
+    // The dot showing where the player is on the map in the corner, the boss rooms, and the
+    // rooms whose elite is still alive
+    pub map_player: [f32; 4],
+    pub map_boss_room: [f32; 4],
+    pub map_elite_room: [f32; 4],
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette {
+            floor: FLOOR,
+            boss_floor: BOSS_FLOOR,
+            wall: WALL,
+            portal: PORTAL,
+            water: WATER,
+            lava: LAVA,
+            grass: GRASS,
+            mountain: MOUNTAIN,
+            structure: STRUCTURE,
+            player: PLAYER,
+            player_invisible: PLAYER_INVISIBLE,
+            player_stunned: PLAYER_STUNNED,
+            chasing_enemy: CHASING_ENEMY,
+            bomber_enemy: BOMBER_ENEMY,
+            bomber_enemy_activated: BOMBER_ENEMY_ACTIVATED,
+            bomber_enemy_deactivated: BOMBER_ENEMY_DEACTIVATED,
+            shooter_enemy: SHOOTER_ENEMY,
+            knight_enemy: KNIGHT_ENEMY,
+            major_enemy: MAJOR_ENEMY,
+            knight_lunge: KNIGHT_LUNGE_INDICATOR,
+            bomber_explosion: BOMBER_EXPLOSION,
+            elite_health: ELITE_HEALTH,
+            elite_health_lost: ELITE_HEALTH_LOST,
+            elite_shield: ELITE_SHIELD,
+            projectile_player: PROJECTILE_PLAYER,
+            tracking_projectile: TRACKING_PROJECTILE,
+            projectile_enemy: PROJECTILE_ENEMY,
+            lightning: [LIGHTNING_PENDING, LIGHTNING_INITIAL, LIGHTNING_SECONDARY, LIGHTNING_FINAL],
+            fire: [FIRE_PENDING, FIRE_INITIAL, FIRE_SECONDARY, FIRE_TERTIARY, FIRE_FINAL],
+            laser_boss: LASER_BOSS,
+            column_boss: COLUMN_BOSS,
+            chasing_boss: CHASING_BOSS,
+            black_out_boss: BLACK_OUT_BOSS,
+            major_boss: MAJOR_BOSS,
+            boss_surroundings: BOSS_SURROUNDINGS,
+            boss_vulnerable: BOSS_VULNERABLE,
+            boss_recovery: [BOSS_RECOVERY_ONE, BOSS_RECOVERY_TWO],
+            boss_laser: [BOSS_LASER_STAGE_1, BOSS_LASER_STAGE_2, BOSS_LASER_REAL],
+            boss_asteroid: [
+                BOSS_ASTEROID_STAGE_1,
+                BOSS_ASTEROID_STAGE_2,
+                BOSS_ASTEROID_STAGE_3,
+                BOSS_ASTEROID_REAL,
+            ],
+            stun_well: [STUN_WELL_INDICATOR, STUN_WELL_REAL],
+            rush_trail: FIRE_TERTIARY,
+            safe_spot: SAFE_SPOT_INDICATOR,
+            map_player: MAP_PLAYER,
+            map_boss_room: MAP_BOSS_ROOM,
+            map_elite_room: MAP_ELITE_ROOM,
+        }
+    }
+}
+
+impl Palette {
+    pub fn terrain(&self, kind: TerrainKind) -> [f32; 4] {
+        match kind {
+            TerrainKind::Wall => self.wall,
+            TerrainKind::Portal => self.portal,
+            TerrainKind::Water => self.water,
+            TerrainKind::Lava => self.lava,
+            TerrainKind::Grass => self.grass,
+            TerrainKind::Mountain(shade) => self.mountain[(shade as usize).min(4)],
+        }
+    }
+
+    // The player is drawn as it is, see World::draw
+    pub fn tile(&self, kind: TileKind) -> [f32; 4] {
+        match kind {
+            TileKind::Player => self.player,
+            TileKind::Enemy(kind) => self.enemy(kind),
+            TileKind::Projectile(kind) => self.projectile(kind),
+            TileKind::Structure => self.structure,
+        }
+    }
+
+    pub fn enemy(&self, kind: EnemyKind) -> [f32; 4] {
+        match kind {
+            EnemyKind::Chaser => self.chasing_enemy,
+            EnemyKind::Bomber => self.bomber_enemy,
+            EnemyKind::BomberActivated => self.bomber_enemy_activated,
+            EnemyKind::BomberDeactivated => self.bomber_enemy_deactivated,
+            EnemyKind::Shooter => self.shooter_enemy,
+            EnemyKind::Knight => self.knight_enemy,
+            EnemyKind::Major => self.major_enemy,
+            EnemyKind::MajorBoss => self.major_boss,
+        }
+    }
+
+    pub fn projectile(&self, kind: ProjectileKind) -> [f32; 4] {
+        match kind {
+            ProjectileKind::Player => self.projectile_player,
+            ProjectileKind::Tracking => self.tracking_projectile,
+            ProjectileKind::Enemy => self.projectile_enemy,
+            ProjectileKind::LightningPending => self.lightning[0],
+            ProjectileKind::LightningInitial => self.lightning[1],
+            ProjectileKind::LightningSecondary => self.lightning[2],
+            ProjectileKind::LightningFinal => self.lightning[3],
+            ProjectileKind::FirePending => self.fire[0],
+            ProjectileKind::FireInitial => self.fire[1],
+            ProjectileKind::FireSecondary => self.fire[2],
+            ProjectileKind::FireTertiary => self.fire[3],
+            ProjectileKind::FireFinal => self.fire[4],
+        }
+    }
+
+    pub fn boss(&self, kind: BossKind) -> [f32; 4] {
+        match kind {
+            BossKind::Laser => self.laser_boss,
+            BossKind::Column => self.column_boss,
+            BossKind::Chasing => self.chasing_boss,
+            BossKind::BlackOut => self.black_out_boss,
+            BossKind::Major => self.major_boss,
+        }
+    }
+
+    pub fn boss_laser(&self, stage: AttackStage) -> [f32; 4] {
+        Self::stage(&self.boss_laser, stage)
+    }
+
+    pub fn boss_asteroid(&self, stage: AttackStage) -> [f32; 4] {
+        Self::stage(&self.boss_asteroid, stage)
+    }
+
+    pub fn stun_well(&self, stage: AttackStage) -> [f32; 4] {
+        Self::stage(&self.stun_well, stage)
+    }
+
+    // The warning colors in order, then the active one
+    fn stage(colors: &[[f32; 4]], stage: AttackStage) -> [f32; 4] {
+        match stage {
+            AttackStage::Warning(turn) => colors[(turn as usize).min(colors.len() - 2)],
+            AttackStage::Active => colors[colors.len() - 1],
+        }
+    }
+}
@@ -1,7 +1,15 @@
 use crate::{
-    direction::Direction, enemy::Enemy, entity::Entity, random, tile, world::World,
-    world::BOSS_ROOMS, BOARD_SIZE, TILE_SIZE, UNIVERSAL_OFFSET, WORLD_SIZE, 
-    player::MAX_PLAYER_HEALTH,
+    balance::BossBalance,
+    damage::{Damage, DamageKind},
+    direction::Direction,
+    enemy::Enemy,
+    entity::Entity,
+    random,
+    status::{StatusEffect, StatusEffects, StatusKind},
+    tile::{AttackStage, BossKind, EnemyKind, Palette, TerrainKind, TileKind},
+    world::World,
+    world::BOSS_ROOMS,
+    BOARD_SIZE, TILE_SIZE, UNIVERSAL_OFFSET, WORLD_SIZE,
 };
 use ggez::{graphics::{self, Canvas}, mint::Point2, glam::*};
 use rand::rngs;
@@ -10,31 +18,17 @@ use rand_chacha::ChaCha8Rng;
 use std::{cmp::max, collections::HashMap};
 use ggez::glam::*;
 
-pub const BOSS_HEALTH: usize = 1000;
-pub const MAJOR_BOSS_HEALTH: usize = 5000;
-const LASER_LINGER_VALUE: usize = 3;
-const ASTEROID_LINGER_VALUE: usize = 5;
-const ASTEROID_COOLDOWN: usize = 20;
-const STUN_WELL_COOLDOWN: usize = 10;
-const STUN_WELL_LINGER_VALUE: usize = 50;
-const LASER_DAMAGE: usize = 5;
-const COLUMN_LASER_DAMAGE: usize = 20;
-const LASER_AMOUNT: usize = 7;
-const ASTEROID_DAMAGE: usize = 10;
-const VULNERABLE_TIME_BASE: usize = 15;
-const BOSS_3_RUSH_COOLDOWN: usize = 20;
-const BOSS_3_MOVE_DELAY: usize = 2;
-const SAFE_SPOT_ATTACK_COOLDOWN: usize = 20;
-const SAFE_SPOT_TIME: usize = 10;
-const BOSS_COLLISION_DAMAGE: usize = 10;
-const STUN_WELL_STUN_TIME: usize = 2;
-const SHIELD_HITS_NEEDED: usize = 3;
-const ENEMY_SPAWN_COOLDOWN: usize = 10;
+// How many turns each attack is shown before it hits
+const LASER_WARNING_TURNS: u8 = 2;
+const ASTEROID_WARNING_TURNS: u8 = 3;
+const STUN_WELL_WARNING_TURNS: u8 = 1;
+const RUSH_WARNING_TURNS: u8 = 2;
 
 #[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq)]
 pub struct Boss {
     pub position: Position,
-    pub color: [f32; 4],
+    #[serde(default)]
+    pub kind: BossKind,
     pub world_position: Position,
     pub health: usize,
     pub laser_amount: usize,
@@ -45,50 +39,65 @@ pub struct Boss {
     pub is_major: bool,
     pub offset: usize,
     pub boss_can_attack: bool,
-    pub rush_info: (bool, Option<Direction>, [f32; 4], usize), //is rushing, direction, color of rush indicator, length of indicator
+    #[serde(rename = "rush", default)]
+    pub rush_info: (bool, Option<Direction>, AttackStage, usize), //is rushing, direction, stage of rush indicator, length of indicator
     pub vulnerable_time: usize,
     pub shield_health: usize,
     pub chase_rush_cooldown: usize,
     pub speed_delay: usize,
     pub first_enter: bool,
+    // Burns and every other effect on the boss
+    #[serde(default)]
+    pub status: StatusEffects,
+
+    // Saves up to version 2 stored colors instead of the kind and the rush stage, the migration
+    // converts them
+    #[serde(rename = "color", default, skip_serializing)]
+    pub legacy_color: [f32; 4],
+    #[serde(rename = "rush_info", default, skip_serializing)]
+    pub legacy_rush_info: (bool, Option<Direction>, [f32; 4], usize),
 }
 
 impl Boss {
     pub fn new(
         x: usize,
         y: usize,
-        color: [f32; 4],
+        kind: BossKind,
         world_position: Position,
-        terrain_loc: &mut HashMap<Position, [f32; 4]>,
+        terrain_loc: &mut HashMap<Position, TerrainKind>,
+        balance: &BossBalance,
     ) -> Self {
         let mut offset: usize = 4;
-        let is_major: bool = color == tile::MAJOR_BOSS;
-        let mut health = BOSS_HEALTH;
+        let is_major: bool = kind == BossKind::Major;
+        let mut health = balance.health;
         if is_major {
             offset = 5;
         }
-        if color == tile::MAJOR_BOSS {
-            health = MAJOR_BOSS_HEALTH;
+        if kind == BossKind::Major {
+            health = balance.major_health;
         }
         Boss {
             position: Position::new(x, y),
-            color,
+            kind,
             world_position,
             health,
-            laser_amount: LASER_AMOUNT,
-            safe_spot_cooldown: SAFE_SPOT_ATTACK_COOLDOWN,
-            asteroid_cooldown: ASTEROID_COOLDOWN,
-            stun_well_cooldown: STUN_WELL_COOLDOWN,
+            laser_amount: balance.laser_amount,
+            safe_spot_cooldown: balance.safe_spot_attack_cooldown,
+            asteroid_cooldown: balance.asteroid_cooldown,
+            stun_well_cooldown: balance.stun_well_cooldown,
             enemy_spawn_cooldown: 0,
             is_major,
             offset,
             first_enter: true,
-            rush_info: (false, None, tile::BOSS_LASER_STAGE_1, 0),
+            status: StatusEffects::default(),
+            rush_info: (false, None, AttackStage::default(), 0),
             boss_can_attack: true,
             vulnerable_time: 0,
-            shield_health: SHIELD_HITS_NEEDED,
-            chase_rush_cooldown: BOSS_3_RUSH_COOLDOWN,
-            speed_delay: BOSS_3_MOVE_DELAY,
+            shield_health: balance.shield_hits_needed,
+            chase_rush_cooldown: balance.rush_cooldown,
+            speed_delay: balance.move_delay,
+            legacy_color: [0.0; 4],
+            legacy_rush_info: (false, None, [0.0; 4], 0),
         }
     }
 
@@ -97,7 +106,7 @@ impl Boss {
         if world.world_position == BOSS_ROOMS[0] {
             if curr_entity_map.is_empty() {
                 if world.bosses[index].vulnerable_time == 0 && world.bosses[index].boss_can_attack && !world.bosses[index].first_enter{
-                    world.bosses[index].vulnerable_time = VULNERABLE_TIME_BASE + 10;
+                    world.bosses[index].vulnerable_time = world.balance.bosses.vulnerable_time_base + 10;
                     world.bosses[index].boss_can_attack = false;
                 } else if world.bosses[index].vulnerable_time != 0 {
                     world.bosses[index].vulnerable_time -= 1;
@@ -119,11 +128,11 @@ impl Boss {
                             world.enemies_map[world.world_position.y as usize][world.world_position.x as usize].push(Enemy::major_enemy(
                                 pos.x as usize,
                                 pos.y as usize,
-                                world.world_position),);
+                                world.world_position, &world.balance.enemies),);
                             for h in 0..3 {
                                 for j in 0..3 {
                                     world.entity_map[world.world_position.y][world.world_position.x].insert(
-                                        Position::new(pos.x as usize + h, pos.y as usize + j), (tile::MAJOR_ENEMY, Entity::Enemy));
+                                        Position::new(pos.x as usize + h, pos.y as usize + j), (TileKind::Enemy(EnemyKind::Major), Entity::Enemy));
                                 }
                             }
 
@@ -131,8 +140,8 @@ impl Boss {
                             world.enemies_map[world.world_position.y as usize][world.world_position.x as usize].push(Enemy::bomber(
                                 pos.x as usize,
                                 pos.y as usize,
-                                world.world_position),);
-                            world.entity_map[world.world_position.y][world.world_position.x].insert(pos, (tile::BOMBER_ENEMY, Entity::Enemy));
+                                world.world_position, &world.balance.enemies),);
+                            world.entity_map[world.world_position.y][world.world_position.x].insert(pos, (TileKind::Enemy(EnemyKind::Bomber), Entity::Enemy));
                         }
                     }
                 }
@@ -154,11 +163,11 @@ impl Boss {
                         world.enemies_map[world.world_position.y as usize][world.world_position.x as usize].push(Enemy::major_enemy(
                             pos.x as usize,
                             pos.y as usize,
-                            world.world_position),);
+                            world.world_position, &world.balance.enemies),);
                         for h in 0..3 {
                             for j in 0..3 {
                                 world.entity_map[world.world_position.y][world.world_position.x].insert(
-                                    Position::new(pos.x as usize + h, pos.y as usize + j), (tile::MAJOR_ENEMY, Entity::Enemy));
+                                    Position::new(pos.x as usize + h, pos.y as usize + j), (TileKind::Enemy(EnemyKind::Major), Entity::Enemy));
                             }
                         }
 
@@ -166,11 +175,11 @@ impl Boss {
                         world.enemies_map[world.world_position.y as usize][world.world_position.x as usize].push(Enemy::chasing(
                             pos.x as usize,
                             pos.y as usize,
-                            world.world_position),);
-                        world.entity_map[world.world_position.y][world.world_position.x].insert(pos, (tile::CHASING_ENEMY, Entity::Enemy));
+                            world.world_position, &world.balance.enemies),);
+                        world.entity_map[world.world_position.y][world.world_position.x].insert(pos, (TileKind::Enemy(EnemyKind::Chaser), Entity::Enemy));
                     }
                 }
-                world.bosses[index].enemy_spawn_cooldown = ENEMY_SPAWN_COOLDOWN;
+                world.bosses[index].enemy_spawn_cooldown = world.balance.bosses.enemy_spawn_cooldown;
             } else {
                 if world.bosses[index].enemy_spawn_cooldown != 0 {
                     world.bosses[index].enemy_spawn_cooldown -= 1;
@@ -192,10 +201,10 @@ impl Boss {
                     world.enemies_map[world.world_position.y as usize][world.world_position.x as usize].push(Enemy::chasing(
                         pos.x as usize,
                         pos.y as usize,
-                        world.world_position),);
-                    world.entity_map[world.world_position.y][world.world_position.x].insert(pos, (tile::CHASING_ENEMY, Entity::Enemy));
+                        world.world_position, &world.balance.enemies),);
+                    world.entity_map[world.world_position.y][world.world_position.x].insert(pos, (TileKind::Enemy(EnemyKind::Chaser), Entity::Enemy));
                 }
-                world.bosses[index].enemy_spawn_cooldown = ENEMY_SPAWN_COOLDOWN;
+                world.bosses[index].enemy_spawn_cooldown = world.balance.bosses.enemy_spawn_cooldown;
             } else {
                 if world.bosses[index].enemy_spawn_cooldown != 0 {
                     world.bosses[index].enemy_spawn_cooldown -= 1;
@@ -218,11 +227,11 @@ impl Boss {
                         world.enemies_map[world.world_position.y as usize][world.world_position.x as usize].push(Enemy::major_enemy(
                             pos.x as usize,
                             pos.y as usize,
-                            world.world_position),);
+                            world.world_position, &world.balance.enemies),);
                         for h in 0..3 {
                             for j in 0..3 {
                                 world.entity_map[world.world_position.y][world.world_position.x].insert(
-                                    Position::new(pos.x as usize + h, pos.y as usize + j), (tile::MAJOR_ENEMY, Entity::Enemy));
+                                    Position::new(pos.x as usize + h, pos.y as usize + j), (TileKind::Enemy(EnemyKind::Major), Entity::Enemy));
                             }
                         }
 
@@ -230,19 +239,19 @@ impl Boss {
                         world.enemies_map[world.world_position.y as usize][world.world_position.x as usize].push(Enemy::chasing(
                             pos.x as usize,
                             pos.y as usize,
-                            world.world_position),);
-                        world.entity_map[world.world_position.y][world.world_position.x].insert(pos, (tile::CHASING_ENEMY, Entity::Enemy));
+                            world.world_position, &world.balance.enemies),);
+                        world.entity_map[world.world_position.y][world.world_position.x].insert(pos, (TileKind::Enemy(EnemyKind::Chaser), Entity::Enemy));
 
                     } else {
                         world.enemies_map[world.world_position.y as usize][world.world_position.x as usize].push(Enemy::bomber(
                             pos.x as usize,
                             pos.y as usize,
-                            world.world_position),);
-                        world.entity_map[world.world_position.y][world.world_position.x].insert(pos, (tile::BOMBER_ENEMY, Entity::Enemy));
+                            world.world_position, &world.balance.enemies),);
+                        world.entity_map[world.world_position.y][world.world_position.x].insert(pos, (TileKind::Enemy(EnemyKind::Bomber), Entity::Enemy));
 
                     }
                 } 
-                world.bosses[index].enemy_spawn_cooldown = ENEMY_SPAWN_COOLDOWN;
+                world.bosses[index].enemy_spawn_cooldown = world.balance.bosses.enemy_spawn_cooldown;
             } else {
                 if world.bosses[index].enemy_spawn_cooldown != 0 {
                     world.bosses[index].enemy_spawn_cooldown -= 1;
@@ -254,7 +263,13 @@ impl Boss {
     pub fn update(world: &mut World, rng: &mut ChaCha8Rng) {
         for index in (0..world.bosses.len()).rev() {
             if world.bosses[index].world_position == world.world_position {
-                Self::attack(world, index, rng);
+                let can_act = world.bosses[index].status.can_act();
+                for damage in world.bosses[index].status.tick() {
+                    Self::damage(world, damage, world.world_position);
+                }
+                if can_act {
+                    Self::attack(world, index, rng);
+                }
             }
             if world.bosses[index].health <= 0 {
                 Self::kill(world, index);
@@ -262,26 +277,26 @@ impl Boss {
         }
     }
 
-    pub fn draw_boss(world: &mut World, canvas: &mut graphics::Canvas, index: usize) {
+    pub fn draw_boss(world: &mut World, canvas: &mut graphics::Canvas, index: usize, palette: &Palette) {
         let boss_size = (world.bosses[index].offset - 1) as i32;
         for i in -boss_size..=boss_size {
             for j in -boss_size..=boss_size {
                 let pos = world.bosses[index].position;
-                let mut color = world.bosses[index].color;
+                let mut color = palette.boss(world.bosses[index].kind);
                 if i == -boss_size || j == -boss_size || i == boss_size || j == boss_size {
                     if world.bosses[index].vulnerable_time > 2 {
-                        color = tile::BOSS_VULNERABLE;
+                        color = palette.boss_vulnerable;
                     } else if world.bosses[index].vulnerable_time == 2 {
-                        color = tile::BOSS_RECOVERY_ONE;
+                        color = palette.boss_recovery[0];
                     } else if world.bosses[index].vulnerable_time == 1 {
-                        color = tile::BOSS_RECOVERY_TWO;
+                        color = palette.boss_recovery[1];
                     } else {
-                        color = tile::BOSS_SURROUNDINGS;
+                        color = palette.boss_surroundings;
                     }
                 }
                 if let Some(spot) = world.boss_vulnerable_spot {
                     if Position::new((pos.x as i32 + i) as usize, (pos.y as i32+ j) as usize) == spot {
-                        color = tile::BOSS_VULNERABLE
+                        color = palette.boss_vulnerable
                     }
                 }
 
@@ -303,24 +318,24 @@ impl Boss {
         }
     }
 
-    pub fn draw_boss_stuff(world: &mut World, canvas: &mut graphics::Canvas, index: usize, rng: &mut ChaCha8Rng) {
+    pub fn draw_boss_stuff(world: &mut World, canvas: &mut graphics::Canvas, index: usize, rng: &mut ChaCha8Rng, palette: &Palette) {
         if world.world_position == BOSS_ROOMS[0] {
-            Self::draw_lasers(world, canvas, rng);
+            Self::draw_lasers(world, canvas, rng, palette);
         } else if world.world_position == BOSS_ROOMS[1] {
-            Self::draw_laser_column(world, index, canvas);
-            Self::draw_asteroids(world, index, canvas);
+            Self::draw_laser_column(world, index, canvas, palette);
+            Self::draw_asteroids(world, index, canvas, palette);
         } else if world.world_position == BOSS_ROOMS[3] {
             if world.bosses[index].rush_info.0 {
-                Self::draw_rush(world, index, world.bosses[index].rush_info.3, canvas);
+                Self::draw_rush(world, index, world.bosses[index].rush_info.3, canvas, palette);
             }
-            Self::draw_stun_wells(world, canvas);
+            Self::draw_stun_wells(world, canvas, palette);
         } else if world.world_position == BOSS_ROOMS[4] {
-            Self::draw_safe_spot(world, canvas);
+            Self::draw_safe_spot(world, canvas, palette);
         } else if world.world_position == BOSS_ROOMS[2] {
-            Self::draw_stun_wells(world, canvas);
-            Self::draw_laser_column(world, index, canvas);
-            Self::draw_asteroids(world, index, canvas);
-            Self::draw_lasers(world, canvas, rng);
+            Self::draw_stun_wells(world, canvas, palette);
+            Self::draw_laser_column(world, index, canvas, palette);
+            Self::draw_asteroids(world, index, canvas, palette);
+            Self::draw_lasers(world, canvas, rng, palette);
         }  
     }
 
@@ -378,15 +393,11 @@ impl Boss {
         let lasers = &mut world.boss_lasers;
         for index in (0..lasers.len()).rev() {
             match lasers[index].1 {
-                tile::BOSS_LASER_STAGE_1 => {
-                    lasers[index].1 = tile::BOSS_LASER_STAGE_2;
+                AttackStage::Warning(_) => {
+                    lasers[index].1 = lasers[index].1.advance(LASER_WARNING_TURNS);
                 },
 
-                tile::BOSS_LASER_STAGE_2 => {
-                    lasers[index].1 = tile::BOSS_LASER_REAL;
-                },
-
-                _ => {
+                AttackStage::Active => {
                     if lasers[index].2 == 0 {
                         lasers.remove(index);
                     } else {
@@ -404,11 +415,11 @@ impl Boss {
             };
             world
                 .boss_lasers
-                .push((coord, tile::BOSS_LASER_STAGE_1, LASER_LINGER_VALUE));
+                .push((coord, AttackStage::default(), world.balance.bosses.laser_linger));
         }
     }
 
-    pub fn draw_lasers(world: &mut World, canvas: &mut Canvas, rng: &mut ChaCha8Rng) {
+    pub fn draw_lasers(world: &mut World, canvas: &mut Canvas, rng: &mut ChaCha8Rng, palette: &Palette) {
          for lasers in &mut world.boss_lasers {
             let mut special_case = true;
             if lasers.0 == Position::new(0,0) {
@@ -428,7 +439,7 @@ impl Boss {
                                 TILE_SIZE.0 as i32,
                                 TILE_SIZE.1 as i32,
                             ))
-                            .color(lasers.1),
+                            .color(palette.boss_laser(lasers.1)),
                     )
                 }
             } else {
@@ -442,7 +453,7 @@ impl Boss {
                                 TILE_SIZE.0 as i32,
                                 TILE_SIZE.1 as i32,
                             ))
-                            .color(lasers.1),
+                            .color(palette.boss_laser(lasers.1)),
                     )
                 }
             }
@@ -453,17 +464,17 @@ impl Boss {
         for laser in &world.boss_lasers {
             if (world.player.pos.x == laser.0.x
             || world.player.pos.y == laser.0.y)
-            && laser.1 == tile::BOSS_LASER_REAL
+            && laser.1 == AttackStage::Active
             && world.player.pos.y != 0
             && world.player.pos.x != 0
             && world.player.pos.y != WORLD_SIZE.1 as usize - 1
             && world.player.pos.x != WORLD_SIZE.0 as usize - 1 {
-                world.player.damage(LASER_DAMAGE);
+                world.player.damage(Damage::new(world.balance.bosses.laser_damage, DamageKind::Laser));
             }
         }
     }
     
-    pub fn draw_asteroids(world: &mut World, index: usize, canvas: &mut Canvas) {
+    pub fn draw_asteroids(world: &mut World, index: usize, canvas: &mut Canvas, palette: &Palette) {
         for asteroids in &mut world.boss_asteroids {
             for i in 0..=2 {
                 for j in 0..=2 {
@@ -476,7 +487,7 @@ impl Boss {
                                 TILE_SIZE.0 as i32,
                                 TILE_SIZE.1 as i32,
                             ))
-                            .color(asteroids.1),
+                            .color(palette.boss_asteroid(asteroids.1)),
                     )
                 }
             }
@@ -488,19 +499,11 @@ impl Boss {
         let cooldown = world.bosses[index].asteroid_cooldown;
         for index in (0..asteroids.len()).rev() {
             match asteroids[index].1 {
-                tile::BOSS_ASTEROID_STAGE_1 => {
-                    asteroids[index].1 = tile::BOSS_ASTEROID_STAGE_2;
-                },
-
-                tile::BOSS_ASTEROID_STAGE_2 => {
-                    asteroids[index].1 = tile::BOSS_ASTEROID_STAGE_3;
-                },
-
-                tile::BOSS_ASTEROID_STAGE_3 => {
-                    asteroids[index].1 = tile::BOSS_ASTEROID_REAL;
+                AttackStage::Warning(_) => {
+                    asteroids[index].1 = asteroids[index].1.advance(ASTEROID_WARNING_TURNS);
                 },
 
-                _ => {
+                AttackStage::Active => {
                     if asteroids[index].2 == 0 {
                         asteroids.remove(index);
                     } else {
@@ -513,10 +516,10 @@ impl Boss {
         if cooldown == 0 {
             world.boss_asteroids.push((
                 world.player.pos,
-                tile::BOSS_ASTEROID_STAGE_1,
-                ASTEROID_LINGER_VALUE,
+                AttackStage::default(),
+                world.balance.bosses.asteroid_linger,
             ));
-            world.bosses[index].asteroid_cooldown = ASTEROID_COOLDOWN;
+            world.bosses[index].asteroid_cooldown = world.balance.bosses.asteroid_cooldown;
         } else {
             world.bosses[index].asteroid_cooldown -= 1;
         }
@@ -528,13 +531,13 @@ impl Boss {
             && world.player.pos.x >= asteroid.0.x - 1
             && world.player.pos.y <= asteroid.0.y + 1
             && world.player.pos.y >= asteroid.0.y - 1)
-            && asteroid.1 == tile::BOSS_ASTEROID_REAL {
-                world.player.damage(ASTEROID_DAMAGE);
+            && asteroid.1 == AttackStage::Active {
+                world.player.damage(Damage::physical(world.balance.bosses.asteroid_damage));
             }
         }
     }
     
-    pub fn draw_laser_column(world: &mut World, index: usize, canvas: &mut Canvas) {
+    pub fn draw_laser_column(world: &mut World, index: usize, canvas: &mut Canvas, palette: &Palette) {
         let boss_pos = world.bosses[index].position;
         let mut laser_width: i32 = -2;
         if world.bosses[index].is_major {
@@ -568,7 +571,7 @@ impl Boss {
                                 TILE_SIZE.0 as i32,
                                 TILE_SIZE.1 as i32,
                             ))
-                            .color(tile::BOSS_LASER_REAL),
+                            .color(palette.boss_laser(AttackStage::Active)),
                     )
                 }
             }
@@ -694,7 +697,7 @@ impl Boss {
             }
 
             if player_in_laser {
-                world.player.damage(COLUMN_LASER_DAMAGE);
+                world.player.damage(Damage::new(world.balance.bosses.column_laser_damage, DamageKind::Laser));
             }
         }
     }
@@ -719,7 +722,7 @@ impl Boss {
             } else {
                 if !world.bosses[index].boss_can_attack {
                     world.bosses[index].boss_can_attack = true;
-                    world.bosses[index].vulnerable_time = VULNERABLE_TIME_BASE;
+                    world.bosses[index].vulnerable_time = world.balance.bosses.vulnerable_time_base;
                 }
                 world.bosses[index].vulnerable_time -= 1;
             }
@@ -728,7 +731,7 @@ impl Boss {
 
     pub fn generate_stun_well(world: &mut World, index: usize, rng: &mut ChaCha8Rng) {
         if world.bosses[index].stun_well_cooldown == 0 {
-            world.bosses[index].stun_well_cooldown = STUN_WELL_COOLDOWN;
+            world.bosses[index].stun_well_cooldown = world.balance.bosses.stun_well_cooldown;
             let mut well_size = 2;
             if Self::coin_flip(rng) {
                 well_size = 3;
@@ -744,16 +747,19 @@ impl Boss {
             }
             world.stun_wells.push((
                 pos,
-                tile::STUN_WELL_INDICATOR,
+                AttackStage::default(),
                 well_size,
-                STUN_WELL_LINGER_VALUE,
+                world.balance.bosses.stun_well_linger,
                 false,
             ));
         } else {
             for index in (0..world.stun_wells.len()).rev() {
                 let well = world.stun_wells[index];
-                if well.1 == tile::STUN_WELL_INDICATOR {
-                    world.stun_wells[index].1 = tile::STUN_WELL_REAL;
+                if well.1 != AttackStage::Active {
+                    world.stun_wells[index].1 = well.1.advance(STUN_WELL_WARNING_TURNS);
+                    if world.stun_wells[index].1 == AttackStage::Active {
+                        Self::stun_enemies_in_well(world, index);
+                    }
                 } else {
                     if well.3 == 0 {
                         world.stun_wells.remove(index);
@@ -766,7 +772,7 @@ impl Boss {
         }
     }
 
-    pub fn draw_stun_wells(world: &mut World, canvas: &mut Canvas) {
+    pub fn draw_stun_wells(world: &mut World, canvas: &mut Canvas, palette: &Palette) {
         for well in &world.stun_wells {
             let len = well.2 as i32;
             let pos = well.0;
@@ -781,13 +787,28 @@ impl Boss {
                                 TILE_SIZE.0 as i32,
                                 TILE_SIZE.1 as i32,
                             ))
-                            .color(well.1),
+                            .color(palette.stun_well(well.1)),
                     )
                 }
             }
         }
     }
 
+    // Enemies caught in a stun well when it goes off are stunned like the player. Only then, so
+    // an enemy wandering into a well that lingers isn't stuck in it
+    pub fn stun_enemies_in_well(world: &mut World, index: usize) {
+        let (well_pos, _, size, _, _) = world.stun_wells[index];
+        let stun = StatusEffect::new(StatusKind::Stun, world.balance.bosses.stun_well_stun_time);
+        for enemy in &mut world.enemies_map[world.world_position.y][world.world_position.x] {
+            if enemy.pos.iter().any(|pos| {
+                pos.x + size >= well_pos.x && pos.x <= well_pos.x + size &&
+                pos.y + size >= well_pos.y && pos.y <= well_pos.y + size
+            }) {
+                enemy.status.apply(stun);
+            }
+        }
+    }
+
     pub fn check_stun_well_stun(world: &mut World) {
         let pos = world.player.pos;
         for index in 0..world.stun_wells.len() {
@@ -796,7 +817,10 @@ impl Boss {
             if pos.x >= (well_pos.x - size) && pos.x <= (well_pos.x + size) &&
             pos.y >= (well_pos.y - size) && pos.y <= (well_pos.y + size) {
                 if !world.stun_wells[index].4 {
-                    world.player.stun_timer = STUN_WELL_STUN_TIME;
+                    world.player.status.apply(StatusEffect::new(
+                        StatusKind::Stun,
+                        world.balance.bosses.stun_well_stun_time,
+                    ));
                     world.stun_wells[index].4 = true;
                 }
             } else {
@@ -805,7 +829,7 @@ impl Boss {
         }
     }
     
-    pub fn draw_safe_spot(world: &mut World, canvas: &mut Canvas) {
+    pub fn draw_safe_spot(world: &mut World, canvas: &mut Canvas, palette: &Palette) {
         if let Some(spot) = world.boss_safe_spot {
             let size = spot.1 as i32;
             let pos = spot.0;
@@ -844,7 +868,7 @@ impl Boss {
                                 TILE_SIZE.0 as i32,
                                 TILE_SIZE.1 as i32,
                             ))
-                            .color(tile::SAFE_SPOT_INDICATOR),
+                            .color(palette.safe_spot),
                     )
                 }
             }
@@ -861,7 +885,7 @@ impl Boss {
         if let Some(spot) = &mut world.boss_safe_spot {
             if spot.2 == 0 {
                 world.boss_safe_spot = None;
-                world.bosses[index].safe_spot_cooldown = SAFE_SPOT_ATTACK_COOLDOWN;
+                world.bosses[index].safe_spot_cooldown = world.balance.bosses.safe_spot_attack_cooldown;
                 world.in_blackout = false;
             } else {
                 spot.2 -= 1;
@@ -887,7 +911,7 @@ impl Boss {
                 pos = Position::new(x,y);
             }
         
-            world.boss_safe_spot = Some((pos, size, SAFE_SPOT_TIME));
+            world.boss_safe_spot = Some((pos, size, world.balance.bosses.safe_spot_time));
         } else {
             world.bosses[index].safe_spot_cooldown -= 1;
         }
@@ -903,7 +927,7 @@ impl Boss {
                 let size = spot.1;
                 if !(pos.x >= (spot_pos.x - size) && pos.x <= (spot_pos.x + size) &&
                 pos.y >= (spot_pos.y - size) && pos.y <= (spot_pos.y + size)) {
-                    world.player.damage(MAX_PLAYER_HEALTH);
+                    world.player.damage(Damage::physical(world.balance.player.max_health));
                 }
             }
         }
@@ -938,8 +962,8 @@ impl Boss {
                 world.boss_vulnerable_spot = Some(vulnerable_spot);
 
                 if world.bosses[index].shield_health == 0 {
-                    world.bosses[index].vulnerable_time = VULNERABLE_TIME_BASE;
-                    world.bosses[index].shield_health = SHIELD_HITS_NEEDED;
+                    world.bosses[index].vulnerable_time = world.balance.bosses.vulnerable_time_base;
+                    world.bosses[index].shield_health = world.balance.bosses.shield_hits_needed;
                     world.boss_vulnerable_spot = None
                 } 
                 
@@ -978,7 +1002,7 @@ impl Boss {
                     Self::move_boss(world, index, boss_pos, Direction::North);
                 }
             }
-            world.bosses[index].speed_delay = BOSS_3_MOVE_DELAY;
+            world.bosses[index].speed_delay = world.balance.bosses.move_delay;
             if (world.bosses[index].chase_rush_cooldown != 0) {
                 world.bosses[index].chase_rush_cooldown -= 1;
             }
@@ -1024,15 +1048,11 @@ impl Boss {
             let offset = world.bosses[index].offset;
             let mut length: usize;
             match stage {
-                tile::BOSS_LASER_STAGE_1 => {
-                    world.bosses[index].rush_info.2 = tile::BOSS_LASER_STAGE_2;
-                }
-
-                tile::BOSS_LASER_STAGE_2 => {
-                    world.bosses[index].rush_info.2 = tile::BOSS_LASER_REAL;
+                AttackStage::Warning(_) => {
+                    world.bosses[index].rush_info.2 = stage.advance(RUSH_WARNING_TURNS);
                 }
 
-                _ => {
+                AttackStage::Active => {
                     if let Some(direction) = world.bosses[index].rush_info.1 {
                         let player_pos = world.player.pos;
                         let boss_pos = world.bosses[index].position;
@@ -1042,7 +1062,7 @@ impl Boss {
                                 if player_pos.x <= boss_pos.x + len && player_pos.x >= boss_pos.x - len &&
                                 player_pos.y <= boss_pos.y 
                                 {
-                                    world.player.damage(MAX_PLAYER_HEALTH);
+                                    world.player.damage(Damage::physical(world.balance.player.max_health));
                                 } else {
                                     world.bosses[index].position.y = 0 + offset;
                                 }
@@ -1052,7 +1072,7 @@ impl Boss {
                                 if player_pos.x <= boss_pos.x + len && player_pos.x >= boss_pos.x - len &&
                                 player_pos.y >= boss_pos.y 
                                 {
-                                    world.player.damage(MAX_PLAYER_HEALTH);
+                                    world.player.damage(Damage::physical(world.balance.player.max_health));
                                 } else {
                                     world.bosses[index].position.y = WORLD_SIZE.1 as usize - offset - 1;
                                 }
@@ -1062,7 +1082,7 @@ impl Boss {
                                 if player_pos.y <= boss_pos.y + len && player_pos.y >= boss_pos.y - len &&
                                 player_pos.x <= boss_pos.x
                                 {
-                                    world.player.damage(MAX_PLAYER_HEALTH);
+                                    world.player.damage(Damage::physical(world.balance.player.max_health));
                                 } else {
                                     world.bosses[index].position.x = 0 + offset;
                                 }
@@ -1072,35 +1092,35 @@ impl Boss {
                                 if player_pos.y <= boss_pos.y + len && player_pos.y >= boss_pos.y - len &&
                                 player_pos.x >= boss_pos.x
                                 {
-                                    world.player.damage(MAX_PLAYER_HEALTH);
+                                    world.player.damage(Damage::physical(world.balance.player.max_health));
                                 } else {
                                     world.bosses[index].position.x = WORLD_SIZE.0 as usize - 1 - offset;
                                 }
                             }
                         }
                     }
-                    world.bosses[index].chase_rush_cooldown = BOSS_3_RUSH_COOLDOWN;
-                    world.bosses[index].rush_info.2 = tile::BOSS_LASER_STAGE_1;
-                    world.bosses[index].vulnerable_time = VULNERABLE_TIME_BASE;
+                    world.bosses[index].chase_rush_cooldown = world.balance.bosses.rush_cooldown;
+                    world.bosses[index].rush_info.2 = AttackStage::default();
+                    world.bosses[index].vulnerable_time = world.balance.bosses.vulnerable_time_base;
                     world.bosses[index].boss_can_attack = false;
                 }
             }
         }
     }
 
-    pub fn draw_rush(world: &mut World, index: usize, distance: usize, canvas: &mut Canvas) {
+    pub fn draw_rush(world: &mut World, index: usize, distance: usize, canvas: &mut Canvas, palette: &Palette) {
         if let Some(direction) = world.bosses[index].rush_info.1 {
             let mut rush_dist = distance;
             let after_rush = !world.bosses[index].boss_can_attack;
             let mut width: i32 = -3;
-            let mut color = world.bosses[index].rush_info.2;
+            let mut color = palette.boss_laser(world.bosses[index].rush_info.2);
             if world.bosses[index].offset == 5 {
                 width = -3;
             }
             if after_rush {
                 world.bosses[index].rush_info.0 = false;
                 // rush_dist = WORLD_SIZE.0 as usize - world.bosses[index].offset;
-                color = tile::FIRE_TERTIARY;
+                color = palette.rush_trail;
             }
 
             for i in 0..rush_dist as i32 {
@@ -1164,11 +1184,11 @@ impl Boss {
         world.bosses[index].position = new_pos;
         let world_map = &world.terrain_map[world.world_position.y][world.world_position.x];
         if  Self::pos_inside_boss(world, world.player.pos, world.world_position) {
-            world.player.damage(BOSS_COLLISION_DAMAGE);
+            world.player.damage(Damage::physical(world.balance.bosses.collision_damage));
             match direction {
                 Direction::North => {
                     if world_map.contains_key(&Position::new(world.player.pos.x, world.player.pos.y - 1)) {
-                        world.player.damage(MAX_PLAYER_HEALTH);
+                        world.player.damage(Damage::physical(world.balance.player.max_health));
                     } else {
                         World::update_position(world, world.player.pos, 
                             (Position::new(world.player.pos.x, world.player.pos.y - 1), 
@@ -1178,7 +1198,7 @@ impl Boss {
                 },
                 Direction::South => {
                     if world_map.contains_key(&Position::new(world.player.pos.x, world.player.pos.y + 1)) {
-                        world.player.damage(MAX_PLAYER_HEALTH);
+                        world.player.damage(Damage::physical(world.balance.player.max_health));
                     } else {
                         World::update_position(world, world.player.pos, 
                             (Position::new(world.player.pos.x, world.player.pos.y + 1), 
@@ -1188,7 +1208,7 @@ impl Boss {
                 },
                 Direction::East => {
                     if world_map.contains_key(&Position::new(world.player.pos.x + 1, world.player.pos.y)) {
-                        world.player.damage(MAX_PLAYER_HEALTH);
+                        world.player.damage(Damage::physical(world.balance.player.max_health));
                     } else {
                         World::update_position(world, world.player.pos, 
                             (Position::new(world.player.pos.x + 1, world.player.pos.y), 
@@ -1198,7 +1218,7 @@ impl Boss {
                 },
                 Direction::West => {
                     if world_map.contains_key(&Position::new(world.player.pos.x - 1, world.player.pos.y )) {
-                        world.player.damage(MAX_PLAYER_HEALTH);
+                        world.player.damage(Damage::physical(world.balance.player.max_health));
                     } else {
                         World::update_position(world, world.player.pos, 
                             (Position::new(world.player.pos.x - 1, world.player.pos.y), 
@@ -1233,10 +1253,20 @@ impl Boss {
         return (hit, can_hit);
     }
     
-    pub fn damage(world: &mut World, damage: usize, world_pos: Position) {
+    pub fn apply_status(world: &mut World, effect: StatusEffect, world_pos: Position) {
+        for boss in &mut world.bosses {
+            if boss.world_position == world_pos {
+                boss.status.apply(effect);
+            }
+        }
+    }
+
+    // The damage is scaled by how much of its kind the boss resists
+    pub fn damage(world: &mut World, damage: Damage, world_pos: Position) {
         for index in 0..world.bosses.len() {
             let boss = &mut world.bosses[index];
             if boss.world_position == world_pos {
+                let damage = world.balance.bosses.resistances(boss.kind).scale(damage);
                 boss.health = max(0, boss.health as i32 - damage as i32) as usize;
             }
         }
@@ -1,15 +1,19 @@
 use crate::{
+    balance::{Balance, BossBalance},
+    damage::Damage,
     direction::Direction,
+    elite::{self, ELITE_ROOMS},
     enemy::Enemy,
-    entity::Entity,
+    entity::{Entity, Faction},
+    pathfinding::Footprint,
     player::Player,
     projectile::Projectile,
     random,
-    tile::{self, FLOOR, PLAYER, *},
+    status::StatusKind,
+    tile::{AttackStage, BossKind, EnemyKind, Palette, TerrainKind, TileKind},
     utils::Boss,
     utils::Position,
     BOARD_SIZE, TILE_SIZE, UNIVERSAL_OFFSET, WORLD_SIZE,
-    player::PLAYER_PROJECTILE_DAMAGE,
 };
 use serde::ser::{Serialize, SerializeStruct, Serializer};
 
@@ -29,9 +33,6 @@ pub const BOSS_ROOMS: [Position; 5] = [
     Position::new(5, 5),
 ];
 pub const FINAL_BOSS_ROOM: Position = Position::new(3, 3);
-const LAKES_PER_WORLD: i16 = 3;
-const TOTAL_MOUNTAINS: i16 = 60;
-const ENEMY_COUNT: usize = 500;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
 pub struct World {
@@ -65,52 +66,83 @@ pub struct World {
     // list of all the projectiles in the world
     pub projectiles: Vec<Projectile>,
 
-    // Hashmap of positions to colors
-    // #[serde(with = "vectorize")]
-    pub entity_map: [[HashMap<Position, ([f32; 4], Entity)>;
+    // What is at each position of each room. The entity map holds the player, enemies and the
+    // player's projectiles, the terrain map the ground and the atmosphere map the spells and
+    // structures that sit on top of both. Positions missing from the terrain map are plain floor
+    #[serde(rename = "entities", default)]
+    pub entity_map: [[HashMap<Position, (TileKind, Entity)>;
         (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
         (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
-    // #[serde(with = "vectorize")]
-    pub terrain_map: [[HashMap<Position, [f32; 4]>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
+    #[serde(rename = "terrain", default)]
+    pub terrain_map: [[HashMap<Position, TerrainKind>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
         (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
-    // #[serde(with = "vectorize")]
-    pub atmosphere_map: [[HashMap<Position, [f32; 4]>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
+    #[serde(rename = "atmosphere", default)]
+    pub atmosphere_map: [[HashMap<Position, TileKind>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
         (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
     pub boss_defeated: [[bool; 7]; 7],
-    pub boss_lasers: Vec<(Position, [f32; 4], usize)>, //Position, color, duration left
-    pub boss_asteroids: Vec<(Position, [f32; 4], usize)>, //Position, color, duration left
+    #[serde(rename = "lasers", default)]
+    pub boss_lasers: Vec<(Position, AttackStage, usize)>, //Position, stage, duration left
+    #[serde(rename = "asteroids", default)]
+    pub boss_asteroids: Vec<(Position, AttackStage, usize)>, //Position, stage, duration left
     pub boss_column_laser: Option<(Position, Direction)>, //Position and direction laser will move
     pub boss_safe_spot: Option<(Position, usize, usize)>, //Center position, size of safe spot, safe spot time
     pub boss_vulnerable_spot: Option<Position>,
     pub in_blackout: bool,
-    pub stun_wells: Vec<(Position, [f32; 4], usize, usize, bool)>, //Position, color, size, duration left, currently in a well
-    pub bomber_explosions: [[Vec<(Position, [f32; 4])>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
+    #[serde(rename = "wells", default)]
+    pub stun_wells: Vec<(Position, AttackStage, usize, usize, bool)>, //Position, stage, size, duration left, currently in a well
+    // Position and ring of every explosion tile, the ring is how far from the bomber it is
+    #[serde(rename = "explosions", default)]
+    pub bomber_explosions: [[Vec<(Position, usize)>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
         (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
+    // The tuning numbers the game is played with. Not saved, it is set from the balance file
+    // every time a world is generated or loaded
+    #[serde(skip)]
+    pub balance: Balance,
+
+    // Up to save version 2 the maps and boss attacks stored colors instead of kinds, under these
+    // names. Only read by the migration, which converts them into the fields above
+    #[serde(rename = "entity_map", default, skip_serializing)]
+    pub legacy_entity_map: [[HashMap<Position, ([f32; 4], Entity)>;
+        (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
+        (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
+    #[serde(rename = "terrain_map", default, skip_serializing)]
+    pub legacy_terrain_map: [[HashMap<Position, [f32; 4]>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
+        (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
+    #[serde(rename = "atmosphere_map", default, skip_serializing)]
+    pub legacy_atmosphere_map: [[HashMap<Position, [f32; 4]>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
+        (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
+    #[serde(rename = "boss_lasers", default, skip_serializing)]
+    pub legacy_boss_lasers: Vec<(Position, [f32; 4], usize)>,
+    #[serde(rename = "boss_asteroids", default, skip_serializing)]
+    pub legacy_boss_asteroids: Vec<(Position, [f32; 4], usize)>,
+    #[serde(rename = "stun_wells", default, skip_serializing)]
+    pub legacy_stun_wells: Vec<(Position, [f32; 4], usize, usize, bool)>,
 }
 
 impl World {
-    pub fn new(rng: &mut ChaCha8Rng) -> Self {
-        let mut entity_map: [[HashMap<Position, ([f32; 4], Entity)>;
+    pub fn new(rng: &mut ChaCha8Rng, balance: &Balance) -> Self {
+        let mut entity_map: [[HashMap<Position, (TileKind, Entity)>;
             (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
             (BOARD_SIZE.1 / WORLD_SIZE.1) as usize] = Default::default();
-        let mut terrain_map: [[HashMap<Position, [f32; 4]>; (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
+        let mut terrain_map: [[HashMap<Position, TerrainKind>; (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
             (BOARD_SIZE.1 / WORLD_SIZE.1) as usize] = Default::default();
         let mut boss_defeated = [[false; 7]; 7];
         World::gen_boss(&mut terrain_map);
         World::gen_outer_boss_walls(&mut terrain_map);
-        World::gen_mountain(rng, &mut terrain_map);
-        World::gen_lake(rng, &mut terrain_map);
+        World::gen_mountain(rng, &mut terrain_map, balance.world.mountains);
+        World::gen_lake(rng, &mut terrain_map, balance.world.lakes_per_room);
         // World::add_doors(&mut terrain_map);
-        let player = Player::new();
+        let player = Player::new(&balance.player);
         let starting_map = &mut entity_map[player.pos.y][player.pos.x];
-        starting_map.insert(player.pos, (player.color, Entity::Player));
+        starting_map.insert(player.pos, (TileKind::Player, Entity::Player));
         let mut enemies_map = Default::default();
         let mut bosses = Vec::new();
-        let mut bomber_explosions: [[Vec<(Position, [f32; 4])>;
+        let mut bomber_explosions: [[Vec<(Position, usize)>;
             (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
             (BOARD_SIZE.1 / WORLD_SIZE.1) as usize] = Default::default();
-        World::gen_enemies(rng, &mut terrain_map, &mut entity_map, &mut enemies_map);
-        World::gen_bosses(&mut terrain_map, &mut entity_map, &mut bosses);
+        World::gen_enemies(rng, &mut terrain_map, &mut entity_map, &mut enemies_map, balance);
+        elite::gen_elites(rng, &terrain_map, &mut entity_map, &mut enemies_map, balance);
+        World::gen_bosses(&mut terrain_map, &mut entity_map, &mut bosses, &balance.bosses);
         World {
             world_position: Position::new(0, 0),
             top_left: (0, 0),
@@ -133,20 +165,55 @@ impl World {
             boss_vulnerable_spot: None,
             in_blackout: false,
             bomber_explosions,
+            balance: balance.clone(),
+            legacy_entity_map: Default::default(),
+            legacy_terrain_map: Default::default(),
+            legacy_atmosphere_map: Default::default(),
+            legacy_boss_lasers: Vec::new(),
+            legacy_boss_asteroids: Vec::new(),
+            legacy_stun_wells: Vec::new(),
         }
     }
 
+    // The first room with nothing in it but the player at pos, for tests to build small scenes on
+    #[cfg(test)]
+    pub fn empty_room(pos: Position) -> Self {
+        let mut balance = Balance::default();
+        balance.world.enemy_count = 0;
+        balance.world.mountains = 0;
+        balance.world.lakes_per_room = 0;
+        let mut world = World::new(&mut ChaCha8Rng::seed_from_u64(0), &balance);
+        world.balance = Balance::default();
+        world.terrain_map[0][0].clear();
+        let (start, room) = (world.player.pos, world.world_position);
+        World::update_position(&mut world, start, (pos, room));
+        world.player.pos = pos;
+        world
+    }
+
+    // Puts the enemy into its room, on every tile it covers
+    #[cfg(test)]
+    pub fn place_enemy(&mut self, enemy: Enemy) {
+        let room = enemy.world_pos;
+        for tile in &enemy.pos {
+            self.entity_map[room.y][room.x].insert(*tile, (TileKind::Enemy(enemy.kind), Entity::Enemy));
+        }
+        self.enemies_map[room.y][room.x].push(enemy);
+    }
+
     pub fn gen_enemies(
         rng: &mut ChaCha8Rng,
-        terrain_map: &mut [[HashMap<Position, [f32; 4]>; (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
+        terrain_map: &mut [[HashMap<Position, TerrainKind>; (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
                  (BOARD_SIZE.1 / WORLD_SIZE.1) as usize],
 
-        entity_map: &mut [[HashMap<Position, ([f32; 4], Entity)>; (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
+        entity_map: &mut [[HashMap<Position, (TileKind, Entity)>; (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
                  (BOARD_SIZE.1 / WORLD_SIZE.1) as usize],
 
         enemies_map: &mut [[Vec<Enemy>; (BOARD_SIZE.0/WORLD_SIZE.0) as usize]; (BOARD_SIZE.1/WORLD_SIZE.1) as usize],
+
+        balance: &Balance,
     ) {
-        for _ in 0..ENEMY_COUNT {
+        for _ in 0..balance.world.enemy_count {
             // the loop just generates new positions until it finds an open one, and it inserts an
             // enemy there
             loop {
@@ -176,31 +243,57 @@ impl World {
                     && ((world_x, world_y) != (0, 0) || ((x > 5) && y > 5))
                 {
                     if random::bernoulli(rng, 0.4) {
-                        world_map_entity.insert(random_loc, (tile::CHASING_ENEMY, Entity::Enemy));
+                        world_map_entity.insert(random_loc, (TileKind::Enemy(EnemyKind::Chaser), Entity::Enemy));
                         enemies_map[world_y as usize][world_x as usize].push(Enemy::chasing(
                             x as usize,
                             y as usize,
                             Position::new(world_x as usize, world_y as usize),
+                            &balance.enemies,
+                        ));
+                        break;
+                    } else if random::bernoulli(rng, 0.3) {
+                        world_map_entity.insert(random_loc, (TileKind::Enemy(EnemyKind::Shooter), Entity::Enemy));
+                        enemies_map[world_y as usize][world_x as usize].push(Enemy::shooting_enemy(
+                            x as usize,
+                            y as usize,
+                            Position::new(world_x as usize, world_y as usize),
+                            &balance.enemies,
+                        ));
+                        break;
+                    } else if random::bernoulli(rng, 0.25) {
+                        world_map_entity.insert(random_loc, (TileKind::Enemy(EnemyKind::Knight), Entity::Enemy));
+                        enemies_map[world_y as usize][world_x as usize].push(Enemy::knight(
+                            x as usize,
+                            y as usize,
+                            Position::new(world_x as usize, world_y as usize),
+                            &balance.enemies,
                         ));
                         break;
                     } else if random::bernoulli(rng, 0.5){
-                        world_map_entity.insert(random_loc, (tile::BOMBER_ENEMY, Entity::Enemy));
+                        world_map_entity.insert(random_loc, (TileKind::Enemy(EnemyKind::Bomber), Entity::Enemy));
                         enemies_map[world_y as usize][world_x as usize].push(Enemy::bomber(
                             x as usize,
                             y as usize,
                             Position::new(world_x as usize, world_y as usize),
+                            &balance.enemies,
                         ));
                         break;
-                    } else if x < WORLD_SIZE.0 - 2 && y < WORLD_SIZE.0 - 2 {
-                        for i in 0..3 {
-                            for j in 0..3 {
-                                world_map_entity.insert(Position::new(x as usize + i, y as usize + j), (tile::MAJOR_ENEMY, Entity::Enemy));
-                            }
+                    } else if x < WORLD_SIZE.0 - 2
+                        && y < WORLD_SIZE.1 - 2
+                        // all nine tiles have to be free, not just the top left one
+                        && Footprint::MAJOR.tiles(random_loc).all(|tile| {
+                            !world_map_terrain.contains_key(&tile)
+                                && !world_map_entity.contains_key(&tile)
+                        })
+                    {
+                        for tile in Footprint::MAJOR.tiles(random_loc) {
+                            world_map_entity.insert(tile, (TileKind::Enemy(EnemyKind::Major), Entity::Enemy));
                         }
                         enemies_map[world_y as usize][world_x as usize].push(Enemy::major_enemy(
                             x as usize,
                             y as usize,
                             Position::new(world_x as usize, world_y as usize),
+                            &balance.enemies,
                         ));
                         break;
                     } 
@@ -210,13 +303,15 @@ impl World {
     }
 
     pub fn gen_bosses(
-        terrain_map: &mut [[HashMap<Position, [f32; 4]>; (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
+        terrain_map: &mut [[HashMap<Position, TerrainKind>; (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
                  (BOARD_SIZE.1 / WORLD_SIZE.1) as usize],
 
-        entity_map: &mut [[HashMap<Position, ([f32; 4], Entity)>; (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
+        entity_map: &mut [[HashMap<Position, (TileKind, Entity)>; (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
                  (BOARD_SIZE.1 / WORLD_SIZE.1) as usize],
 
         bosses: &mut Vec<Boss>,
+
+        balance: &BossBalance,
     ) {
         for room_coord in BOSS_ROOMS {
             let world_map_terrain = &mut terrain_map[room_coord.y][room_coord.x];
@@ -226,38 +321,39 @@ impl World {
                 bosses.push(Boss::new(
                     x as usize,
                     y as usize,
-                    tile::MAJOR_BOSS,
+                    BossKind::Major,
                     room_coord,
                     world_map_terrain,
+                    balance,
                 ));
             } else {
-                let mut boss_tile = tile::LASER_BOSS;
+                let mut boss_kind = BossKind::Laser;
                 if room_coord == BOSS_ROOMS[1] {
-                    boss_tile = tile::COLUMN_BOSS;
+                    boss_kind = BossKind::Column;
                 } else if room_coord == BOSS_ROOMS[3] {
-                    boss_tile = tile::CHASING_BOSS;
+                    boss_kind = BossKind::Chasing;
                 } else if room_coord == BOSS_ROOMS[4] {
-                    boss_tile = tile::BLACK_OUT_BOSS;
+                    boss_kind = BossKind::BlackOut;
                 } 
                 bosses.push(Boss::new(
                     x as usize,
                     y as usize,
-                    boss_tile,
+                    boss_kind,
                     room_coord,
                     world_map_terrain,
+                    balance,
                 ));
             }
         }
     }
 
     //Draws the map on the top right and corner of the world
-    pub fn draw_world_map(&self, canvas: &mut graphics::Canvas) {
+    pub fn draw_world_map(&self, canvas: &mut graphics::Canvas, palette: &Palette) {
         //Get number of cells on each x and y axis
         let mut x = BOARD_SIZE.0 as usize / WORLD_SIZE.0 as usize;
         let mut y = BOARD_SIZE.1 as usize / WORLD_SIZE.0 as usize;
-        let player_indicator = [0.9, 0.1, 0.1, 1.0]; //Color of the dot on the map
-        const dungeon_indicator: [f32; 4] = [0.1, 0.5, 0.1, 1.0]; //Color of boss indicators
-        const boss_room_indicator: [f32; 4] = [0.1, 0.5, 0.1, 1.0]; //Color of boss indicators
+        let player_indicator = palette.map_player; //Color of the dot on the map
+        let dungeon_indicator = palette.map_boss_room; //Color of boss indicators
         for i in 0..(x * 6 - x + 1) {
             //Calculate length and iterate that many times
             for j in 0..(y * 6 - y + 1) {
@@ -301,6 +397,29 @@ impl World {
                 }
             }
         }
+        //Draw the rooms with an elite left in them, the same way
+        for position in ELITE_ROOMS {
+            if !elite::has_elite(self, position) {
+                continue;
+            }
+            x = 2 + (position.x as usize) * 5;
+            y = 2 + (position.y as usize) * 5;
+            for i in x..x + 2 {
+                for j in y..y + 2 {
+                    canvas.draw(
+                        &graphics::Quad,
+                        graphics::DrawParam::new()
+                            .dest_rect(graphics::Rect::new_i32(
+                                (i as i32 + 360) * 2 as i32,
+                                (j as i32 + 2) * 2 as i32,
+                                2,
+                                2,
+                            ))
+                            .color(palette.map_elite_room),
+                    )
+                }
+            }
+        }
         //Drawing the colored dot indicator
         //Get initial position at the corner of the cell
         x = 2 + (self.world_position.x as usize) * 5;
@@ -325,16 +444,17 @@ impl World {
     }
 
     //This function draws the whole entire world that is seen by the player
-    pub fn draw(&mut self, canvas: &mut graphics::Canvas, rng: &mut ChaCha8Rng) {
+    pub fn draw(&mut self, canvas: &mut graphics::Canvas, rng: &mut ChaCha8Rng, palette: &Palette) {
         //Draw lasers if in boss room
         if BOSS_ROOMS.contains(&self.world_position) {
             for index in 0..self.bosses.len() {
                 if self.bosses[index].world_position == self.world_position {
-                    Boss::draw_boss_stuff(self, canvas, index, rng);
+                    Boss::draw_boss_stuff(self, canvas, index, rng, palette);
                 }
             }
         }
-        Enemy::draw_bomber_explosion(self, canvas);
+        Enemy::draw_bomber_explosion(self, canvas, palette);
+        Enemy::draw_knight_lunges(self, canvas, palette);
 
         //Draw the black bar on top that has the health/energy indicators
         for i in 0..WORLD_SIZE.0 {
@@ -356,13 +476,14 @@ impl World {
         //Draw health and energy indicators
         self.player.draw_health(canvas);
         self.player.draw_energy(canvas);
-        self.draw_world_map(canvas);
+        self.player.draw_status(canvas);
+        self.draw_world_map(canvas, palette);
 
         //Draw every pixel that is contained in the terrain HashMap
         if !self.in_blackout {
             let curr_world_terrain_map =
                 &self.terrain_map[self.world_position.y][self.world_position.x];
-            for (loc, color) in curr_world_terrain_map {
+            for (loc, kind) in curr_world_terrain_map {
                 canvas.draw(
                     &graphics::Quad,
                     graphics::DrawParam::new()
@@ -372,25 +493,25 @@ impl World {
                             TILE_SIZE.0 as i32,
                             TILE_SIZE.1 as i32,
                         ))
-                        .color(Self::related_color(rng, *color)),
+                        .color(Self::related_color(rng, *kind, palette.terrain(*kind))),
                 )
             }
         }
 
         //Draw every pixel that is contained in the entity HashMap
         let curr_world_entity_map = &self.entity_map[self.world_position.y][self.world_position.x];
-        for (loc, color) in curr_world_entity_map {
-            let mut color = color.0;
-            if color == tile::PLAYER {
-                color = if self.player.stun_timer > 0 {
-                    tile::PLAYER_STUNNED
+        for (loc, (kind, _)) in curr_world_entity_map {
+            let mut color = palette.tile(*kind);
+            if *kind == TileKind::Player {
+                color = if self.player.status.has(StatusKind::Stun) {
+                    palette.player_stunned
                 } else if self.player.is_visible() {
-                    tile::PLAYER
+                    palette.player
                 } else {
-                    tile::PLAYER_INVISIBLE
+                    palette.player_invisible
                 }
             }
-            if !self.in_blackout || color == tile::PLAYER || color == tile::PLAYER_INVISIBLE {
+            if !self.in_blackout || *kind == TileKind::Player {
                 canvas.draw(
                     &graphics::Quad,
                     graphics::DrawParam::new()
@@ -410,11 +531,15 @@ impl World {
             }
         }
 
+        if !self.in_blackout {
+            elite::draw_health_bars(self, canvas, palette);
+        }
+
         //Draw every pixel that is contained in the terrain HashMap
         if !self.in_blackout {
             let curr_world_atmosphere_map =
                 &self.atmosphere_map[self.world_position.y][self.world_position.x];
-            for (loc, color) in curr_world_atmosphere_map {
+            for (loc, kind) in curr_world_atmosphere_map {
                 canvas.draw(
                     &graphics::Quad,
                     graphics::DrawParam::new()
@@ -424,13 +549,13 @@ impl World {
                             TILE_SIZE.0 as i32,
                             TILE_SIZE.1 as i32,
                         ))
-                        .color(*color),
+                        .color(palette.tile(*kind)),
                 )
             }
             if BOSS_ROOMS.contains(&self.world_position) {
                 for index in 0..self.bosses.len() {
                     if self.bosses[index].world_position == self.world_position {
-                        Boss::draw_boss(self, canvas, index);
+                        Boss::draw_boss(self, canvas, index, palette);
                     }
                 }
             }
@@ -464,9 +589,9 @@ impl World {
         let curr_world = &mut world.entity_map[new_position_info.1.y][new_position_info.1.x];
         let info = &curr_world.get(&prev_position); //Access contents of what was at previous position
         if let Some(contents) = info {
-            let tile_color = contents.0;
+            let tile_kind = contents.0;
             let tile_type = contents.1.clone();
-            curr_world.insert(new_position_info.0, (tile_color, tile_type)); //Insert same contents into new position
+            curr_world.insert(new_position_info.0, (tile_kind, tile_type)); //Insert same contents into new position
             curr_world.remove(&prev_position); //Remove old position
         }
     }
@@ -544,7 +669,7 @@ impl World {
                                     [world.world_position.x];
                                 curr_player_map.insert(
                                     Position::new(new_position.0.x, WORLD_SIZE.1 as usize - 1),
-                                    (tile::PLAYER, Entity::Player),
+                                    (TileKind::Player, Entity::Player),
                                 );
                                 world.player.pos =
                                     Position::new(new_position.0.x, WORLD_SIZE.1 as usize - 1);
@@ -556,7 +681,7 @@ impl World {
                                     [world.world_position.x];
                                 curr_player_map.insert(
                                     Position::new(0, new_position.0.y),
-                                    (tile::PLAYER, Entity::Player),
+                                    (TileKind::Player, Entity::Player),
                                 );
                                 world.player.pos = Position::new(0, new_position.0.y);
                             }
@@ -567,7 +692,7 @@ impl World {
                                     [world.world_position.x];
                                 curr_player_map.insert(
                                     Position::new(WORLD_SIZE.0 as usize - 1, new_position.0.y),
-                                    (tile::PLAYER, Entity::Player),
+                                    (TileKind::Player, Entity::Player),
                                 );
                                 world.player.pos =
                                     Position::new(WORLD_SIZE.0 as usize - 1, new_position.0.y);
@@ -579,7 +704,7 @@ impl World {
                                     [world.world_position.x];
                                 curr_player_map.insert(
                                     Position::new(new_position.0.x, 0),
-                                    (tile::PLAYER, Entity::Player),
+                                    (TileKind::Player, Entity::Player),
                                 );
                                 world.player.pos = Position::new(new_position.0.x, 0);
                             }
@@ -613,19 +738,33 @@ impl World {
                         return false;
                     }
                     let i = index.unwrap();
+                    let faction = world.projectiles[i].faction;
+                    //Enemy shots hit the player, the player's own projectiles never do
+                    if faction == Faction::Enemies
+                        && new_position.0 == world.player.pos
+                        && world.projectiles[i].world_pos == world.world_position
+                    {
+                        let hit = world.projectiles[i].hit();
+                        Enemy::shot_hit_player(world, hit);
+                        return false;
+                    }
                     for index in 0..world.enemies_map[world.world_position.y][world.world_position.x].len() {
-                        //Check if the projectile will hit an enemy, if so damage the enemy
+                        //Check if the projectile will hit an enemy, if so damage the enemy. Enemy
+                        //shots are stopped by other enemies without hurting them
                         if world.enemies_map[world.world_position.y][world.world_position.x][index].pos.contains(&new_position.0)
                             && new_position.1 == world.enemies_map[world.world_position.y][world.world_position.x][index].world_pos
                         {
-                            world.enemies_map[world.world_position.y][world.world_position.x][index].damage(world.projectiles[i].damage);
+                            if faction == Faction::Player {
+                                world.enemies_map[world.world_position.y][world.world_position.x][index].hit_by_projectile(world.projectiles[i].hit(), &world.balance.enemies);
+                            }
                             return false; //Will delete the projectile that hits the enemy
                         }
                     }
                     if BOSS_ROOMS.contains(&new_position.1) {
                         let hit_info = Boss::can_hit_boss(world, new_position.0, new_position.1);
-                        if hit_info.0 && hit_info.1 {
-                            Boss::damage(world, PLAYER_PROJECTILE_DAMAGE, new_position.1);
+                        if hit_info.0 && hit_info.1 && faction == Faction::Player {
+                            let damage = Damage::physical(world.balance.player.projectile_boss_damage);
+                            Boss::damage(world, damage, new_position.1);
                             return false;
                         } else if hit_info.1 {
                             return false;
@@ -779,7 +918,7 @@ impl World {
 
     // generates the center boss room for map
     pub fn gen_boss(
-        terrain_map: &mut [[HashMap<Position, [f32; 4]>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
+        terrain_map: &mut [[HashMap<Position, TerrainKind>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
                  (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
     ) {
         // x and y of center of map
@@ -792,7 +931,7 @@ impl World {
         for i in 0..12 {
             for j in 0..12 {
                 let loc = Position::new(x - 5 + i as usize, y - 5 + j as usize);
-                world_map.insert(loc, tile::WALL);
+                world_map.insert(loc, TerrainKind::Wall);
             }
         }
 
@@ -800,7 +939,7 @@ impl World {
         for i in 0..4 {
             for j in 0..4 {
                 let loc = Position::new(x - 1 + i as usize, y - 1 + j as usize);
-                world_map.insert(loc, tile::PORTAL);
+                world_map.insert(loc, TerrainKind::Portal);
             }
         }
     }
@@ -808,17 +947,18 @@ impl World {
     // generates water tiles around the map
     pub fn gen_lake(
         rng: &mut ChaCha8Rng,
-        terrain_map: &mut [[HashMap<Position, [f32; 4]>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
+        terrain_map: &mut [[HashMap<Position, TerrainKind>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
                  (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
+        lakes_per_room: i16,
     ) {
         for i in 0..7 {
             for j in 0..7 {
                 let mut lakes_added = 0;
-                while lakes_added < LAKES_PER_WORLD {
+                while lakes_added < lakes_per_room {
                     let x = random::rand_range(rng, 5, WORLD_SIZE.0); // random x coordinate
                     let y = random::rand_range(rng, 5, WORLD_SIZE.1); // random y coordinate
 
-                    let mut lake: HashMap<Position, [f32; 4]> = HashMap::new();
+                    let mut lake: HashMap<Position, TerrainKind> = HashMap::new();
                     Self::gen_lake_helper(
                         rng,
                         i * WORLD_SIZE.0 + x,
@@ -844,20 +984,20 @@ impl World {
         x: i16,
         y: i16,
         dist: i16,
-        terrain_map: &mut [[HashMap<Position, [f32; 4]>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
+        terrain_map: &mut [[HashMap<Position, TerrainKind>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
                  (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
-        lake: &mut HashMap<Position, [f32; 4]>,
+        lake: &mut HashMap<Position, TerrainKind>,
     ) {
         let pos = Position::new(x as usize, y as usize);
         if !Self::has_adjacent_terrain(x as usize, y as usize, &terrain_map) {
             // sets curr tile to water
             let world_loc = Position::new((x / WORLD_SIZE.0) as usize, (y / WORLD_SIZE.0) as usize);
 
-            let tile: [f32; 4];
+            let tile: TerrainKind;
             if BOSS_ROOMS.contains(&world_loc) {
-                tile = tile::LAVA;
+                tile = TerrainKind::Lava;
             } else {
-                tile = tile::WATER;
+                tile = TerrainKind::Water;
             }
 
             if !lake.contains_key(&pos) {
@@ -887,9 +1027,9 @@ impl World {
         random::bernoulli(rng, 1. - 0.15 * (dist as f32))
     }
 
-    // adds a little variability to lake color
-    pub fn related_color(rng: &mut ChaCha8Rng, color: [f32; 4]) -> [f32; 4] {
-        if color == tile::WATER {
+    // adds a little variability to the color of lakes and grass
+    pub fn related_color(rng: &mut ChaCha8Rng, kind: TerrainKind, color: [f32; 4]) -> [f32; 4] {
+        if kind == TerrainKind::Water {
             const MAX_DIFF: f32 = 0.05;
             return [
                 color[0] + random::rand_fraction(rng) * 2.0 * MAX_DIFF - MAX_DIFF,
@@ -897,7 +1037,7 @@ impl World {
                 color[2] + random::rand_fraction(rng) * 2.0 * MAX_DIFF - MAX_DIFF,
                 color[3],
             ];
-        } else if color == tile::LAVA {
+        } else if kind == TerrainKind::Lava {
             const MAX_DIFF_1: f32 = 0.01;
             const MAX_DIFF_2: f32 = 0.10;
             return [
@@ -906,7 +1046,7 @@ impl World {
                 color[2] + random::rand_fraction(rng) * 2.0 * MAX_DIFF_1 - MAX_DIFF_1,
                 color[3],
             ];
-        } else if color == tile::GRASS {
+        } else if kind == TerrainKind::Grass {
             const MAX_DIFF_1: f32 = 0.01;
             const MAX_DIFF_2: f32 = 0.10;
             return [
@@ -921,7 +1061,7 @@ impl World {
 
     //TODO: make faster, makes the game really slow rn
     fn gen_outer_boss_walls(
-        terrain_map: &mut [[HashMap<Position, [f32; 4]>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
+        terrain_map: &mut [[HashMap<Position, TerrainKind>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
                  (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
     ) {
         // the upper left corner of each mini boss room
@@ -940,49 +1080,49 @@ impl World {
                     [corner[0] as usize / WORLD_SIZE.0 as usize];
                 if i as i16 != WORLD_SIZE.0 / 2 - 1 && i as i16 != WORLD_SIZE.0 / 2 {
                     let mut loc = Position::new(0, i);
-                    world_map.insert(loc, tile::WALL);
+                    world_map.insert(loc, TerrainKind::Wall);
                     loc = Position::new(i, 0);
-                    world_map.insert(loc, tile::WALL);
+                    world_map.insert(loc, TerrainKind::Wall);
                     loc = Position::new(i, WORLD_SIZE.0 as usize - 1);
-                    world_map.insert(loc, tile::WALL);
+                    world_map.insert(loc, TerrainKind::Wall);
                     loc = Position::new(WORLD_SIZE.0 as usize - 1, i);
-                    world_map.insert(loc, tile::WALL);
+                    world_map.insert(loc, TerrainKind::Wall);
 
                     world_map = &mut terrain_map[corner[1] as usize / WORLD_SIZE.0 as usize]
                         [corner[0] as usize / WORLD_SIZE.0 as usize + 1];
                     loc = Position::new(0, i);
-                    world_map.insert(loc, tile::WALL);
+                    world_map.insert(loc, TerrainKind::Wall);
 
                     world_map = &mut terrain_map[corner[1] as usize / WORLD_SIZE.0 as usize]
                         [corner[0] as usize / WORLD_SIZE.0 as usize - 1];
                     loc = Position::new(WORLD_SIZE.0 as usize - 1, i);
-                    world_map.insert(loc, tile::WALL);
+                    world_map.insert(loc, TerrainKind::Wall);
 
                     world_map = &mut terrain_map[corner[1] as usize / WORLD_SIZE.0 as usize + 1]
                         [corner[0] as usize / WORLD_SIZE.0 as usize];
                     loc = Position::new(i, 0);
-                    world_map.insert(loc, tile::WALL);
+                    world_map.insert(loc, TerrainKind::Wall);
 
                     world_map = &mut terrain_map[corner[1] as usize / WORLD_SIZE.0 as usize - 1]
                         [corner[0] as usize / WORLD_SIZE.0 as usize];
                     loc = Position::new(i, WORLD_SIZE.1 as usize - 1);
-                    world_map.insert(loc, tile::WALL);
+                    world_map.insert(loc, TerrainKind::Wall);
                 }
 
                 // let mut loc = Position::new(corner[1] as usize, (corner[0] + i) as usize);
-                // terrain_positions.insert(loc, tile::WALL);
+                // terrain_positions.insert(loc, TerrainKind::Wall);
                 // loc = Position::new((corner[1] + WORLD_SIZE.1 - 1) as usize, (corner[0] + i) as usize);
-                // terrain_positions.insert(loc, tile::WALL);
+                // terrain_positions.insert(loc, TerrainKind::Wall);
                 // loc = Position::new((corner[1] + i) as usize, corner[0] as usize);
-                // terrain_positions.insert(loc, tile::WALL);
+                // terrain_positions.insert(loc, TerrainKind::Wall);
                 // loc = Position::new((corner[1] + i) as usize, (corner[0] + WORLD_SIZE.0 - 1) as usize);
-                // terrain_positions.insert(loc, tile::WALL);
+                // terrain_positions.insert(loc, TerrainKind::Wall);
 
                 // let mut loc = Position::new((corner[1] - 1) as usize, (corner[0] + i) as usize);
-                // terrain_positions.insert(loc, tile::WALL);
+                // terrain_positions.insert(loc, TerrainKind::Wall);
                 // loc = Position::new((corner[1] + WORLD_SIZE.1) as usize, (corner[0] + i) as usize);
-                // terrain_positions.insert(loc, tile::WALL);
-                // loc = Position::new((corner[1] + i) as usize, (corner[0] - 1) as usize); terrain_positions.insert(loc, tile::WALL); loc = Position::new((corner[1] + i) as usize, (corner[0] + WORLD_SIZE.0) as usize); terrain_positions.insert(loc, tile::WALL);
+                // terrain_positions.insert(loc, TerrainKind::Wall);
+                // loc = Position::new((corner[1] + i) as usize, (corner[0] - 1) as usize); terrain_positions.insert(loc, TerrainKind::Wall); loc = Position::new((corner[1] + i) as usize, (corner[0] + WORLD_SIZE.0) as usize); terrain_positions.insert(loc, TerrainKind::Wall);
             }
         }
         // in progress: creates a hole in the left wall of the upper left mini boss room
@@ -992,11 +1132,12 @@ impl World {
 
     pub fn gen_mountain(
         rng: &mut ChaCha8Rng,
-        terrain_map: &mut [[HashMap<Position, [f32; 4]>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
+        terrain_map: &mut [[HashMap<Position, TerrainKind>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
                  (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
+        mountains: i16,
     ) {
         let mut mountains_added = 0;
-        while mountains_added < TOTAL_MOUNTAINS {
+        while mountains_added < mountains {
             let x = random::rand_range(rng, 5, BOARD_SIZE.0); // random x coordinate
             let y = random::rand_range(rng, 5, BOARD_SIZE.1); // random y coordinate
 
@@ -1006,7 +1147,7 @@ impl World {
                 continue;
             }
 
-            let mut mountain: HashMap<Position, [f32; 4]> = HashMap::new();
+            let mut mountain: HashMap<Position, TerrainKind> = HashMap::new();
             Self::gen_mountain_helper(rng, x, y, 0, terrain_map, &mut mountain); // new lake centered at (x, y)
             if mountain.len() > 0 {
                 Self::combine_into_terrain(terrain_map, &mountain);
@@ -1023,14 +1164,14 @@ impl World {
         x: i16,
         y: i16,
         dist: i16,
-        terrain_map: &mut [[HashMap<Position, [f32; 4]>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
+        terrain_map: &mut [[HashMap<Position, TerrainKind>; (BOARD_SIZE.1 / WORLD_SIZE.1) as usize];
                  (BOARD_SIZE.0 / WORLD_SIZE.0) as usize],
-        mountain: &mut HashMap<Position, [f32; 4]>,
+        mountain: &mut HashMap<Position, TerrainKind>,
     ) {
         let pos = Position::new(x as usize, y as usize);
         if !Self::has_adjacent_terrain(x as usize, y as usize, &terrain_map) {
             // sets curr tile to water
-            let tile: [f32; 4] = tile::MOUNTAIN[min(4, (dist + 2) / 3) as usize];
+            let tile = TerrainKind::Mountain(min(4, (dist + 2) / 3) as u8);
 
             if !mountain.contains_key(&pos) {
                 mountain.insert(pos, tile);
@@ -1060,7 +1201,7 @@ impl World {
     }
 
     pub fn toggle_doors(
-        terrain_map: &mut [[HashMap<Position, [f32; 4]>; (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
+        terrain_map: &mut [[HashMap<Position, TerrainKind>; (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
                  (BOARD_SIZE.1 / WORLD_SIZE.1) as usize],
         world_loc: Position,
         loc: Position,
@@ -1091,10 +1232,10 @@ impl World {
                 let world_y = (world_loc.y as i16 + pos[1]) as usize;
                 let wall_pos = Position::new(y, x);
                 if !terrain_map[world_loc.y][world_loc.x].contains_key(&wall_pos) {
-                    terrain_map[world_loc.y][world_loc.x].insert(wall_pos, tile::WALL);
+                    terrain_map[world_loc.y][world_loc.x].insert(wall_pos, TerrainKind::Wall);
                 }
                 if !terrain_map[world_y][world_x].contains_key(&wall_pos) {
-                    terrain_map[world_y][world_x].insert(wall_pos, tile::WALL);
+                    terrain_map[world_y][world_x].insert(wall_pos, TerrainKind::Wall);
                 }
             }
         } else if boss_defeated[world_loc.y][world_loc.x] {
@@ -1133,10 +1274,10 @@ impl World {
                            let world_y = (3 + pos[1]) as usize;
                            let wall_pos = Position::new(y, x);
                            if !terrain_map[3][3].contains_key(&wall_pos) {
-                               terrain_map[3][3].insert(wall_pos, tile::WALL);
+                               terrain_map[3][3].insert(wall_pos, TerrainKind::Wall);
                            }
                            if !terrain_map[world_x][world_y].contains_key(&wall_pos) {
-                               terrain_map[world_x][world_y].insert(wall_pos, tile::WALL);
+                               terrain_map[world_x][world_y].insert(wall_pos, TerrainKind::Wall);
                            }
                        }
         }
@@ -1145,7 +1286,7 @@ impl World {
     fn has_adjacent_terrain(
         x: usize,
         y: usize,
-        terrain_map: &[[HashMap<Position, [f32; 4]>; (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
+        terrain_map: &[[HashMap<Position, TerrainKind>; (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
              (BOARD_SIZE.1 / WORLD_SIZE.1) as usize],
     ) -> bool {
         if x == 0 || x == BOARD_SIZE.0 as usize - 1 || y == 0 || y == BOARD_SIZE.1 as usize - 1 {
@@ -1178,7 +1319,7 @@ impl World {
             );
 
             if let Some(tile) = terrain_map[world_loc.y][world_loc.x].get(&loc) {
-                if *tile == tile::WALL {
+                if *tile == TerrainKind::Wall {
                     return true;
                 }
             }
@@ -1187,9 +1328,9 @@ impl World {
     }
 
     fn combine_into_terrain(
-        terrain_map: &mut [[HashMap<Position, [f32; 4]>; (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
+        terrain_map: &mut [[HashMap<Position, TerrainKind>; (BOARD_SIZE.0 / WORLD_SIZE.0) as usize];
                  (BOARD_SIZE.1 / WORLD_SIZE.1) as usize],
-        other: &HashMap<Position, [f32; 4]>,
+        other: &HashMap<Position, TerrainKind>,
     ) {
         for (pos, tile) in other {
             let world_loc = Position::new(
@@ -0,0 +1,45 @@
+use hoffman_final_project::balance::Balance;
+
+use std::fs;
+use std::path::PathBuf;
+
+// A directory of its own under the system's temporary directory for each test
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("balance_test_{}_{}", name, std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn missing_default_file_is_written_with_the_defaults() {
+    let path = temp_dir("written").join("balance.ron");
+    assert_eq!(Balance::load_or_create(&path), Ok(Balance::default()));
+    assert_eq!(Balance::load(&path), Ok(Balance::default()));
+}
+
+#[test]
+fn missing_file_given_explicitly_is_an_error() {
+    let path = temp_dir("missing").join("balance.ron");
+    assert!(Balance::load(&path).is_err());
+    assert!(!path.exists());
+}
+
+#[test]
+fn fields_left_out_keep_their_default() {
+    let path = temp_dir("partial").join("balance.ron");
+    fs::write(&path, "(player: (max_health: 150))").unwrap();
+    let balance = Balance::load(&path).unwrap();
+    assert_eq!(balance.player.max_health, 150);
+    assert_eq!(balance.player.max_energy, Balance::default().player.max_energy);
+    assert_eq!(balance.enemies, Balance::default().enemies);
+}
+
+#[test]
+fn misspelled_field_is_an_error() {
+    let path = temp_dir("misspelled").join("balance.ron");
+    fs::write(&path, "(player: (max_heatlh: 150))").unwrap();
+    assert!(Balance::load(&path).is_err());
+    fs::write(&path, "(enemies: (knight_resistances: (lightnig: 2.0)))").unwrap();
+    assert!(Balance::load(&path).is_err());
+}
@@ -0,0 +1,52 @@
+use hoffman_final_project::keymap::{Binding, Command, Keymap};
+
+// The default keymap, where Space fires a projectile, with Space also bound to moving up and to
+// melee as an edited keymap file could have it
+fn space_bound_three_times() -> (Keymap, Binding) {
+    let space: Binding = "Space".parse().unwrap();
+    let mut keymap = Keymap::default();
+    for command in [Command::MoveUp, Command::Melee] {
+        let mut bindings = keymap.bindings(command).to_vec();
+        bindings.push(space);
+        keymap = with_bindings(&keymap, command, bindings);
+    }
+    (keymap, space)
+}
+
+// The keymap with the command's bindings replaced, going through the file format since that is
+// the only way to end up with a binding on several commands
+fn with_bindings(keymap: &Keymap, command: Command, bindings: Vec<Binding>) -> Keymap {
+    let mut file: std::collections::BTreeMap<Command, Vec<Binding>> =
+        ron::from_str(&ron::to_string(keymap).unwrap()).unwrap();
+    file.insert(command, bindings);
+    ron::from_str(&ron::to_string(&file).unwrap()).unwrap()
+}
+
+#[test]
+fn conflicts_with_lists_every_other_command() {
+    let (keymap, space) = space_bound_three_times();
+    assert_eq!(
+        keymap.conflicts_with(Command::Wait, space),
+        vec![Command::MoveUp, Command::Melee, Command::Projectile]
+    );
+}
+
+#[test]
+fn rebind_takes_the_binding_from_every_command() {
+    let (mut keymap, space) = space_bound_three_times();
+    keymap.rebind(Command::Wait, space);
+    assert_eq!(keymap.bindings(Command::Wait), &[space]);
+    assert!(keymap.conflicts_with(Command::Wait, space).is_empty());
+    assert!(!keymap.bindings(Command::MoveUp).contains(&space));
+    assert!(!keymap.bindings(Command::Melee).contains(&space));
+    assert!(!keymap.bindings(Command::Projectile).contains(&space));
+    assert!(keymap.conflicts().is_empty());
+}
+
+#[test]
+fn chord_commands_dont_conflict_with_the_others() {
+    let keymap = Keymap::default();
+    for binding in keymap.bindings(Command::Save) {
+        assert!(keymap.conflicts_with(Command::Save, *binding).is_empty());
+    }
+}
@@ -0,0 +1,173 @@
+use hoffman_final_project::{
+    ai::AiState,
+    balance::Balance,
+    damage::Damage,
+    enemy::Enemy,
+    save::{SaveFile, SAVE_VERSION},
+    tile::EnemyKind,
+    utils::Position,
+};
+
+use std::path::PathBuf;
+
+// Every save in fixtures/saves/ was written by (or converted to the format of) the version in its
+// directory name, and all of them hold the same freshly generated world: the player at full
+// health in the first room, every boss alive and the default 500 enemies. The first chaser of the
+// v5 world is a knight, with the projectile resistance knights were made with then
+
+fn fixture(version: u32) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("fixtures/saves")
+        .join(format!("v{}", version))
+}
+
+// Loads the fixture of the version, upgrades it and checks it came out as the same world
+fn check_fixture(version: u32) {
+    let mut save = SaveFile::read_unmigrated(&fixture(version))
+        .unwrap_or_else(|e| panic!("couldn't read the v{} fixture: {}", version, e));
+    assert_eq!(save.version, version);
+    save.migrate();
+    assert_eq!(save.version, SAVE_VERSION);
+
+    let world = &save.world;
+    assert_eq!(world.world_position, Position::new(0, 0));
+    assert_eq!(world.player.pos, Position::new(0, 0));
+    assert_eq!(world.player.health(), 100);
+    assert_eq!(world.player.energy(), 100);
+    assert!(world.player.is_alive());
+    assert_eq!(world.boss_defeated.iter().flatten().filter(|defeated| **defeated).count(), 0);
+    assert_eq!(world.bosses.len(), 5);
+
+    let enemies: Vec<_> = world.enemies_map.iter().flatten().flatten().collect();
+    assert_eq!(enemies.len(), 500);
+    // the kinds of saves from before enemies had one are worked out from their color
+    let majors = enemies.iter().filter(|enemy| enemy.kind == EnemyKind::Major).count();
+    assert!(majors > 0);
+    assert!(enemies.iter().all(|enemy| enemy.kind != EnemyKind::Major || enemy.pos.len() == 9));
+    assert!(enemies.iter().all(|enemy| enemy.kind == EnemyKind::Major || enemy.pos.len() == 1));
+}
+
+#[test]
+fn every_version_has_a_fixture() {
+    for version in 0..=SAVE_VERSION {
+        assert!(fixture(version).is_dir(), "fixtures/saves/v{} is missing", version);
+    }
+}
+
+#[test]
+fn v0() {
+    check_fixture(0);
+}
+
+#[test]
+fn v1() {
+    check_fixture(1);
+}
+
+#[test]
+fn v2() {
+    check_fixture(2);
+}
+
+#[test]
+fn v3() {
+    check_fixture(3);
+}
+
+#[test]
+fn v4() {
+    check_fixture(4);
+}
+
+#[test]
+fn v5() {
+    check_fixture(5);
+}
+
+// Upgrades the v5 fixture with the balance the game would continue it with
+fn migrate_v5(balance: &Balance) -> SaveFile {
+    let mut save = SaveFile::read_unmigrated(&fixture(5)).unwrap();
+    save.world.balance = balance.clone();
+    save.migrate();
+    save
+}
+
+fn first(save: &SaveFile, kind: EnemyKind) -> Enemy {
+    save.world.enemies_map[0][0]
+        .iter()
+        .find(|enemy| enemy.kind == kind)
+        .cloned()
+        .unwrap_or_else(|| panic!("the v5 fixture has a {:?}", kind))
+}
+
+// The knight keeps its resistance to projectiles, and only to projectiles, even when the balance
+// no longer gives knights one. Enemies with the resistance of their kind go by the balance
+#[test]
+fn v5_knight_keeps_its_projectile_resistance() {
+    let mut balance = Balance::default();
+    balance.enemies.knight_resistances.projectile = 1.0;
+    let save = migrate_v5(&balance);
+    let knight = first(&save, EnemyKind::Knight);
+    assert_eq!(knight.projectile_resistance, Some(0.5));
+    assert_eq!(first(&save, EnemyKind::Chaser).projectile_resistance, None);
+
+    let mut shot = knight.clone();
+    shot.hit_by_projectile(Damage::physical(20), &balance.enemies);
+    assert_eq!(shot.health(), knight.health() - 10);
+    let mut hit = knight.clone();
+    hit.damage(Damage::physical(20), &balance.enemies);
+    assert_eq!(hit.health(), knight.health() - 20);
+
+    // with the default balance its resistance is just the one of its kind
+    let save = migrate_v5(&Balance::default());
+    assert_eq!(first(&save, EnemyKind::Knight).projectile_resistance, None);
+    assert_eq!(first(&save, EnemyKind::Chaser).projectile_resistance, None);
+}
+
+#[test]
+fn v6() {
+    check_fixture(6);
+}
+
+#[test]
+fn v7() {
+    check_fixture(7);
+}
+
+#[test]
+fn v8() {
+    check_fixture(8);
+}
+
+// Every enemy of the player's room went after them before enemies had an AI state, so a fight
+// that was going on when the game was saved is still going on once it is loaded
+#[test]
+fn v8_enemies_in_the_players_room_keep_chasing() {
+    let mut save = SaveFile::read_unmigrated(&fixture(8)).unwrap();
+    save.migrate();
+    let world = &save.world;
+    let room = world.world_position;
+    assert!(!world.enemies_map[room.y][room.x].is_empty());
+    for enemy in &world.enemies_map[room.y][room.x] {
+        assert_eq!(enemy.ai, AiState::Chase);
+        assert_eq!(enemy.last_seen, Some(world.player.pos));
+    }
+    let elsewhere = world
+        .enemies_map
+        .iter()
+        .flatten()
+        .flatten()
+        .filter(|enemy| enemy.world_pos != room);
+    assert!(elsewhere.clone().count() > 0);
+    assert!(elsewhere.clone().all(|enemy| enemy.ai == AiState::Idle && enemy.last_seen.is_none()));
+}
+
+#[test]
+fn v9() {
+    check_fixture(9);
+}
+
+#[test]
+fn v10() {
+    check_fixture(10);
+}